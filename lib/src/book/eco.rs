@@ -0,0 +1,361 @@
+use std::{collections::HashMap, sync::LazyLock};
+
+use crate::game::{
+    game::{Game, GameStart},
+    lan::lan_try_of,
+    mode::standard_chess,
+    rule::{
+        init::game_of_mode,
+        move_piece::{play_game_move, play_move},
+    },
+    zobrist::GameHash,
+};
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct EcoOpening {
+    pub code: &'static str,
+    pub name: &'static str,
+    pub moves: &'static str,
+}
+
+impl EcoOpening {
+    const fn of(code: &'static str, name: &'static str, moves: &'static str) -> Self {
+        EcoOpening { code, name, moves }
+    }
+}
+
+pub static ECO_OPENINGS: &[EcoOpening] = &[
+    EcoOpening::of("A00", "Polish Opening", "b2b4"),
+    EcoOpening::of("A00", "Grob Opening", "g2g4"),
+    EcoOpening::of("A00", "Van't Kruijs Opening", "e2e3"),
+    EcoOpening::of("A00", "Mieses Opening", "d2d3"),
+    EcoOpening::of("A00", "Hungarian Opening", "g2g3"),
+    EcoOpening::of("A00", "Saragossa Opening", "c2c3"),
+    EcoOpening::of("A00", "Amar Opening", "g1h3"),
+    EcoOpening::of("A00", "Durkin Opening", "b1a3"),
+    EcoOpening::of("A00", "Ware Opening", "a2a4"),
+    EcoOpening::of("A00", "Barnes Opening", "f2f3"),
+    EcoOpening::of("A00", "Barnes Opening: Fool's Mate", "f2f3 e7e5 g2g4 d8h4"),
+    EcoOpening::of("A01", "Nimzo-Larsen Attack", "b2b3"),
+    EcoOpening::of("A02", "Bird Opening", "f2f4"),
+    EcoOpening::of("A03", "Bird Opening: Dutch Variation", "f2f4 d7d5"),
+    EcoOpening::of("A04", "Zukertort Opening", "g1f3"),
+    EcoOpening::of("A07", "King's Indian Attack", "g1f3 d7d5 g2g3"),
+    EcoOpening::of("A09", "Réti Opening", "g1f3 d7d5 c2c4"),
+    EcoOpening::of("A10", "English Opening", "c2c4"),
+    EcoOpening::of("A13", "English Opening: Agincourt Defense", "c2c4 e7e6"),
+    EcoOpening::of("A15", "English Opening: Anglo-Indian Defense", "c2c4 g8f6"),
+    EcoOpening::of("A20", "English Opening: King's English Variation", "c2c4 e7e5"),
+    EcoOpening::of("A30", "English Opening: Symmetrical Variation", "c2c4 c7c5"),
+    EcoOpening::of("A40", "Queen's Pawn Game", "d2d4"),
+    EcoOpening::of("A40", "Englund Gambit", "d2d4 e7e5"),
+    EcoOpening::of("A43", "Old Benoni Defense", "d2d4 c7c5"),
+    EcoOpening::of("A45", "Indian Defense", "d2d4 g8f6"),
+    EcoOpening::of("A45", "Trompowsky Attack", "d2d4 g8f6 c1g5"),
+    EcoOpening::of("A46", "Indian Defense: Knights Variation", "d2d4 g8f6 g1f3"),
+    EcoOpening::of("A51", "Budapest Defense", "d2d4 g8f6 c2c4 e7e5"),
+    EcoOpening::of("A53", "Old Indian Defense", "d2d4 g8f6 c2c4 d7d6"),
+    EcoOpening::of("A56", "Benoni Defense", "d2d4 g8f6 c2c4 c7c5"),
+    EcoOpening::of("A57", "Benko Gambit", "d2d4 g8f6 c2c4 c7c5 d4d5 b7b5"),
+    EcoOpening::of("A60", "Benoni Defense: Modern Variation", "d2d4 g8f6 c2c4 c7c5 d4d5 e7e6"),
+    EcoOpening::of("A80", "Dutch Defense", "d2d4 f7f5"),
+    EcoOpening::of("A82", "Dutch Defense: Staunton Gambit", "d2d4 f7f5 e2e4"),
+    EcoOpening::of("B00", "King's Pawn Game", "e2e4"),
+    EcoOpening::of("B00", "Nimzowitsch Defense", "e2e4 b8c6"),
+    EcoOpening::of("B00", "Owen Defense", "e2e4 b7b6"),
+    EcoOpening::of("B01", "Scandinavian Defense", "e2e4 d7d5"),
+    EcoOpening::of("B01", "Scandinavian Defense: Mieses-Kotroc Variation", "e2e4 d7d5 e4d5 d8d5"),
+    EcoOpening::of("B01", "Scandinavian Defense: Modern Variation", "e2e4 d7d5 e4d5 g8f6"),
+    EcoOpening::of("B02", "Alekhine Defense", "e2e4 g8f6"),
+    EcoOpening::of(
+        "B03",
+        "Alekhine Defense: Four Pawns Attack",
+        "e2e4 g8f6 e4e5 f6d5 d2d4 d7d6 c2c4 d5b6 f2f4",
+    ),
+    EcoOpening::of(
+        "B04",
+        "Alekhine Defense: Modern Variation",
+        "e2e4 g8f6 e4e5 f6d5 d2d4 d7d6 g1f3",
+    ),
+    EcoOpening::of("B06", "Modern Defense", "e2e4 g7g6"),
+    EcoOpening::of("B07", "Pirc Defense", "e2e4 d7d6 d2d4 g8f6"),
+    EcoOpening::of("B09", "Pirc Defense: Austrian Attack", "e2e4 d7d6 d2d4 g8f6 b1c3 g7g6 f2f4"),
+    EcoOpening::of("B10", "Caro-Kann Defense", "e2e4 c7c6"),
+    EcoOpening::of("B12", "Caro-Kann Defense: Advance Variation", "e2e4 c7c6 d2d4 d7d5 e4e5"),
+    EcoOpening::of("B13", "Caro-Kann Defense: Exchange Variation", "e2e4 c7c6 d2d4 d7d5 e4d5 c6d5"),
+    EcoOpening::of("B15", "Caro-Kann Defense: Main Line", "e2e4 c7c6 d2d4 d7d5 b1c3"),
+    EcoOpening::of(
+        "B18",
+        "Caro-Kann Defense: Classical Variation",
+        "e2e4 c7c6 d2d4 d7d5 b1c3 d5e4 c3e4 c8f5",
+    ),
+    EcoOpening::of("B20", "Sicilian Defense", "e2e4 c7c5"),
+    EcoOpening::of("B21", "Sicilian Defense: Smith-Morra Gambit", "e2e4 c7c5 d2d4 c5d4 c2c3"),
+    EcoOpening::of("B22", "Sicilian Defense: Alapin Variation", "e2e4 c7c5 c2c3"),
+    EcoOpening::of("B23", "Sicilian Defense: Closed", "e2e4 c7c5 b1c3"),
+    EcoOpening::of("B27", "Sicilian Defense", "e2e4 c7c5 g1f3"),
+    EcoOpening::of("B30", "Sicilian Defense: Old Sicilian", "e2e4 c7c5 g1f3 b8c6"),
+    EcoOpening::of("B32", "Sicilian Defense: Open", "e2e4 c7c5 g1f3 b8c6 d2d4 c5d4 f3d4"),
+    EcoOpening::of(
+        "B33",
+        "Sicilian Defense: Sveshnikov Variation",
+        "e2e4 c7c5 g1f3 b8c6 d2d4 c5d4 f3d4 g8f6 b1c3 e7e5",
+    ),
+    EcoOpening::of("B40", "Sicilian Defense: French Variation", "e2e4 c7c5 g1f3 e7e6"),
+    EcoOpening::of(
+        "B44",
+        "Sicilian Defense: Taimanov Variation",
+        "e2e4 c7c5 g1f3 e7e6 d2d4 c5d4 f3d4 b8c6",
+    ),
+    EcoOpening::of("B50", "Sicilian Defense: Modern Variations", "e2e4 c7c5 g1f3 d7d6"),
+    EcoOpening::of("B54", "Sicilian Defense: Open", "e2e4 c7c5 g1f3 d7d6 d2d4 c5d4 f3d4"),
+    EcoOpening::of(
+        "B70",
+        "Sicilian Defense: Dragon Variation",
+        "e2e4 c7c5 g1f3 d7d6 d2d4 c5d4 f3d4 g8f6 b1c3 g7g6",
+    ),
+    EcoOpening::of(
+        "B80",
+        "Sicilian Defense: Scheveningen Variation",
+        "e2e4 c7c5 g1f3 d7d6 d2d4 c5d4 f3d4 g8f6 b1c3 e7e6",
+    ),
+    EcoOpening::of(
+        "B90",
+        "Sicilian Defense: Najdorf Variation",
+        "e2e4 c7c5 g1f3 d7d6 d2d4 c5d4 f3d4 g8f6 b1c3 a7a6",
+    ),
+    EcoOpening::of("C00", "French Defense", "e2e4 e7e6"),
+    EcoOpening::of("C01", "French Defense: Exchange Variation", "e2e4 e7e6 d2d4 d7d5 e4d5"),
+    EcoOpening::of("C02", "French Defense: Advance Variation", "e2e4 e7e6 d2d4 d7d5 e4e5"),
+    EcoOpening::of("C03", "French Defense: Tarrasch Variation", "e2e4 e7e6 d2d4 d7d5 b1d2"),
+    EcoOpening::of("C10", "French Defense: Paulsen Variation", "e2e4 e7e6 d2d4 d7d5 b1c3"),
+    EcoOpening::of("C11", "French Defense: Classical Variation", "e2e4 e7e6 d2d4 d7d5 b1c3 g8f6"),
+    EcoOpening::of("C15", "French Defense: Winawer Variation", "e2e4 e7e6 d2d4 d7d5 b1c3 f8b4"),
+    EcoOpening::of("C20", "King's Pawn Game", "e2e4 e7e5"),
+    EcoOpening::of("C21", "Danish Gambit", "e2e4 e7e5 d2d4 e5d4 c2c3"),
+    EcoOpening::of("C22", "Center Game", "e2e4 e7e5 d2d4 e5d4 d1d4"),
+    EcoOpening::of("C23", "Bishop's Opening", "e2e4 e7e5 f1c4"),
+    EcoOpening::of("C25", "Vienna Game", "e2e4 e7e5 b1c3"),
+    EcoOpening::of("C30", "King's Gambit", "e2e4 e7e5 f2f4"),
+    EcoOpening::of("C30", "King's Gambit Declined: Classical Variation", "e2e4 e7e5 f2f4 f8c5"),
+    EcoOpening::of("C33", "King's Gambit Accepted", "e2e4 e7e5 f2f4 e5f4"),
+    EcoOpening::of("C40", "King's Knight Opening", "e2e4 e7e5 g1f3"),
+    EcoOpening::of("C40", "Latvian Gambit", "e2e4 e7e5 g1f3 f7f5"),
+    EcoOpening::of("C41", "Philidor Defense", "e2e4 e7e5 g1f3 d7d6"),
+    EcoOpening::of("C42", "Petrov's Defense", "e2e4 e7e5 g1f3 g8f6"),
+    EcoOpening::of("C44", "King's Knight Opening: Normal Variation", "e2e4 e7e5 g1f3 b8c6"),
+    EcoOpening::of("C44", "Ponziani Opening", "e2e4 e7e5 g1f3 b8c6 c2c3"),
+    EcoOpening::of("C45", "Scotch Game", "e2e4 e7e5 g1f3 b8c6 d2d4"),
+    EcoOpening::of("C46", "Three Knights Opening", "e2e4 e7e5 g1f3 b8c6 b1c3"),
+    EcoOpening::of("C47", "Four Knights Game", "e2e4 e7e5 g1f3 b8c6 b1c3 g8f6"),
+    EcoOpening::of("C50", "Italian Game", "e2e4 e7e5 g1f3 b8c6 f1c4"),
+    EcoOpening::of("C50", "Italian Game: Hungarian Defense", "e2e4 e7e5 g1f3 b8c6 f1c4 f8e7"),
+    EcoOpening::of("C50", "Italian Game: Giuoco Piano", "e2e4 e7e5 g1f3 b8c6 f1c4 f8c5"),
+    EcoOpening::of("C50", "Italian Game: Giuoco Pianissimo", "e2e4 e7e5 g1f3 b8c6 f1c4 f8c5 d2d3"),
+    EcoOpening::of("C51", "Italian Game: Evans Gambit", "e2e4 e7e5 g1f3 b8c6 f1c4 f8c5 b2b4"),
+    EcoOpening::of("C55", "Italian Game: Two Knights Defense", "e2e4 e7e5 g1f3 b8c6 f1c4 g8f6"),
+    EcoOpening::of(
+        "C57",
+        "Italian Game: Two Knights Defense, Knight Attack",
+        "e2e4 e7e5 g1f3 b8c6 f1c4 g8f6 f3g5",
+    ),
+    EcoOpening::of(
+        "C57",
+        "Italian Game: Two Knights Defense, Fried Liver Attack",
+        "e2e4 e7e5 g1f3 b8c6 f1c4 g8f6 f3g5 d7d5 e4d5 f6d5 g5f7",
+    ),
+    EcoOpening::of("C60", "Ruy Lopez", "e2e4 e7e5 g1f3 b8c6 f1b5"),
+    EcoOpening::of("C62", "Ruy Lopez: Steinitz Defense", "e2e4 e7e5 g1f3 b8c6 f1b5 d7d6"),
+    EcoOpening::of("C63", "Ruy Lopez: Schliemann Defense", "e2e4 e7e5 g1f3 b8c6 f1b5 f7f5"),
+    EcoOpening::of("C65", "Ruy Lopez: Berlin Defense", "e2e4 e7e5 g1f3 b8c6 f1b5 g8f6"),
+    EcoOpening::of("C68", "Ruy Lopez: Exchange Variation", "e2e4 e7e5 g1f3 b8c6 f1b5 a7a6 b5c6"),
+    EcoOpening::of("C70", "Ruy Lopez: Morphy Defense", "e2e4 e7e5 g1f3 b8c6 f1b5 a7a6 b5a4"),
+    EcoOpening::of(
+        "C80",
+        "Ruy Lopez: Open Variation",
+        "e2e4 e7e5 g1f3 b8c6 f1b5 a7a6 b5a4 g8f6 e1g1 f6e4",
+    ),
+    EcoOpening::of(
+        "C84",
+        "Ruy Lopez: Closed Variation",
+        "e2e4 e7e5 g1f3 b8c6 f1b5 a7a6 b5a4 g8f6 e1g1 f8e7",
+    ),
+    EcoOpening::of(
+        "C89",
+        "Ruy Lopez: Marshall Attack",
+        "e2e4 e7e5 g1f3 b8c6 f1b5 a7a6 b5a4 g8f6 e1g1 f8e7 f1e1 b7b5 a4b3 e8g8 c2c3 d7d5",
+    ),
+    EcoOpening::of("D00", "Queen's Pawn Game", "d2d4 d7d5"),
+    EcoOpening::of("D00", "Blackmar-Diemer Gambit", "d2d4 d7d5 e2e4"),
+    EcoOpening::of("D00", "Queen's Pawn Game: Accelerated London System", "d2d4 d7d5 c1f4"),
+    EcoOpening::of("D02", "Queen's Pawn Game: Zukertort Variation", "d2d4 d7d5 g1f3"),
+    EcoOpening::of("D06", "Queen's Gambit", "d2d4 d7d5 c2c4"),
+    EcoOpening::of("D07", "Queen's Gambit Declined: Chigorin Defense", "d2d4 d7d5 c2c4 b8c6"),
+    EcoOpening::of("D08", "Queen's Gambit Declined: Albin Countergambit", "d2d4 d7d5 c2c4 e7e5"),
+    EcoOpening::of("D10", "Slav Defense", "d2d4 d7d5 c2c4 c7c6"),
+    EcoOpening::of("D20", "Queen's Gambit Accepted", "d2d4 d7d5 c2c4 d5c4"),
+    EcoOpening::of("D30", "Queen's Gambit Declined", "d2d4 d7d5 c2c4 e7e6"),
+    EcoOpening::of(
+        "D35",
+        "Queen's Gambit Declined: Exchange Variation",
+        "d2d4 d7d5 c2c4 e7e6 b1c3 g8f6 c4d5",
+    ),
+    EcoOpening::of("D43", "Semi-Slav Defense", "d2d4 d7d5 c2c4 c7c6 g1f3 g8f6 b1c3 e7e6"),
+    EcoOpening::of("D80", "Grünfeld Defense", "d2d4 g8f6 c2c4 g7g6 b1c3 d7d5"),
+    EcoOpening::of(
+        "D85",
+        "Grünfeld Defense: Exchange Variation",
+        "d2d4 g8f6 c2c4 g7g6 b1c3 d7d5 c4d5 f6d5",
+    ),
+    EcoOpening::of("E00", "Indian Defense: East Indian Defense", "d2d4 g8f6 c2c4 e7e6"),
+    EcoOpening::of("E01", "Catalan Opening", "d2d4 g8f6 c2c4 e7e6 g2g3"),
+    EcoOpening::of("E10", "Indian Defense: Anti-Nimzo-Indian", "d2d4 g8f6 c2c4 e7e6 g1f3"),
+    EcoOpening::of("E11", "Bogo-Indian Defense", "d2d4 g8f6 c2c4 e7e6 g1f3 f8b4"),
+    EcoOpening::of("E12", "Queen's Indian Defense", "d2d4 g8f6 c2c4 e7e6 g1f3 b7b6"),
+    EcoOpening::of("E20", "Nimzo-Indian Defense", "d2d4 g8f6 c2c4 e7e6 b1c3 f8b4"),
+    EcoOpening::of(
+        "E32",
+        "Nimzo-Indian Defense: Classical Variation",
+        "d2d4 g8f6 c2c4 e7e6 b1c3 f8b4 d1c2",
+    ),
+    EcoOpening::of("E60", "King's Indian Defense", "d2d4 g8f6 c2c4 g7g6"),
+    EcoOpening::of("E61", "King's Indian Defense", "d2d4 g8f6 c2c4 g7g6 b1c3 f8g7"),
+    EcoOpening::of(
+        "E70",
+        "King's Indian Defense: Normal Variation",
+        "d2d4 g8f6 c2c4 g7g6 b1c3 f8g7 e2e4 d7d6",
+    ),
+    EcoOpening::of(
+        "E76",
+        "King's Indian Defense: Four Pawns Attack",
+        "d2d4 g8f6 c2c4 g7g6 b1c3 f8g7 e2e4 d7d6 f2f4",
+    ),
+    EcoOpening::of(
+        "E80",
+        "King's Indian Defense: Sämisch Variation",
+        "d2d4 g8f6 c2c4 g7g6 b1c3 f8g7 e2e4 d7d6 f2f3",
+    ),
+    EcoOpening::of(
+        "E92",
+        "King's Indian Defense: Classical Variation",
+        "d2d4 g8f6 c2c4 g7g6 b1c3 f8g7 e2e4 d7d6 g1f3 e8g8 f1e2 e7e5",
+    ),
+];
+
+// openings are keyed by the position they reach, so move orders that transpose still match
+static ECO_POSITIONS: LazyLock<HashMap<GameHash, &'static EcoOpening>> = LazyLock::new(|| {
+    let mut positions = HashMap::new();
+    for opening in ECO_OPENINGS {
+        let mut game = game_of_mode(standard_chess());
+        for lan in opening.moves.split_whitespace() {
+            if let Some((from, to)) = lan_try_of(&game, lan) {
+                play_move(&mut game, &from, &to);
+            }
+        }
+        positions.entry(game.hash()).or_insert(opening);
+    }
+    positions
+});
+
+static ECO_MAX_PLY: LazyLock<usize> = LazyLock::new(|| {
+    ECO_OPENINGS.iter().map(|opening| opening.moves.split_whitespace().count()).max().unwrap_or(0)
+});
+
+pub fn eco_classify(game: &Game) -> Option<&'static EcoOpening> {
+    let mode = standard_chess();
+    if game.bounds != mode.bounds || game.start != GameStart::default() {
+        return None;
+    }
+    // replaying the whole history from the standard start also rules out custom initial boards
+    let mut replay = game_of_mode(mode);
+    let mut opening = None;
+    for game_move in &game.history {
+        play_game_move(&mut replay, game_move);
+        if replay.history.last() != Some(game_move) {
+            return None;
+        }
+        if replay.history.len() <= *ECO_MAX_PLY
+            && let Some(found) = ECO_POSITIONS.get(&replay.hash())
+        {
+            opening = Some(*found);
+        }
+    }
+    if replay.board != game.board {
+        return None;
+    }
+    opening
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use crate::{
+        game::{
+            fen::game_of_fen, game::Game, lan::lan_try_of, mode::standard_chess,
+            rule::init::game_of_mode, rule::move_piece::play_move,
+        },
+        pos::pos_of,
+    };
+
+    use super::{ECO_OPENINGS, eco_classify};
+
+    fn game_of_lans(lans: &str) -> Game {
+        let mut game = game_of_mode(standard_chess());
+        for lan in lans.split_whitespace() {
+            let (from, to) = lan_try_of(&game, lan).unwrap();
+            play_move(&mut game, &from, &to);
+        }
+        game
+    }
+
+    #[test]
+    fn eco_openings_are_legal() {
+        for opening in ECO_OPENINGS {
+            let game = game_of_lans(opening.moves);
+            assert_eq!(game.history.len(), opening.moves.split_whitespace().count());
+            assert_eq!(eco_classify(&game), Some(opening));
+        }
+    }
+
+    #[test]
+    fn eco_openings_are_unique() {
+        let moves: HashSet<&str> = ECO_OPENINGS.iter().map(|opening| opening.moves).collect();
+        assert_eq!(moves.len(), ECO_OPENINGS.len());
+    }
+
+    #[test]
+    fn eco_classify_deepest_match() {
+        assert_eq!(eco_classify(&game_of_lans("")), None);
+        assert_eq!(eco_classify(&game_of_lans("e2e4")).unwrap().code, "B00");
+        let sicilian = eco_classify(&game_of_lans("e2e4 c7c5 g1f3 d7d6 d2d4")).unwrap();
+        assert_eq!((sicilian.code, sicilian.name), ("B50", "Sicilian Defense: Modern Variations"));
+        let najdorf =
+            eco_classify(&game_of_lans("e2e4 c7c5 g1f3 d7d6 d2d4 c5d4 f3d4 g8f6 b1c3 a7a6 c1e3"))
+                .unwrap();
+        assert_eq!((najdorf.code, najdorf.name), ("B90", "Sicilian Defense: Najdorf Variation"));
+        let ruy =
+            eco_classify(&game_of_lans("e2e4 e7e5 g1f3 b8c6 f1b5 a7a6 b5a4 g8f6 e1g1 f8e7 f1e1"))
+                .unwrap();
+        assert_eq!(ruy.code, "C84");
+    }
+
+    #[test]
+    fn eco_classify_transposition() {
+        let kia = eco_classify(&game_of_lans("g2g3 d7d5 g1f3")).unwrap();
+        assert_eq!((kia.code, kia.name), ("A07", "King's Indian Attack"));
+        let budapest = eco_classify(&game_of_lans("c2c4 e7e5 d2d4 g8f6")).unwrap();
+        assert_eq!((budapest.code, budapest.name), ("A51", "Budapest Defense"));
+    }
+
+    #[test]
+    fn eco_classify_non_standard_start() {
+        let game = game_of_fen("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1");
+        assert_eq!(eco_classify(&game), None);
+        let mut mode = standard_chess();
+        mode.initial_board.remove(&pos_of("A1"));
+        let mut game = game_of_mode(mode);
+        play_move(&mut game, &pos_of("E2"), &pos_of("E4"));
+        assert_eq!(eco_classify(&game), None);
+    }
+}
@@ -0,0 +1,3 @@
+pub mod eco;
+mod keys;
+pub mod polyglot;
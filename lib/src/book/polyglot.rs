@@ -0,0 +1,324 @@
+use crate::{
+    color::Color,
+    engine::SearchMove,
+    game::{
+        game::{Game, GameBounds},
+        mov::{
+            GameMove, PieceMoveType, is_promotion,
+            special::{
+                castling::{long_castling_right, short_castling_right},
+                en_passant::en_passant_moves,
+            },
+        },
+        rule::turn::evaluate_turn,
+    },
+    piece::{Piece, PieceType},
+    pos::Pos,
+};
+
+use super::keys::{
+    POLYGLOT_CASTLING, POLYGLOT_EN_PASSANT, POLYGLOT_PIECE, POLYGLOT_RANDOM, POLYGLOT_TURN,
+};
+
+const ENTRY_SIZE: usize = 16;
+
+fn polyglot_piece_kind(piece: &Piece) -> usize {
+    let typ = match piece.typ {
+        PieceType::Pawn => 0,
+        PieceType::Knight => 1,
+        PieceType::Bishop => 2,
+        PieceType::Rook => 3,
+        PieceType::Queen => 4,
+        PieceType::King => 5,
+    };
+    match piece.color {
+        Color::Black => typ * 2,
+        Color::White => typ * 2 + 1,
+    }
+}
+
+pub fn polyglot_hash(game: &Game) -> Option<u64> {
+    if game.bounds != GameBounds::of(0, 0, 7, 7) {
+        return None;
+    }
+    let mut hash = 0;
+    for (pos, piece) in game.board.iter() {
+        let square = usize::from(pos.row) * 8 + usize::from(pos.col);
+        hash ^= POLYGLOT_RANDOM[POLYGLOT_PIECE + 64 * polyglot_piece_kind(piece) + square];
+    }
+    let castling = [
+        short_castling_right(&game.board, &game.start, &game.history, &Color::White),
+        long_castling_right(&game.board, &game.start, &game.history, &Color::White),
+        short_castling_right(&game.board, &game.start, &game.history, &Color::Black),
+        long_castling_right(&game.board, &game.start, &game.history, &Color::Black),
+    ];
+    for (i, right) in castling.iter().enumerate() {
+        if *right {
+            hash ^= POLYGLOT_RANDOM[POLYGLOT_CASTLING + i];
+        }
+    }
+    let turn = evaluate_turn(&game.start, &game.history);
+    let maybe_en_passant = game
+        .board
+        .iter()
+        .filter(|(_, piece)| piece.color == turn && piece.typ == PieceType::Pawn)
+        .flat_map(|(pos, _)| {
+            en_passant_moves(&game.board, &game.start, &game.history, pos).into_keys()
+        })
+        .next();
+    if let Some(to) = maybe_en_passant {
+        hash ^= POLYGLOT_RANDOM[POLYGLOT_EN_PASSANT + usize::from(to.col)];
+    }
+    if turn == Color::White {
+        hash ^= POLYGLOT_RANDOM[POLYGLOT_TURN];
+    }
+    Some(hash)
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct PolyglotEntry {
+    pub key: u64,
+    pub mov: u16,
+    pub weight: u16,
+    pub learn: u32,
+}
+
+impl PolyglotEntry {
+    pub fn to_bytes(&self) -> [u8; ENTRY_SIZE] {
+        let mut bytes = [0; ENTRY_SIZE];
+        bytes[0..8].copy_from_slice(&self.key.to_be_bytes());
+        bytes[8..10].copy_from_slice(&self.mov.to_be_bytes());
+        bytes[10..12].copy_from_slice(&self.weight.to_be_bytes());
+        bytes[12..16].copy_from_slice(&self.learn.to_be_bytes());
+        bytes
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct PolyglotBook {
+    entries: Vec<PolyglotEntry>,
+}
+
+impl PolyglotBook {
+    pub fn try_of(bytes: &[u8]) -> Option<Self> {
+        if !bytes.len().is_multiple_of(ENTRY_SIZE) {
+            return None;
+        }
+        let mut entries: Vec<PolyglotEntry> = bytes
+            .chunks_exact(ENTRY_SIZE)
+            .map(|chunk| PolyglotEntry {
+                key: u64::from_be_bytes(chunk[0..8].try_into().unwrap()),
+                mov: u16::from_be_bytes(chunk[8..10].try_into().unwrap()),
+                weight: u16::from_be_bytes(chunk[10..12].try_into().unwrap()),
+                learn: u32::from_be_bytes(chunk[12..16].try_into().unwrap()),
+            })
+            .collect();
+        entries.sort_by_key(|entry| entry.key);
+        Some(PolyglotBook { entries })
+    }
+
+    pub fn of(bytes: &[u8]) -> Self {
+        Self::try_of(bytes).unwrap()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn entries_of(&self, key: u64) -> &[PolyglotEntry] {
+        let start = self.entries.partition_point(|entry| entry.key < key);
+        let end = self.entries.partition_point(|entry| entry.key <= key);
+        &self.entries[start..end]
+    }
+}
+
+pub fn polyglot_move_squares(mov: u16) -> (Pos, Pos) {
+    let square = |bits: u16| Pos::of(((bits >> 3) & 7) as u8, (bits & 7) as u8);
+    (square(mov >> 6), square(mov))
+}
+
+pub fn polyglot_move_promotion(mov: u16) -> Option<PieceMoveType> {
+    match (mov >> 12) & 7 {
+        1 => Some(PieceMoveType::PromotionToKnight),
+        2 => Some(PieceMoveType::PromotionToBishop),
+        3 => Some(PieceMoveType::PromotionToRook),
+        4 => Some(PieceMoveType::PromotionToQueen),
+        _ => None,
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct BookMove {
+    pub from: Pos,
+    pub to: Pos,
+    pub weight: u16,
+    pub game_move: GameMove,
+}
+
+pub fn book_moves(book: &PolyglotBook, game: &Game) -> Vec<BookMove> {
+    let Some(key) = polyglot_hash(game) else {
+        return Vec::new();
+    };
+    let Some(player) = game.players.get(&evaluate_turn(&game.start, &game.history)) else {
+        return Vec::new();
+    };
+    let mut result: Vec<BookMove> = book
+        .entries_of(key)
+        .iter()
+        .filter_map(|entry| {
+            let (from, to) = polyglot_move_squares(entry.mov);
+            let mut typ = player.moves.get(&from)?.get(&to)?.clone();
+            if let Some(promotion) =
+                polyglot_move_promotion(entry.mov).filter(|_| is_promotion(&typ))
+            {
+                typ = promotion;
+            }
+            let game_move =
+                SearchMove { from: from.clone(), to: to.clone(), typ }.to_game_move(&game.board)?;
+            Some(BookMove { from, to, weight: entry.weight, game_move })
+        })
+        .collect();
+    result.sort_by_key(|mv| std::cmp::Reverse(mv.weight));
+    result
+}
+
+pub fn pick_book_move(moves: &[BookMove], random: u64) -> Option<&BookMove> {
+    let total: u64 = moves.iter().map(|mv| u64::from(mv.weight)).sum();
+    if total == 0 {
+        return moves.first();
+    }
+    let mut target = random % total;
+    for mv in moves {
+        if target < u64::from(mv.weight) {
+            return Some(mv);
+        }
+        target -= u64::from(mv.weight);
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        game::{
+            fen::game_of_fen,
+            game::Game,
+            lan::lan_try_of,
+            mode::standard_chess,
+            mov::{GameMove, PieceMoveType},
+            rule::init::game_of_mode,
+            rule::move_piece::play_move,
+        },
+        pos::pos_of,
+    };
+
+    use super::{
+        BookMove, PolyglotBook, PolyglotEntry, book_moves, pick_book_move, polyglot_hash,
+        polyglot_move_promotion, polyglot_move_squares,
+    };
+
+    fn game_of_lans(lans: &[&str]) -> Game {
+        let mut game = game_of_mode(standard_chess());
+        for lan in lans {
+            let (from, to) = lan_try_of(&game, lan).unwrap();
+            play_move(&mut game, &from, &to);
+        }
+        game
+    }
+
+    #[test]
+    fn polyglot_hash_reference_positions() {
+        assert_eq!(polyglot_hash(&game_of_lans(&[])), Some(0x463B_9618_1691_FC9C));
+        assert_eq!(polyglot_hash(&game_of_lans(&["e2e4"])), Some(0x823C_9B50_FD11_4196));
+        assert_eq!(polyglot_hash(&game_of_lans(&["e2e4", "d7d5"])), Some(0x0756_B944_61C5_0FB0));
+        assert_eq!(
+            polyglot_hash(&game_of_lans(&["e2e4", "d7d5", "e4e5"])),
+            Some(0x662F_AFB9_65DB_29D4)
+        );
+        assert_eq!(
+            polyglot_hash(&game_of_lans(&["e2e4", "d7d5", "e4e5", "f7f5"])),
+            Some(0x22A4_8B5A_8E47_FF78)
+        );
+        assert_eq!(
+            polyglot_hash(&game_of_lans(&["e2e4", "d7d5", "e4e5", "f7f5", "e1e2"])),
+            Some(0x652A_607C_A3F2_42C1)
+        );
+        assert_eq!(
+            polyglot_hash(&game_of_lans(&["e2e4", "d7d5", "e4e5", "f7f5", "e1e2", "e8f7"])),
+            Some(0x00FD_D303_C946_BDD9)
+        );
+        assert_eq!(
+            polyglot_hash(&game_of_lans(&["a2a4", "b7b5", "h2h4", "b5b4", "c2c4"])),
+            Some(0x3C81_23EA_7B06_7637)
+        );
+        assert_eq!(
+            polyglot_hash(&game_of_lans(&["a2a4", "b7b5", "h2h4", "b5b4", "c2c4", "b4c3", "a1a3"])),
+            Some(0x5C3F_9B82_9B27_9560)
+        );
+        assert_eq!(polyglot_hash(&game_of_fen("k9/10/10/10/10/10/10/10/10/9K w - -")), None);
+    }
+
+    #[test]
+    fn polyglot_move_squares_of() {
+        assert_eq!(polyglot_move_squares(0x031C), (pos_of("E2"), pos_of("E4")));
+        assert_eq!(polyglot_move_squares(0x0107), (pos_of("E1"), pos_of("H1")));
+        assert_eq!(polyglot_move_squares(0x1CBA), (pos_of("C7"), pos_of("C8")));
+        assert_eq!(polyglot_move_promotion(0x1CBA), Some(PieceMoveType::PromotionToKnight));
+        assert_eq!(polyglot_move_promotion(0x031C), None);
+    }
+
+    #[test]
+    fn polyglot_book_try_of() {
+        assert_eq!(PolyglotBook::try_of(&[0; 15]), None);
+        assert_eq!(PolyglotBook::try_of(&[]), Some(PolyglotBook::default()));
+        let entry = PolyglotEntry { key: 0x0102_0304_0506_0708, mov: 0x0910, weight: 3, learn: 7 };
+        let book = PolyglotBook::of(&entry.to_bytes());
+        assert_eq!(book.len(), 1);
+        assert_eq!(book.entries_of(0x0102_0304_0506_0708), &[entry]);
+        assert!(book.entries_of(1).is_empty());
+    }
+
+    #[test]
+    fn polyglot_book_moves() {
+        let key = 0x463B_9618_1691_FC9C;
+        let bytes: Vec<u8> = [
+            PolyglotEntry { key: key - 1, mov: 0x0B5D, weight: 99, learn: 0 },
+            PolyglotEntry { key, mov: 0x02DB, weight: 5, learn: 0 },
+            PolyglotEntry { key, mov: 0x031C, weight: 10, learn: 0 },
+            PolyglotEntry { key, mov: 0x0324, weight: 1, learn: 0 },
+            PolyglotEntry { key: key + 1, mov: 0x0B5D, weight: 99, learn: 0 },
+        ]
+        .iter()
+        .flat_map(|entry| entry.to_bytes())
+        .collect();
+        let book = PolyglotBook::of(&bytes);
+        let moves = book_moves(&book, &game_of_mode(standard_chess()));
+        assert_eq!(
+            moves,
+            vec![
+                BookMove {
+                    from: pos_of("E2"),
+                    to: pos_of("E4"),
+                    weight: 10,
+                    game_move: GameMove::default_of('♙', "E2", "E4"),
+                },
+                BookMove {
+                    from: pos_of("D2"),
+                    to: pos_of("D4"),
+                    weight: 5,
+                    game_move: GameMove::default_of('♙', "D2", "D4"),
+                },
+            ]
+        );
+        assert_eq!(pick_book_move(&moves, 0), moves.first());
+        assert_eq!(pick_book_move(&moves, 9), moves.first());
+        assert_eq!(pick_book_move(&moves, 10), moves.get(1));
+        assert_eq!(pick_book_move(&moves, 29), moves.get(1));
+        assert_eq!(pick_book_move(&[], 3), None);
+        assert!(book_moves(&book, &game_of_lans(&["e2e4"])).is_empty());
+    }
+}
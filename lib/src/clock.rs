@@ -0,0 +1,388 @@
+use std::{collections::HashMap, sync::LazyLock};
+
+use crate::{color::Color, time::TimeSource};
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Increment {
+    None,
+    Fischer(u64),
+    Bronstein(u64),
+    Delay(u64),
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TimeControlStage {
+    pub moves: Option<u16>,
+    pub time_ms: u64,
+    pub increment: Increment,
+}
+
+// stages is never empty, so it is private and checked again when deserialized
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "TimeControlStages"))]
+pub struct TimeControl {
+    stages: Vec<TimeControlStage>,
+}
+
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct TimeControlStages {
+    stages: Vec<TimeControlStage>,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<TimeControlStages> for TimeControl {
+    type Error = &'static str;
+
+    fn try_from(value: TimeControlStages) -> Result<Self, Self::Error> {
+        TimeControl::try_of_stages(value.stages).ok_or("invalid time control stages")
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct TimeControlPreset {
+    pub id: &'static str,
+    pub name: &'static str,
+}
+
+fn stage_try_of(s: &str) -> Option<TimeControlStage> {
+    let (moves, rest) = match s.split_once('/') {
+        Some((moves, rest)) => (Some(moves.parse::<u16>().ok().filter(|moves| *moves > 0)?), rest),
+        None => (None, s),
+    };
+    let (time, increment) = match rest.split_once('+') {
+        Some((time, increment)) => {
+            (time, Increment::Fischer(increment.parse::<u64>().ok()? * 1000))
+        }
+        None => (rest, Increment::None),
+    };
+    Some(TimeControlStage { moves, time_ms: time.parse::<u64>().ok()? * 1000, increment })
+}
+
+impl TimeControl {
+    fn single(time_ms: u64, increment: Increment) -> Self {
+        TimeControl { stages: vec![TimeControlStage { moves: None, time_ms, increment }] }
+    }
+
+    pub fn sudden_death(time_ms: u64) -> Self {
+        Self::single(time_ms, Increment::None)
+    }
+
+    pub fn fischer(time_ms: u64, increment_ms: u64) -> Self {
+        Self::single(time_ms, Increment::Fischer(increment_ms))
+    }
+
+    pub fn bronstein(time_ms: u64, delay_ms: u64) -> Self {
+        Self::single(time_ms, Increment::Bronstein(delay_ms))
+    }
+
+    pub fn simple_delay(time_ms: u64, delay_ms: u64) -> Self {
+        Self::single(time_ms, Increment::Delay(delay_ms))
+    }
+
+    pub fn try_of_stages(stages: Vec<TimeControlStage>) -> Option<Self> {
+        if stages.is_empty() || stages.iter().rev().skip(1).any(|stage| stage.moves.is_none()) {
+            return None;
+        }
+        Some(TimeControl { stages })
+    }
+
+    pub fn try_of(s: &str) -> Option<Self> {
+        Self::try_of_stages(s.split(':').map(stage_try_of).collect::<Option<Vec<_>>>()?)
+    }
+
+    pub fn stages(&self) -> &[TimeControlStage] {
+        &self.stages
+    }
+
+    pub fn of(s: &str) -> Self {
+        Self::try_of(s).unwrap()
+    }
+
+    pub fn stage(&self, i: usize) -> &TimeControlStage {
+        &self.stages[i.min(self.stages.len() - 1)]
+    }
+}
+
+static PRESETS: LazyLock<HashMap<&str, TimeControl>> = LazyLock::new(|| {
+    HashMap::from([
+        ("bullet_1_0", TimeControl::sudden_death(60_000)),
+        ("blitz_3_2", TimeControl::fischer(180_000, 2_000)),
+        ("blitz_5_0", TimeControl::sudden_death(300_000)),
+        ("blitz_5_d3", TimeControl::simple_delay(300_000, 3_000)),
+        ("rapid_10_5", TimeControl::fischer(600_000, 5_000)),
+        ("rapid_15_b10", TimeControl::bronstein(900_000, 10_000)),
+        ("classical_40_90_30", TimeControl::of("40/5400+30:1800+30")),
+    ])
+});
+
+pub fn try_get_time_control(preset: &str) -> Option<TimeControl> {
+    PRESETS.get(preset).cloned()
+}
+
+pub fn get_time_control(preset: &str) -> TimeControl {
+    try_get_time_control(preset).unwrap()
+}
+
+pub fn get_time_control_presets() -> Vec<TimeControlPreset> {
+    vec![
+        TimeControlPreset { id: "bullet_1_0", name: "1 min" },
+        TimeControlPreset { id: "blitz_3_2", name: "3 | 2" },
+        TimeControlPreset { id: "blitz_5_0", name: "5 min" },
+        TimeControlPreset { id: "blitz_5_d3", name: "5 min, 3s delay" },
+        TimeControlPreset { id: "rapid_10_5", name: "10 | 5" },
+        TimeControlPreset { id: "rapid_15_b10", name: "15 min, 10s Bronstein" },
+        TimeControlPreset { id: "classical_40_90_30", name: "40/90 + 30" },
+    ]
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PlayerClock {
+    pub remaining_ms: u64,
+    pub stage: usize,
+    pub stage_moves: u16,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Clock {
+    pub control: TimeControl,
+    pub players: HashMap<Color, PlayerClock>,
+    pub running: Option<(Color, u64)>,
+    pub flagged: Option<Color>,
+}
+
+fn charged_ms(increment: Increment, elapsed: u64) -> u64 {
+    match increment {
+        Increment::Delay(delay) => elapsed.saturating_sub(delay),
+        _ => elapsed,
+    }
+}
+
+impl Clock {
+    pub fn of(control: TimeControl) -> Self {
+        let player =
+            PlayerClock { remaining_ms: control.stage(0).time_ms, stage: 0, stage_moves: 0 };
+        Clock {
+            control,
+            players: HashMap::from([(Color::White, player.clone()), (Color::Black, player)]),
+            running: None,
+            flagged: None,
+        }
+    }
+
+    pub fn remaining_ms(&self, color: &Color, time_source: &dyn TimeSource) -> u64 {
+        let Some(player) = self.players.get(color) else {
+            return 0;
+        };
+        match &self.running {
+            Some((running, started)) if running == color => {
+                let elapsed = time_source.now_ms().saturating_sub(*started);
+                let increment = self.control.stage(player.stage).increment;
+                player.remaining_ms.saturating_sub(charged_ms(increment, elapsed))
+            }
+            _ => player.remaining_ms,
+        }
+    }
+
+    pub fn start(&mut self, color: &Color, time_source: &dyn TimeSource) {
+        if self.flagged.is_none() {
+            self.running = Some((*color, time_source.now_ms()));
+        }
+    }
+
+    pub fn stop(&mut self, time_source: &dyn TimeSource) {
+        if let Some((color, _)) = self.running {
+            let remaining_ms = self.remaining_ms(&color, time_source);
+            if let Some(player) = self.players.get_mut(&color) {
+                player.remaining_ms = remaining_ms;
+            }
+            self.running = None;
+        }
+    }
+
+    pub fn check_flag(&mut self, time_source: &dyn TimeSource) -> Option<Color> {
+        if let Some((color, _)) = self.running
+            && self.remaining_ms(&color, time_source) == 0
+        {
+            self.stop(time_source);
+            self.flagged = Some(color);
+        }
+        self.flagged
+    }
+
+    pub fn press(&mut self, time_source: &dyn TimeSource) {
+        if self.check_flag(time_source).is_some() {
+            return;
+        }
+        let Some((color, started)) = self.running else {
+            return;
+        };
+        let now = time_source.now_ms();
+        let elapsed = now.saturating_sub(started);
+        let remaining_ms = self.remaining_ms(&color, time_source);
+        let Some(player) = self.players.get_mut(&color) else {
+            return;
+        };
+        let stage = self.control.stage(player.stage);
+        player.remaining_ms = match stage.increment {
+            Increment::Fischer(increment) => remaining_ms + increment,
+            Increment::Bronstein(delay) => remaining_ms + elapsed.min(delay),
+            _ => remaining_ms,
+        };
+        player.stage_moves += 1;
+        if stage.moves.is_some_and(|moves| player.stage_moves >= moves) {
+            player.stage += 1;
+            player.stage_moves = 0;
+            player.remaining_ms += self.control.stage(player.stage).time_ms;
+        }
+        let opponent = match color {
+            Color::White => Color::Black,
+            Color::Black => Color::White,
+        };
+        self.running = Some((opponent, now));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{color::Color, time::ManualTimeSource};
+
+    use super::{
+        Clock, Increment, TimeControl, TimeControlStage, get_time_control_presets,
+        try_get_time_control,
+    };
+
+    #[test]
+    fn time_control_try_of() {
+        assert_eq!(TimeControl::try_of("300"), Some(TimeControl::sudden_death(300_000)));
+        assert_eq!(TimeControl::try_of("180+2"), Some(TimeControl::fischer(180_000, 2_000)));
+        assert_eq!(
+            TimeControl::try_of("40/5400+30:1800+30"),
+            Some(TimeControl {
+                stages: vec![
+                    TimeControlStage {
+                        moves: Some(40),
+                        time_ms: 5_400_000,
+                        increment: Increment::Fischer(30_000)
+                    },
+                    TimeControlStage {
+                        moves: None,
+                        time_ms: 1_800_000,
+                        increment: Increment::Fischer(30_000)
+                    },
+                ]
+            })
+        );
+        assert_eq!(TimeControl::try_of(""), None);
+        assert_eq!(TimeControl::try_of("0/300"), None);
+        assert_eq!(TimeControl::try_of("300+"), None);
+        assert_eq!(TimeControl::try_of("300:40/300"), None);
+        assert_eq!(TimeControl::try_of_stages(Vec::new()), None);
+    }
+
+    #[test]
+    fn time_control_presets() {
+        for preset in get_time_control_presets() {
+            assert!(try_get_time_control(preset.id).is_some());
+        }
+        assert_eq!(try_get_time_control("unknown"), None);
+    }
+
+    #[test]
+    fn clock_sudden_death() {
+        let time_source = ManualTimeSource::of(1_000);
+        let mut clock = Clock::of(TimeControl::sudden_death(10_000));
+        assert_eq!(clock.remaining_ms(&Color::White, &time_source), 10_000);
+        clock.start(&Color::White, &time_source);
+        time_source.advance(3_000);
+        assert_eq!(clock.remaining_ms(&Color::White, &time_source), 7_000);
+        clock.press(&time_source);
+        time_source.advance(4_000);
+        assert_eq!(clock.remaining_ms(&Color::White, &time_source), 7_000);
+        assert_eq!(clock.remaining_ms(&Color::Black, &time_source), 6_000);
+        assert_eq!(clock.check_flag(&time_source), None);
+        time_source.advance(6_000);
+        assert_eq!(clock.check_flag(&time_source), Some(Color::Black));
+        assert_eq!(clock.running, None);
+        clock.press(&time_source);
+        clock.start(&Color::White, &time_source);
+        assert_eq!(clock.running, None);
+    }
+
+    #[test]
+    fn clock_fischer() {
+        let time_source = ManualTimeSource::of(0);
+        let mut clock = Clock::of(TimeControl::fischer(10_000, 2_000));
+        clock.start(&Color::White, &time_source);
+        time_source.advance(500);
+        clock.press(&time_source);
+        assert_eq!(clock.remaining_ms(&Color::White, &time_source), 11_500);
+    }
+
+    #[test]
+    fn clock_bronstein() {
+        let time_source = ManualTimeSource::of(0);
+        let mut clock = Clock::of(TimeControl::bronstein(10_000, 2_000));
+        clock.start(&Color::White, &time_source);
+        time_source.advance(500);
+        clock.press(&time_source);
+        assert_eq!(clock.remaining_ms(&Color::White, &time_source), 10_000);
+        time_source.advance(3_000);
+        clock.press(&time_source);
+        assert_eq!(clock.remaining_ms(&Color::Black, &time_source), 9_000);
+    }
+
+    #[test]
+    fn clock_simple_delay() {
+        let time_source = ManualTimeSource::of(0);
+        let mut clock = Clock::of(TimeControl::simple_delay(10_000, 2_000));
+        clock.start(&Color::White, &time_source);
+        time_source.advance(1_500);
+        assert_eq!(clock.remaining_ms(&Color::White, &time_source), 10_000);
+        time_source.advance(1_500);
+        assert_eq!(clock.remaining_ms(&Color::White, &time_source), 9_000);
+        clock.press(&time_source);
+        assert_eq!(clock.remaining_ms(&Color::White, &time_source), 9_000);
+        time_source.advance(11_999);
+        assert_eq!(clock.check_flag(&time_source), None);
+        time_source.advance(1);
+        assert_eq!(clock.check_flag(&time_source), Some(Color::Black));
+    }
+
+    #[test]
+    fn clock_multi_stage() {
+        let time_source = ManualTimeSource::of(0);
+        let mut clock = Clock::of(TimeControl::of("2/10+1:5"));
+        clock.start(&Color::White, &time_source);
+        for _ in 0..4 {
+            time_source.advance(1_000);
+            clock.press(&time_source);
+        }
+        let white = clock.players.get(&Color::White).unwrap();
+        assert_eq!((white.remaining_ms, white.stage, white.stage_moves), (15_000, 1, 0));
+        time_source.advance(1_000);
+        clock.press(&time_source);
+        time_source.advance(1_000);
+        clock.press(&time_source);
+        assert_eq!(clock.remaining_ms(&Color::White, &time_source), 14_000);
+        let white = clock.players.get(&Color::White).unwrap();
+        assert_eq!((white.stage, white.stage_moves), (1, 1));
+    }
+
+    #[test]
+    fn clock_stop() {
+        let time_source = ManualTimeSource::of(0);
+        let mut clock = Clock::of(TimeControl::fischer(10_000, 2_000));
+        clock.start(&Color::Black, &time_source);
+        time_source.advance(4_000);
+        clock.stop(&time_source);
+        time_source.advance(4_000);
+        assert_eq!(clock.running, None);
+        assert_eq!(clock.remaining_ms(&Color::Black, &time_source), 6_000);
+    }
+}
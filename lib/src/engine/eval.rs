@@ -0,0 +1,641 @@
+use std::{collections::HashMap, fmt, sync::LazyLock};
+
+use manfredo::matrix::rect::rect_u8::{len_col, len_row};
+
+use crate::{
+    color::Color,
+    game::{board::GameBoard, game::GameBounds, game::GamePlayers},
+    piece::PieceType,
+    pos::Pos,
+};
+
+const MAX_PHASE: i32 = 24;
+
+static DEFAULT_PARAMS: LazyLock<EvalParams> = LazyLock::new(EvalParams::default);
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Tapered {
+    pub mg: i32,
+    pub eg: i32,
+}
+
+impl Tapered {
+    pub fn of(mg: i32, eg: i32) -> Self {
+        Tapered { mg, eg }
+    }
+
+    fn add(&mut self, other: Tapered, times: i32) {
+        self.mg += other.mg * times;
+        self.eg += other.eg * times;
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EvalParams {
+    pub material: [Tapered; 6],
+    #[cfg_attr(feature = "serde", serde(with = "psqt_serde"))]
+    pub psqt: [[Tapered; 64]; 6],
+    pub mobility: [Tapered; 6],
+    pub pawn_shield: Tapered,
+    pub king_zone_attack: Tapered,
+    pub doubled_pawn: Tapered,
+    pub isolated_pawn: Tapered,
+    pub passed_pawn: [Tapered; 8],
+    pub bishop_pair: Tapered,
+}
+
+// serde only derives arrays up to 32 elements, so each table goes through a Vec
+#[cfg(feature = "serde")]
+mod psqt_serde {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer, de::Error};
+
+    use super::Tapered;
+
+    pub fn serialize<S: Serializer>(
+        psqt: &[[Tapered; 64]; 6],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        psqt.iter().map(|table| table.as_slice()).collect::<Vec<_>>().serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<[[Tapered; 64]; 6], D::Error> {
+        let tables = Vec::<Vec<Tapered>>::deserialize(deserializer)?;
+        let tables = tables
+            .into_iter()
+            .map(|table| {
+                <[Tapered; 64]>::try_from(table)
+                    .map_err(|table| D::Error::invalid_length(table.len(), &"64 squares"))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        <[[Tapered; 64]; 6]>::try_from(tables)
+            .map_err(|tables| D::Error::invalid_length(tables.len(), &"6 piece types"))
+    }
+}
+
+#[rustfmt::skip]
+const PSQT_PAWN: [i32; 64] = [
+     0,  0,  0,  0,  0,  0,  0,  0,
+    50, 50, 50, 50, 50, 50, 50, 50,
+    10, 10, 20, 30, 30, 20, 10, 10,
+     5,  5, 10, 25, 25, 10,  5,  5,
+     0,  0,  0, 20, 20,  0,  0,  0,
+     5, -5,-10,  0,  0,-10, -5,  5,
+     5, 10, 10,-20,-20, 10, 10,  5,
+     0,  0,  0,  0,  0,  0,  0,  0,
+];
+
+#[rustfmt::skip]
+const PSQT_KNIGHT: [i32; 64] = [
+    -50,-40,-30,-30,-30,-30,-40,-50,
+    -40,-20,  0,  0,  0,  0,-20,-40,
+    -30,  0, 10, 15, 15, 10,  0,-30,
+    -30,  5, 15, 20, 20, 15,  5,-30,
+    -30,  0, 15, 20, 20, 15,  0,-30,
+    -30,  5, 10, 15, 15, 10,  5,-30,
+    -40,-20,  0,  5,  5,  0,-20,-40,
+    -50,-40,-30,-30,-30,-30,-40,-50,
+];
+
+#[rustfmt::skip]
+const PSQT_BISHOP: [i32; 64] = [
+    -20,-10,-10,-10,-10,-10,-10,-20,
+    -10,  0,  0,  0,  0,  0,  0,-10,
+    -10,  0,  5, 10, 10,  5,  0,-10,
+    -10,  5,  5, 10, 10,  5,  5,-10,
+    -10,  0, 10, 10, 10, 10,  0,-10,
+    -10, 10, 10, 10, 10, 10, 10,-10,
+    -10,  5,  0,  0,  0,  0,  5,-10,
+    -20,-10,-10,-10,-10,-10,-10,-20,
+];
+
+#[rustfmt::skip]
+const PSQT_ROOK: [i32; 64] = [
+     0,  0,  0,  0,  0,  0,  0,  0,
+     5, 10, 10, 10, 10, 10, 10,  5,
+    -5,  0,  0,  0,  0,  0,  0, -5,
+    -5,  0,  0,  0,  0,  0,  0, -5,
+    -5,  0,  0,  0,  0,  0,  0, -5,
+    -5,  0,  0,  0,  0,  0,  0, -5,
+    -5,  0,  0,  0,  0,  0,  0, -5,
+     0,  0,  0,  5,  5,  0,  0,  0,
+];
+
+#[rustfmt::skip]
+const PSQT_QUEEN: [i32; 64] = [
+    -20,-10,-10, -5, -5,-10,-10,-20,
+    -10,  0,  0,  0,  0,  0,  0,-10,
+    -10,  0,  5,  5,  5,  5,  0,-10,
+     -5,  0,  5,  5,  5,  5,  0, -5,
+      0,  0,  5,  5,  5,  5,  0, -5,
+    -10,  5,  5,  5,  5,  5,  0,-10,
+    -10,  0,  5,  0,  0,  0,  0,-10,
+    -20,-10,-10, -5, -5,-10,-10,-20,
+];
+
+#[rustfmt::skip]
+const PSQT_KING_MG: [i32; 64] = [
+    -30,-40,-40,-50,-50,-40,-40,-30,
+    -30,-40,-40,-50,-50,-40,-40,-30,
+    -30,-40,-40,-50,-50,-40,-40,-30,
+    -30,-40,-40,-50,-50,-40,-40,-30,
+    -20,-30,-30,-40,-40,-30,-30,-20,
+    -10,-20,-20,-20,-20,-20,-20,-10,
+     20, 20,  0,  0,  0,  0, 20, 20,
+     20, 30, 10,  0,  0, 10, 30, 20,
+];
+
+#[rustfmt::skip]
+const PSQT_KING_EG: [i32; 64] = [
+    -50,-40,-30,-20,-20,-30,-40,-50,
+    -30,-20,-10,  0,  0,-10,-20,-30,
+    -30,-10, 20, 30, 30, 20,-10,-30,
+    -30,-10, 30, 40, 40, 30,-10,-30,
+    -30,-10, 30, 40, 40, 30,-10,-30,
+    -30,-10, 20, 30, 30, 20,-10,-30,
+    -30,-30,  0,  0,  0,  0,-30,-30,
+    -50,-30,-30,-30,-30,-30,-30,-50,
+];
+
+fn psqt_of(mg: &[i32; 64], eg: &[i32; 64]) -> [Tapered; 64] {
+    let mut result = [Tapered::default(); 64];
+    for (i, value) in result.iter_mut().enumerate() {
+        *value = Tapered::of(mg[i], eg[i]);
+    }
+    result
+}
+
+impl Default for EvalParams {
+    fn default() -> Self {
+        EvalParams {
+            material: [
+                Tapered::of(100, 120),
+                Tapered::of(320, 300),
+                Tapered::of(330, 320),
+                Tapered::of(500, 530),
+                Tapered::of(900, 940),
+                Tapered::of(0, 0),
+            ],
+            psqt: [
+                psqt_of(&PSQT_PAWN, &PSQT_PAWN),
+                psqt_of(&PSQT_KNIGHT, &PSQT_KNIGHT),
+                psqt_of(&PSQT_BISHOP, &PSQT_BISHOP),
+                psqt_of(&PSQT_ROOK, &PSQT_ROOK),
+                psqt_of(&PSQT_QUEEN, &PSQT_QUEEN),
+                psqt_of(&PSQT_KING_MG, &PSQT_KING_EG),
+            ],
+            mobility: [
+                Tapered::of(0, 0),
+                Tapered::of(4, 4),
+                Tapered::of(5, 5),
+                Tapered::of(2, 4),
+                Tapered::of(1, 2),
+                Tapered::of(0, 0),
+            ],
+            pawn_shield: Tapered::of(10, 0),
+            king_zone_attack: Tapered::of(-8, -2),
+            doubled_pawn: Tapered::of(-10, -20),
+            isolated_pawn: Tapered::of(-10, -15),
+            passed_pawn: [
+                Tapered::of(0, 0),
+                Tapered::of(5, 10),
+                Tapered::of(10, 20),
+                Tapered::of(15, 35),
+                Tapered::of(25, 60),
+                Tapered::of(40, 90),
+                Tapered::of(60, 130),
+                Tapered::of(0, 0),
+            ],
+            bishop_pair: Tapered::of(30, 50),
+        }
+    }
+}
+
+const PARAM_GROUPS: [(&str, usize); 14] = [
+    ("material", 6),
+    ("psqt_pawn", 64),
+    ("psqt_knight", 64),
+    ("psqt_bishop", 64),
+    ("psqt_rook", 64),
+    ("psqt_queen", 64),
+    ("psqt_king", 64),
+    ("mobility", 6),
+    ("pawn_shield", 1),
+    ("king_zone_attack", 1),
+    ("doubled_pawn", 1),
+    ("isolated_pawn", 1),
+    ("passed_pawn", 8),
+    ("bishop_pair", 1),
+];
+
+impl EvalParams {
+    fn groups(&self) -> Vec<&[Tapered]> {
+        vec![
+            &self.material,
+            &self.psqt[0],
+            &self.psqt[1],
+            &self.psqt[2],
+            &self.psqt[3],
+            &self.psqt[4],
+            &self.psqt[5],
+            &self.mobility,
+            std::slice::from_ref(&self.pawn_shield),
+            std::slice::from_ref(&self.king_zone_attack),
+            std::slice::from_ref(&self.doubled_pawn),
+            std::slice::from_ref(&self.isolated_pawn),
+            &self.passed_pawn,
+            std::slice::from_ref(&self.bishop_pair),
+        ]
+    }
+
+    fn groups_mut(&mut self) -> Vec<&mut [Tapered]> {
+        let [pawn, knight, bishop, rook, queen, king] = &mut self.psqt;
+        vec![
+            &mut self.material,
+            pawn,
+            knight,
+            bishop,
+            rook,
+            queen,
+            king,
+            &mut self.mobility,
+            std::slice::from_mut(&mut self.pawn_shield),
+            std::slice::from_mut(&mut self.king_zone_attack),
+            std::slice::from_mut(&mut self.doubled_pawn),
+            std::slice::from_mut(&mut self.isolated_pawn),
+            &mut self.passed_pawn,
+            std::slice::from_mut(&mut self.bishop_pair),
+        ]
+    }
+
+    pub fn to_vec(&self) -> Vec<i32> {
+        self.groups().iter().flat_map(|group| group.iter().flat_map(|t| [t.mg, t.eg])).collect()
+    }
+
+    pub fn try_of_vec(values: &[i32]) -> Option<Self> {
+        let mut params = EvalParams::default();
+        if values.len() != params.to_vec().len() {
+            return None;
+        }
+        let mut it = values.iter();
+        for group in params.groups_mut() {
+            for t in group.iter_mut() {
+                t.mg = *it.next()?;
+                t.eg = *it.next()?;
+            }
+        }
+        Some(params)
+    }
+
+    pub fn try_of(s: &str) -> Option<Self> {
+        let mut groups: HashMap<&str, Vec<i32>> = HashMap::new();
+        for line in s.lines().map(str::trim).filter(|line| !line.is_empty()) {
+            let mut parts = line.split_whitespace();
+            let name = parts.next()?;
+            let values: Option<Vec<i32>> = parts.map(|part| part.parse().ok()).collect();
+            groups.insert(name, values?);
+        }
+        let mut values = Vec::new();
+        for (name, len) in PARAM_GROUPS {
+            let group = groups.get(name)?;
+            if group.len() != len * 2 {
+                return None;
+            }
+            values.extend(group);
+        }
+        Self::try_of_vec(&values)
+    }
+
+    pub fn of(s: &str) -> Self {
+        Self::try_of(s).unwrap()
+    }
+}
+
+impl fmt::Display for EvalParams {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for ((name, _), group) in PARAM_GROUPS.iter().zip(self.groups()) {
+            write!(f, "{name}")?;
+            for t in group {
+                write!(f, " {} {}", t.mg, t.eg)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+fn piece_type_index(typ: &PieceType) -> usize {
+    match typ {
+        PieceType::Pawn => 0,
+        PieceType::Knight => 1,
+        PieceType::Bishop => 2,
+        PieceType::Rook => 3,
+        PieceType::Queen => 4,
+        PieceType::King => 5,
+    }
+}
+
+fn phase_weight(typ: &PieceType) -> i32 {
+    match typ {
+        PieceType::Knight | PieceType::Bishop => 1,
+        PieceType::Rook => 2,
+        PieceType::Queen => 4,
+        PieceType::Pawn | PieceType::King => 0,
+    }
+}
+
+pub fn piece_value(typ: &PieceType) -> i32 {
+    DEFAULT_PARAMS.material[piece_type_index(typ)].mg
+}
+
+pub fn game_phase(board: &GameBoard) -> i32 {
+    board.values().map(|piece| phase_weight(&piece.typ)).sum::<i32>().min(MAX_PHASE)
+}
+
+fn relative_row(bounds: &GameBounds, pos: &Pos, color: &Color) -> u8 {
+    match color {
+        Color::White => pos.row - bounds.min.row,
+        Color::Black => bounds.max.row - pos.row,
+    }
+}
+
+fn psqt_index(bounds: &GameBounds, pos: &Pos, color: &Color) -> usize {
+    let row = usize::from(relative_row(bounds, pos, color)) * 8 / usize::from(len_row(bounds));
+    let col = usize::from(pos.col - bounds.min.col) * 8 / usize::from(len_col(bounds));
+    (7 - row) * 8 + col
+}
+
+fn forward(color: &Color) -> i16 {
+    match color {
+        Color::White => 1,
+        Color::Black => -1,
+    }
+}
+
+pub fn pawn_structure(
+    board: &GameBoard,
+    bounds: &GameBounds,
+    color: &Color,
+    params: &EvalParams,
+) -> Tapered {
+    let mut result = Tapered::default();
+    let own: Vec<&Pos> = board
+        .iter()
+        .filter(|(_, piece)| &piece.color == color && piece.typ == PieceType::Pawn)
+        .map(|(pos, _)| pos)
+        .collect();
+    let enemy: Vec<&Pos> = board
+        .iter()
+        .filter(|(_, piece)| &piece.color != color && piece.typ == PieceType::Pawn)
+        .map(|(pos, _)| pos)
+        .collect();
+    for col in bounds.iter_col() {
+        let count = own.iter().filter(|pos| pos.col == col).count() as i32;
+        if count > 1 {
+            result.add(params.doubled_pawn, count - 1);
+        }
+    }
+    for pos in own.iter() {
+        let adjacent = |other: &&Pos| other.col.abs_diff(pos.col) == 1;
+        if !own.iter().any(adjacent) {
+            result.add(params.isolated_pawn, 1);
+        }
+        let ahead = |other: &&Pos| {
+            other.col.abs_diff(pos.col) <= 1
+                && (i16::from(other.row) - i16::from(pos.row)) * forward(color) > 0
+        };
+        if !enemy.iter().any(ahead) {
+            let rank =
+                usize::from(relative_row(bounds, pos, color)) * 8 / usize::from(len_row(bounds));
+            result.add(params.passed_pawn[rank.min(7)], 1);
+        }
+    }
+    result
+}
+
+pub fn king_safety(
+    board: &GameBoard,
+    players: &GamePlayers,
+    color: &Color,
+    params: &EvalParams,
+) -> Tapered {
+    let mut result = Tapered::default();
+    let maybe_king =
+        board.iter().find(|(_, piece)| &piece.color == color && piece.typ == PieceType::King);
+    if let Some((king_pos, _)) = maybe_king {
+        let shield = board
+            .iter()
+            .filter(|(pos, piece)| {
+                &piece.color == color
+                    && piece.typ == PieceType::Pawn
+                    && pos.col.abs_diff(king_pos.col) <= 1
+                    && (1..=2).contains(
+                        &((i16::from(pos.row) - i16::from(king_pos.row)) * forward(color)),
+                    )
+            })
+            .count() as i32;
+        result.add(params.pawn_shield, shield);
+        let attacks = players
+            .values()
+            .filter(|player| &player.color != color)
+            .flat_map(|player| player.moves.values())
+            .flat_map(|piece_moves| piece_moves.keys())
+            .filter(|to| to.row.abs_diff(king_pos.row) <= 1 && to.col.abs_diff(king_pos.col) <= 1)
+            .count() as i32;
+        result.add(params.king_zone_attack, attacks);
+    }
+    result
+}
+
+fn evaluate_color(
+    board: &GameBoard,
+    bounds: &GameBounds,
+    players: &GamePlayers,
+    color: &Color,
+    params: &EvalParams,
+) -> Tapered {
+    let mut result = Tapered::default();
+    let mut bishops = 0;
+    for (pos, piece) in board.iter().filter(|(_, piece)| &piece.color == color) {
+        let i = piece_type_index(&piece.typ);
+        result.add(params.material[i], 1);
+        result.add(params.psqt[i][psqt_index(bounds, pos, color)], 1);
+        if piece.typ == PieceType::Bishop {
+            bishops += 1;
+        }
+        let mobility = players
+            .get(color)
+            .and_then(|player| player.moves.get(pos))
+            .map(|piece_moves| piece_moves.len() as i32)
+            .unwrap_or(0);
+        result.add(params.mobility[i], mobility);
+    }
+    if bishops >= 2 {
+        result.add(params.bishop_pair, 1);
+    }
+    result.add(pawn_structure(board, bounds, color, params), 1);
+    result.add(king_safety(board, players, color, params), 1);
+    result
+}
+
+pub fn evaluate(
+    board: &GameBoard,
+    bounds: &GameBounds,
+    players: &GamePlayers,
+    turn: &Color,
+    params: &EvalParams,
+) -> i32 {
+    let opponent = match turn {
+        Color::White => Color::Black,
+        Color::Black => Color::White,
+    };
+    let mut score = evaluate_color(board, bounds, players, turn, params);
+    score.add(evaluate_color(board, bounds, players, &opponent, params), -1);
+    let phase = game_phase(board);
+    (score.mg * phase + score.eg * (MAX_PHASE - phase)) / MAX_PHASE
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        color::Color,
+        game::{
+            board::board_of_str, game::empty_players, mode::standard_chess,
+            rule::init::game_of_mode,
+        },
+    };
+
+    use super::{
+        EvalParams, Tapered, evaluate, game_phase, king_safety, pawn_structure, piece_value,
+    };
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn eval_params_serde() {
+        let params = EvalParams::default();
+        let json = serde_json::to_string(&params).unwrap();
+        assert_eq!(serde_json::from_str::<EvalParams>(&json).unwrap(), params);
+        let truncated = json.replacen("{\"mg\":0,\"eg\":0},", "", 1);
+        assert!(serde_json::from_str::<EvalParams>(&truncated).is_err());
+    }
+
+    #[test]
+    fn evaluate_initial_position() {
+        let game = game_of_mode(standard_chess());
+        let params = EvalParams::default();
+        assert_eq!(evaluate(&game.board, &game.bounds, &game.players, &Color::White, &params), 0);
+        assert_eq!(evaluate(&game.board, &game.bounds, &game.players, &Color::Black, &params), 0);
+    }
+
+    #[test]
+    fn evaluate_material() {
+        let mode = standard_chess();
+        let board = board_of_str(
+            &mode.bounds,
+            [
+                "    ♚   ",
+                "        ",
+                "        ",
+                "        ",
+                "        ",
+                "        ",
+                "        ",
+                "   ♕♔   ",
+            ],
+        );
+        let params = EvalParams::default();
+        let players = empty_players();
+        let white = evaluate(&board, &mode.bounds, &players, &Color::White, &params);
+        assert!(white > 800);
+        assert_eq!(evaluate(&board, &mode.bounds, &players, &Color::Black, &params), -white);
+    }
+
+    #[test]
+    fn evaluate_piece_value() {
+        assert_eq!(piece_value(&crate::piece::PieceType::Pawn), 100);
+        assert_eq!(piece_value(&crate::piece::PieceType::Queen), 900);
+        assert_eq!(piece_value(&crate::piece::PieceType::King), 0);
+    }
+
+    #[test]
+    fn evaluate_game_phase() {
+        let mode = standard_chess();
+        assert_eq!(game_phase(&mode.initial_board), 24);
+        let board = board_of_str(
+            &mode.bounds,
+            [
+                "    ♚   ",
+                "♜       ",
+                "        ",
+                "        ",
+                "        ",
+                "        ",
+                "     ♘  ",
+                "    ♔   ",
+            ],
+        );
+        assert_eq!(game_phase(&board), 3);
+    }
+
+    #[test]
+    fn evaluate_pawn_structure() {
+        let mode = standard_chess();
+        let params = EvalParams::default();
+        let board = board_of_str(
+            &mode.bounds,
+            [
+                "    ♚   ",
+                "      ♟ ",
+                "        ",
+                "        ",
+                "        ",
+                "♙       ",
+                "♙   ♙ ♙ ",
+                "    ♔   ",
+            ],
+        );
+        let mut expected = Tapered::default();
+        expected.add(params.doubled_pawn, 1);
+        expected.add(params.isolated_pawn, 4);
+        expected.add(params.passed_pawn[1], 2);
+        expected.add(params.passed_pawn[2], 1);
+        assert_eq!(pawn_structure(&board, &mode.bounds, &Color::White, &params), expected);
+        let mut expected = Tapered::default();
+        expected.add(params.isolated_pawn, 1);
+        assert_eq!(pawn_structure(&board, &mode.bounds, &Color::Black, &params), expected);
+    }
+
+    #[test]
+    fn evaluate_king_safety() {
+        let mode = standard_chess();
+        let params = EvalParams::default();
+        let game = game_of_mode(mode);
+        let mut expected = Tapered::default();
+        expected.add(params.pawn_shield, 3);
+        assert_eq!(king_safety(&game.board, &game.players, &Color::White, &params), expected);
+    }
+
+    #[test]
+    fn params_to_vec_and_back() {
+        let params = EvalParams::default();
+        let values = params.to_vec();
+        assert_eq!(values.len(), 818);
+        assert_eq!(EvalParams::try_of_vec(&values), Some(params));
+        assert_eq!(EvalParams::try_of_vec(&values[1..]), None);
+    }
+
+    #[test]
+    fn params_to_string_and_back() {
+        let params = EvalParams { bishop_pair: Tapered::of(-7, 11), ..Default::default() };
+        let s = params.to_string();
+        assert!(s.starts_with("material 100 120 320 300"));
+        assert!(s.contains("\nbishop_pair -7 11\n"));
+        assert_eq!(EvalParams::try_of(&s), Some(params.clone()));
+        assert_eq!(EvalParams::of(&s), params);
+        assert_eq!(EvalParams::try_of("material 1 2"), None);
+        assert_eq!(EvalParams::try_of(&s.replace("bishop_pair -7 11", "bishop_pair x 11")), None);
+    }
+}
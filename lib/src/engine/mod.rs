@@ -0,0 +1,166 @@
+use crate::{
+    game::{
+        board::GameBoard,
+        mov::{GameMove, GameMoveType, PROMOTIONS, PieceMoveType, is_promotion},
+        player::PlayerMoves,
+    },
+    mov::Mov,
+    pos::Pos,
+};
+
+pub mod eval;
+pub mod ordering;
+pub mod search;
+pub mod tt;
+pub mod tuning;
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct SearchMove {
+    pub from: Pos,
+    pub to: Pos,
+    pub typ: PieceMoveType,
+}
+
+impl SearchMove {
+    pub fn is_capture(&self, board: &GameBoard) -> bool {
+        self.typ == PieceMoveType::EnPassant
+            || ((self.typ == PieceMoveType::Default || is_promotion(&self.typ))
+                && board.contains_key(&self.to))
+    }
+
+    pub fn to_game_move(&self, board: &GameBoard) -> Option<GameMove> {
+        let piece = *board.get(&self.from)?;
+        let typ = match self.typ {
+            PieceMoveType::Default => {
+                if board.contains_key(&self.to) {
+                    GameMoveType::Capture
+                } else {
+                    GameMoveType::Default
+                }
+            }
+            PieceMoveType::EnPassant => GameMoveType::EnPassant,
+            PieceMoveType::LongCastling => GameMoveType::LongCastling,
+            PieceMoveType::ShortCastling => GameMoveType::ShortCastling,
+            PieceMoveType::PromotionToQueen => GameMoveType::PromotionToQueen,
+            PieceMoveType::PromotionToRook => GameMoveType::PromotionToRook,
+            PieceMoveType::PromotionToBishop => GameMoveType::PromotionToBishop,
+            PieceMoveType::PromotionToKnight => GameMoveType::PromotionToKnight,
+        };
+        Some(GameMove { mov: Mov { piece, from: self.from.clone(), to: self.to.clone() }, typ })
+    }
+}
+
+pub fn search_moves_of(moves: &PlayerMoves) -> Vec<SearchMove> {
+    let mut result: Vec<SearchMove> = moves
+        .iter()
+        .flat_map(|(from, piece_moves)| {
+            piece_moves.iter().flat_map(|(to, typ)| {
+                let typs = if is_promotion(typ) { PROMOTIONS.to_vec() } else { vec![typ.clone()] };
+                typs.into_iter().map(|typ| SearchMove { from: from.clone(), to: to.clone(), typ })
+            })
+        })
+        .collect();
+    result.sort_by_key(|mv| (mv.from.row, mv.from.col, mv.to.row, mv.to.col));
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        game::{
+            board::board_of_str,
+            mode::standard_chess,
+            mov::{GameMove, PieceMoveType},
+        },
+        pos::pos_of,
+    };
+
+    use super::{SearchMove, search_moves_of};
+
+    #[test]
+    fn search_move_to_game_move() {
+        let mode = standard_chess();
+        let board = board_of_str(
+            &mode.bounds,
+            [
+                "    ♚   ",
+                "        ",
+                "        ",
+                "   ♟    ",
+                "    ♙   ",
+                "        ",
+                "        ",
+                "♖   ♔   ",
+            ],
+        );
+        let default =
+            SearchMove { from: pos_of("E4"), to: pos_of("E5"), typ: PieceMoveType::Default };
+        let capture =
+            SearchMove { from: pos_of("E4"), to: pos_of("D5"), typ: PieceMoveType::Default };
+        let castling =
+            SearchMove { from: pos_of("E1"), to: pos_of("A1"), typ: PieceMoveType::LongCastling };
+        let empty =
+            SearchMove { from: pos_of("E3"), to: pos_of("E4"), typ: PieceMoveType::Default };
+        assert_eq!(default.to_game_move(&board), Some(GameMove::default_of('♙', "E4", "E5")));
+        assert_eq!(capture.to_game_move(&board), Some(GameMove::capture_of('♙', "E4", "D5")));
+        assert_eq!(
+            castling.to_game_move(&board),
+            Some(GameMove::long_castling_of('♔', "E1", "A1"))
+        );
+        assert_eq!(empty.to_game_move(&board), None);
+        assert!(!default.is_capture(&board));
+        assert!(capture.is_capture(&board));
+    }
+
+    #[test]
+    fn search_moves_of_player_moves() {
+        let moves = [
+            (
+                pos_of("B1"),
+                [(pos_of("C3"), PieceMoveType::Default), (pos_of("A3"), PieceMoveType::Default)]
+                    .into(),
+            ),
+            (pos_of("A2"), [(pos_of("A3"), PieceMoveType::Default)].into()),
+        ]
+        .into();
+        assert_eq!(
+            search_moves_of(&moves),
+            vec![
+                SearchMove { from: pos_of("B1"), to: pos_of("A3"), typ: PieceMoveType::Default },
+                SearchMove { from: pos_of("B1"), to: pos_of("C3"), typ: PieceMoveType::Default },
+                SearchMove { from: pos_of("A2"), to: pos_of("A3"), typ: PieceMoveType::Default },
+            ]
+        );
+    }
+
+    #[test]
+    fn search_moves_of_promotion() {
+        let moves =
+            [(pos_of("C7"), [(pos_of("C8"), PieceMoveType::PromotionToQueen)].into())].into();
+        assert_eq!(
+            search_moves_of(&moves),
+            vec![
+                SearchMove {
+                    from: pos_of("C7"),
+                    to: pos_of("C8"),
+                    typ: PieceMoveType::PromotionToQueen
+                },
+                SearchMove {
+                    from: pos_of("C7"),
+                    to: pos_of("C8"),
+                    typ: PieceMoveType::PromotionToRook
+                },
+                SearchMove {
+                    from: pos_of("C7"),
+                    to: pos_of("C8"),
+                    typ: PieceMoveType::PromotionToBishop
+                },
+                SearchMove {
+                    from: pos_of("C7"),
+                    to: pos_of("C8"),
+                    typ: PieceMoveType::PromotionToKnight
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,188 @@
+use std::collections::HashMap;
+
+use crate::{game::board::GameBoard, piece::PieceType, pos::Pos};
+
+use super::{SearchMove, eval::piece_value};
+
+const TT_MOVE_SCORE: i32 = 1_000_000;
+const CAPTURE_SCORE: i32 = 100_000;
+const FIRST_KILLER_SCORE: i32 = 90_000;
+const SECOND_KILLER_SCORE: i32 = 80_000;
+
+pub fn mvv_lva(board: &GameBoard, mv: &SearchMove) -> i32 {
+    let victim = board
+        .get(&mv.to)
+        .map(|piece| piece_value(&piece.typ))
+        .unwrap_or(piece_value(&PieceType::Pawn));
+    let attacker = board.get(&mv.from).map(|piece| piece_value(&piece.typ)).unwrap_or(0);
+    victim * 10 - attacker / 10
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Killers {
+    moves: Vec<[Option<SearchMove>; 2]>,
+}
+
+impl Killers {
+    pub fn of(max_ply: usize) -> Self {
+        Killers { moves: vec![[None, None]; max_ply] }
+    }
+
+    pub fn store(&mut self, ply: usize, mv: &SearchMove) {
+        if let Some(slots) = self.moves.get_mut(ply)
+            && slots[0].as_ref() != Some(mv)
+        {
+            slots[1] = slots[0].take();
+            slots[0] = Some(mv.clone());
+        }
+    }
+
+    pub fn rank(&self, ply: usize, mv: &SearchMove) -> Option<usize> {
+        self.moves.get(ply)?.iter().position(|killer| killer.as_ref() == Some(mv))
+    }
+
+    pub fn clear(&mut self) {
+        self.moves.iter_mut().for_each(|slots| *slots = [None, None]);
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct HistoryTable {
+    scores: HashMap<(Pos, Pos), i32>,
+}
+
+impl HistoryTable {
+    pub fn store(&mut self, mv: &SearchMove, depth: u8) {
+        let bonus = i32::from(depth) * i32::from(depth);
+        let score = self.scores.entry((mv.from.clone(), mv.to.clone())).or_insert(0);
+        *score = (*score + bonus).min(SECOND_KILLER_SCORE - 1);
+    }
+
+    pub fn score(&self, mv: &SearchMove) -> i32 {
+        self.scores.get(&(mv.from.clone(), mv.to.clone())).copied().unwrap_or(0)
+    }
+
+    pub fn clear(&mut self) {
+        self.scores.clear();
+    }
+}
+
+pub fn score_move(
+    board: &GameBoard,
+    mv: &SearchMove,
+    tt_move: Option<&SearchMove>,
+    killers: &Killers,
+    history: &HistoryTable,
+    ply: usize,
+) -> i32 {
+    if tt_move == Some(mv) {
+        return TT_MOVE_SCORE;
+    }
+    if mv.is_capture(board) {
+        return CAPTURE_SCORE + mvv_lva(board, mv);
+    }
+    match killers.rank(ply, mv) {
+        Some(0) => FIRST_KILLER_SCORE,
+        Some(_) => SECOND_KILLER_SCORE,
+        None => history.score(mv),
+    }
+}
+
+pub fn order_moves(
+    board: &GameBoard,
+    moves: &mut [SearchMove],
+    tt_move: Option<&SearchMove>,
+    killers: &Killers,
+    history: &HistoryTable,
+    ply: usize,
+) {
+    moves.sort_by_cached_key(|mv| -score_move(board, mv, tt_move, killers, history, ply));
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        engine::SearchMove,
+        game::{board::board_of_str, mode::standard_chess, mov::PieceMoveType},
+        pos::pos_of,
+    };
+
+    use super::{HistoryTable, Killers, mvv_lva, order_moves};
+
+    fn mv(from: &str, to: &str) -> SearchMove {
+        SearchMove { from: pos_of(from), to: pos_of(to), typ: PieceMoveType::Default }
+    }
+
+    #[test]
+    fn mvv_lva_prefers_valuable_victims_and_cheap_attackers() {
+        let mode = standard_chess();
+        let board = board_of_str(
+            &mode.bounds,
+            [
+                "    ♚   ",
+                "        ",
+                "   ♛ ♟  ",
+                "    ♙   ",
+                "        ",
+                "        ",
+                "   ♕    ",
+                "    ♔   ",
+            ],
+        );
+        assert!(mvv_lva(&board, &mv("E5", "D6")) > mvv_lva(&board, &mv("D2", "D6")));
+        assert!(mvv_lva(&board, &mv("D2", "D6")) > mvv_lva(&board, &mv("E5", "F6")));
+    }
+
+    #[test]
+    fn killers() {
+        let mut killers = Killers::of(4);
+        killers.store(1, &mv("A2", "A3"));
+        killers.store(1, &mv("B2", "B3"));
+        killers.store(1, &mv("B2", "B3"));
+        assert_eq!(killers.rank(1, &mv("B2", "B3")), Some(0));
+        assert_eq!(killers.rank(1, &mv("A2", "A3")), Some(1));
+        assert_eq!(killers.rank(0, &mv("A2", "A3")), None);
+        killers.store(1, &mv("C2", "C3"));
+        assert_eq!(killers.rank(1, &mv("A2", "A3")), None);
+        killers.clear();
+        assert_eq!(killers.rank(1, &mv("C2", "C3")), None);
+    }
+
+    #[test]
+    fn history() {
+        let mut history = HistoryTable::default();
+        history.store(&mv("A2", "A3"), 3);
+        history.store(&mv("A2", "A3"), 2);
+        assert_eq!(history.score(&mv("A2", "A3")), 13);
+        assert_eq!(history.score(&mv("B2", "B3")), 0);
+    }
+
+    #[test]
+    fn order_moves_tt_captures_killers_history() {
+        let mode = standard_chess();
+        let board = board_of_str(
+            &mode.bounds,
+            [
+                "    ♚   ",
+                "        ",
+                "   ♛    ",
+                "    ♙   ",
+                "        ",
+                "        ",
+                "♙♙      ",
+                "    ♔   ",
+            ],
+        );
+        let mut killers = Killers::of(4);
+        killers.store(0, &mv("B2", "B3"));
+        let mut history = HistoryTable::default();
+        history.store(&mv("A2", "A4"), 2);
+        let mut moves =
+            vec![mv("A2", "A3"), mv("A2", "A4"), mv("B2", "B3"), mv("E5", "D6"), mv("E1", "F1")];
+        order_moves(&board, &mut moves, Some(&mv("E1", "F1")), &killers, &history, 0);
+        assert_eq!(
+            moves,
+            vec![mv("E1", "F1"), mv("E5", "D6"), mv("B2", "B3"), mv("A2", "A4"), mv("A2", "A3")]
+        );
+    }
+}
@@ -0,0 +1,587 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::{
+    color::Color,
+    game::{
+        game::Game,
+        mov::GameMove,
+        rule::{
+            check::is_in_check, move_piece::play_move_with_promotion,
+            repetition::repetition_hashes, turn::evaluate_turn,
+        },
+        zobrist::GameHash,
+    },
+    tablebase::syzygy::{Tablebase, Wdl},
+    time::TimeSource,
+};
+
+use super::{
+    SearchMove,
+    eval::{EvalParams, evaluate},
+    ordering::{HistoryTable, Killers, order_moves},
+    search_moves_of,
+    tt::{Bound, TranspositionTable, TtEntry},
+};
+
+pub const MATE: i32 = 30_000;
+pub const INFINITY: i32 = 32_000;
+pub const MAX_PLY: usize = 64;
+pub const TB_WIN: i32 = MATE - 2 * MAX_PLY as i32;
+const MAX_DEPTH: u8 = 32;
+const TIME_CHECK_INTERVAL: u64 = 64;
+
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct SearchLimits {
+    pub depth: Option<u8>,
+    pub nodes: Option<u64>,
+    pub time: Option<u64>,
+    pub mate: Option<u8>,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct SearchResult {
+    pub best_move: Option<GameMove>,
+    pub pv: Vec<GameMove>,
+    pub score: i32,
+    pub depth: u8,
+    pub nodes: u64,
+}
+
+pub fn is_mate_score(score: i32) -> bool {
+    score.abs() >= MATE - MAX_PLY as i32
+}
+
+pub fn tablebase_score(wdl: Wdl, ply: usize) -> i32 {
+    match wdl {
+        Wdl::Win => TB_WIN - ply as i32,
+        Wdl::Loss => -TB_WIN + ply as i32,
+        _ => wdl.value(),
+    }
+}
+
+fn score_to_tt(score: i32, ply: usize) -> i32 {
+    if score >= MATE - MAX_PLY as i32 {
+        score + ply as i32
+    } else if score <= -MATE + MAX_PLY as i32 {
+        score - ply as i32
+    } else {
+        score
+    }
+}
+
+fn score_from_tt(score: i32, ply: usize) -> i32 {
+    if score >= MATE - MAX_PLY as i32 {
+        score - ply as i32
+    } else if score <= -MATE + MAX_PLY as i32 {
+        score + ply as i32
+    } else {
+        score
+    }
+}
+
+pub(crate) fn legal_moves_of(game: &Game) -> Vec<SearchMove> {
+    let turn = evaluate_turn(&game.start, &game.history);
+    game.players.get(&turn).map(|player| search_moves_of(&player.moves)).unwrap_or_default()
+}
+
+pub(crate) fn game_after(game: &Game, mv: &SearchMove) -> Game {
+    let mut child = game.clone();
+    play_move_with_promotion(&mut child, &mv.from, &mv.to, Some(&mv.typ));
+    child
+}
+
+fn game_moves_of(game: &Game, moves: &[SearchMove]) -> Vec<GameMove> {
+    let mut result = Vec::new();
+    let mut curr: Option<Game> = None;
+    for mv in moves {
+        let game_ref = curr.as_ref().unwrap_or(game);
+        match mv.to_game_move(&game_ref.board) {
+            Some(game_move) => result.push(game_move),
+            None => break,
+        }
+        curr = Some(game_after(game_ref, mv));
+    }
+    result
+}
+
+pub struct Engine {
+    pub params: EvalParams,
+    pub tablebase: Tablebase,
+    tt: TranspositionTable,
+    killers: Killers,
+    history: HistoryTable,
+}
+
+impl Engine {
+    pub fn of(tt_size: usize) -> Self {
+        Engine {
+            params: EvalParams::default(),
+            tablebase: Tablebase::default(),
+            tt: TranspositionTable::of(tt_size),
+            killers: Killers::of(MAX_PLY),
+            history: HistoryTable::default(),
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.tt.clear();
+        self.killers.clear();
+        self.history.clear();
+    }
+
+    pub fn search(
+        &mut self,
+        game: &Game,
+        limits: &SearchLimits,
+        time_source: &dyn TimeSource,
+        stop: &AtomicBool,
+        on_iteration: &mut dyn FnMut(&SearchResult),
+    ) -> SearchResult {
+        self.killers.clear();
+        if let Some((mv, wdl)) = self.tablebase.best_move(game) {
+            let best_move = mv.to_game_move(&game.board);
+            let result = SearchResult {
+                best_move: best_move.clone(),
+                pv: best_move.into_iter().collect(),
+                score: tablebase_score(wdl, 0),
+                depth: 1,
+                nodes: 1,
+            };
+            on_iteration(&result);
+            return result;
+        }
+        let mut search = Search {
+            engine: self,
+            limits,
+            time_source,
+            stop,
+            start: time_source.now_ms(),
+            nodes: 0,
+            aborted: false,
+            path: repetition_hashes(game),
+        };
+        let fallback = legal_moves_of(game).into_iter().next();
+        let mut result = SearchResult {
+            best_move: fallback.as_ref().and_then(|mv| mv.to_game_move(&game.board)),
+            pv: Vec::new(),
+            score: 0,
+            depth: 0,
+            nodes: 0,
+        };
+        if fallback.is_none() {
+            return result;
+        }
+        // a mate in n moves is at most 2n - 1 plies deep, the loop stops once it is found
+        let mate_depth = limits.mate.map(|moves| moves.saturating_mul(2).saturating_sub(1));
+        let max_depth = [limits.depth, mate_depth]
+            .into_iter()
+            .flatten()
+            .min()
+            .unwrap_or(MAX_DEPTH)
+            .clamp(1, MAX_DEPTH);
+        for depth in 1..=max_depth {
+            let mut pv = Vec::new();
+            let score = search.negamax(game, depth, 0, -INFINITY, INFINITY, &mut pv);
+            if search.aborted && (depth > 1 || pv.is_empty()) {
+                result.nodes = search.nodes;
+                break;
+            }
+            let pv = game_moves_of(game, &pv);
+            result = SearchResult {
+                best_move: pv.first().cloned().or(result.best_move),
+                pv,
+                score,
+                depth,
+                nodes: search.nodes,
+            };
+            on_iteration(&result);
+            if search.aborted || is_mate_score(score) || search.soft_time_exceeded() {
+                break;
+            }
+        }
+        result
+    }
+}
+
+struct Search<'a> {
+    engine: &'a mut Engine,
+    limits: &'a SearchLimits,
+    time_source: &'a dyn TimeSource,
+    stop: &'a AtomicBool,
+    start: u64,
+    nodes: u64,
+    aborted: bool,
+    path: Vec<GameHash>,
+}
+
+impl Search<'_> {
+    fn evaluate(&self, game: &Game, turn: &Color) -> i32 {
+        evaluate(&game.board, &game.bounds, &game.players, turn, &self.engine.params)
+    }
+
+    fn elapsed(&self) -> u64 {
+        self.time_source.now_ms().saturating_sub(self.start)
+    }
+
+    fn soft_time_exceeded(&self) -> bool {
+        self.limits.time.is_some_and(|time| self.elapsed() >= time / 2)
+    }
+
+    fn should_abort(&mut self) -> bool {
+        if self.aborted {
+            return true;
+        }
+        if self.stop.load(Ordering::Relaxed) {
+            self.aborted = true;
+        }
+        if self.limits.nodes.is_some_and(|nodes| self.nodes >= nodes) {
+            self.aborted = true;
+        }
+        if self.nodes.is_multiple_of(TIME_CHECK_INTERVAL)
+            && self.limits.time.is_some_and(|time| self.elapsed() >= time)
+        {
+            self.aborted = true;
+        }
+        self.aborted
+    }
+
+    fn negamax(
+        &mut self,
+        game: &Game,
+        depth: u8,
+        ply: usize,
+        mut alpha: i32,
+        beta: i32,
+        pv: &mut Vec<SearchMove>,
+    ) -> i32 {
+        if self.should_abort() {
+            return 0;
+        }
+        self.nodes += 1;
+        if ply > 0 && self.path.contains(&game.hash) {
+            return 0;
+        }
+        let moves = legal_moves_of(game);
+        let turn = evaluate_turn(&game.start, &game.history);
+        if moves.is_empty() {
+            return if is_in_check(&game.board, &game.players, &game.start, &game.history) {
+                -MATE + ply as i32
+            } else {
+                0
+            };
+        }
+        if ply > 0
+            && let Some(wdl) = self.engine.tablebase.probe_wdl(game)
+        {
+            return tablebase_score(wdl, ply);
+        }
+        if depth == 0 || ply >= MAX_PLY - 1 {
+            return self.quiescence(game, ply, alpha, beta);
+        }
+        let mut tt_move = None;
+        if let Some(entry) = self.engine.tt.get(game.hash) {
+            tt_move = entry.best_move.clone();
+            if ply > 0 && entry.depth >= depth {
+                let score = score_from_tt(entry.score, ply);
+                match entry.bound {
+                    Bound::Exact => return score,
+                    Bound::Lower if score >= beta => return score,
+                    Bound::Upper if score <= alpha => return score,
+                    _ => {}
+                }
+            }
+        }
+        let mut moves = moves;
+        order_moves(
+            &game.board,
+            &mut moves,
+            tt_move.as_ref(),
+            &self.engine.killers,
+            &self.engine.history,
+            ply,
+        );
+        let mut best_score = -INFINITY;
+        let mut best_move = None;
+        let mut bound = Bound::Upper;
+        for mv in moves {
+            let child = game_after(game, &mv);
+            if evaluate_turn(&child.start, &child.history) == turn {
+                continue;
+            }
+            let mut child_pv = Vec::new();
+            self.path.push(game.hash);
+            let score = -self.negamax(&child, depth - 1, ply + 1, -beta, -alpha, &mut child_pv);
+            self.path.pop();
+            if self.aborted {
+                return 0;
+            }
+            if score > best_score {
+                best_score = score;
+                best_move = Some(mv.clone());
+                if score > alpha {
+                    alpha = score;
+                    bound = Bound::Exact;
+                    pv.clear();
+                    pv.push(mv.clone());
+                    pv.extend(child_pv);
+                    if alpha >= beta {
+                        bound = Bound::Lower;
+                        if !mv.is_capture(&game.board) {
+                            self.engine.killers.store(ply, &mv);
+                            self.engine.history.store(&mv, depth);
+                        }
+                        break;
+                    }
+                }
+            }
+        }
+        if best_move.is_none() {
+            return self.evaluate(game, &turn);
+        }
+        self.engine.tt.insert(TtEntry {
+            hash: game.hash,
+            depth,
+            score: score_to_tt(best_score, ply),
+            bound,
+            best_move,
+        });
+        best_score
+    }
+
+    fn quiescence(&mut self, game: &Game, ply: usize, mut alpha: i32, beta: i32) -> i32 {
+        if self.should_abort() {
+            return 0;
+        }
+        self.nodes += 1;
+        let turn = evaluate_turn(&game.start, &game.history);
+        let stand_pat = self.evaluate(game, &turn);
+        if stand_pat >= beta || ply >= MAX_PLY - 1 {
+            return stand_pat;
+        }
+        alpha = alpha.max(stand_pat);
+        let mut captures: Vec<SearchMove> =
+            legal_moves_of(game).into_iter().filter(|mv| mv.is_capture(&game.board)).collect();
+        order_moves(
+            &game.board,
+            &mut captures,
+            None,
+            &self.engine.killers,
+            &self.engine.history,
+            ply,
+        );
+        for mv in captures {
+            let child = game_after(game, &mv);
+            if evaluate_turn(&child.start, &child.history) == turn {
+                continue;
+            }
+            let score = -self.quiescence(&child, ply + 1, -beta, -alpha);
+            if self.aborted {
+                return 0;
+            }
+            if score >= beta {
+                return score;
+            }
+            alpha = alpha.max(score);
+        }
+        alpha
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicBool;
+
+    use crate::{
+        game::{
+            board::board_of_str,
+            fen::game_of_fen,
+            mode::{GameMode, standard_chess},
+            mov::GameMove,
+            rule::{
+                init::game_of_mode,
+                move_piece::{play_move, refresh_moves},
+            },
+        },
+        pos::pos_of,
+        time::TimeSource,
+    };
+
+    use super::{Engine, MATE, SearchLimits, SearchResult, is_mate_score};
+
+    struct StoppedTimeSource;
+
+    impl TimeSource for StoppedTimeSource {
+        fn now_ms(&self) -> u64 {
+            0
+        }
+    }
+
+    fn search(mode: GameMode, limits: SearchLimits) -> SearchResult {
+        let game = game_of_mode(mode);
+        let mut engine = Engine::of(1 << 12);
+        engine.search(&game, &limits, &StoppedTimeSource, &AtomicBool::new(false), &mut |_| {})
+    }
+
+    #[test]
+    fn search_finds_mate_in_one() {
+        let bounds = standard_chess().bounds;
+        let initial_board = board_of_str(
+            &bounds,
+            [
+                "      ♚ ",
+                "     ♟♟♟",
+                "        ",
+                "        ",
+                "        ",
+                "        ",
+                "     ♙♙♙",
+                " ♕    ♔ ",
+            ],
+        );
+        let result = search(
+            GameMode { bounds, initial_board },
+            SearchLimits { depth: Some(3), ..Default::default() },
+        );
+        assert_eq!(result.best_move, Some(GameMove::default_of('♕', "B1", "B8")));
+        assert_eq!(result.score, MATE - 1);
+        assert!(is_mate_score(result.score));
+    }
+
+    #[test]
+    fn search_mate_limit() {
+        let bounds = standard_chess().bounds;
+        let initial_board = board_of_str(
+            &bounds,
+            [
+                "       ♚",
+                "        ",
+                "        ",
+                "        ",
+                "        ",
+                "        ",
+                " ♖      ",
+                "♖   ♔   ",
+            ],
+        );
+        let mode = GameMode { bounds, initial_board };
+        let result = search(mode.clone(), SearchLimits { mate: Some(2), ..Default::default() });
+        assert_eq!(result.score, MATE - 3);
+        assert_eq!(result.depth, 3);
+        let result = search(mode, SearchLimits { mate: Some(1), ..Default::default() });
+        assert!(!is_mate_score(result.score));
+        assert_eq!(result.depth, 1);
+    }
+
+    #[test]
+    fn search_captures_hanging_queen() {
+        let bounds = standard_chess().bounds;
+        let initial_board = board_of_str(
+            &bounds,
+            [
+                "    ♚   ",
+                "        ",
+                "        ",
+                "   ♛    ",
+                "        ",
+                "  ♘     ",
+                "        ",
+                "    ♔   ",
+            ],
+        );
+        let result = search(
+            GameMode { bounds, initial_board },
+            SearchLimits { depth: Some(2), ..Default::default() },
+        );
+        assert_eq!(result.best_move, Some(GameMove::capture_of('♘', "C3", "D5")));
+        assert_eq!(result.pv.first(), Some(&GameMove::capture_of('♘', "C3", "D5")));
+        assert_eq!(result.depth, 2);
+        assert!(result.score > 300);
+    }
+
+    #[test]
+    fn search_respects_node_limit() {
+        let result =
+            search(standard_chess(), SearchLimits { nodes: Some(50), ..Default::default() });
+        assert!(result.best_move.is_some());
+        assert!(result.nodes <= 51);
+    }
+
+    #[test]
+    fn search_repetition_of_game_history() {
+        let mut game = game_of_fen("k7/8/8/8/q7/8/8/7K w - - 0 1");
+        for (from, to) in [("H1", "G1"), ("A8", "B8"), ("G1", "H1"), ("B8", "A8")] {
+            play_move(&mut game, &pos_of(from), &pos_of(to));
+        }
+        let mut engine = Engine::of(1 << 12);
+        let result = engine.search(
+            &game,
+            &SearchLimits { depth: Some(1), ..Default::default() },
+            &StoppedTimeSource,
+            &AtomicBool::new(false),
+            &mut |_| {},
+        );
+        assert_eq!(result.best_move, Some(GameMove::default_of('♔', "H1", "G1")));
+        assert_eq!(result.score, 0);
+    }
+
+    #[test]
+    fn search_stopped() {
+        let game = game_of_mode(standard_chess());
+        let mut engine = Engine::of(1 << 12);
+        let result = engine.search(
+            &game,
+            &SearchLimits::default(),
+            &StoppedTimeSource,
+            &AtomicBool::new(true),
+            &mut |_| {},
+        );
+        assert!(result.best_move.is_some());
+        assert_eq!(result.depth, 0);
+    }
+
+    #[test]
+    fn search_reports_iterations() {
+        let game = game_of_mode(standard_chess());
+        let mut engine = Engine::of(1 << 12);
+        let mut depths = Vec::new();
+        engine.search(
+            &game,
+            &SearchLimits { depth: Some(2), ..Default::default() },
+            &StoppedTimeSource,
+            &AtomicBool::new(false),
+            &mut |result| depths.push(result.depth),
+        );
+        assert_eq!(depths, vec![1, 2]);
+    }
+
+    #[test]
+    fn search_without_moves() {
+        let bounds = standard_chess().bounds;
+        let initial_board = board_of_str(
+            &bounds,
+            [
+                "       ♚",
+                "     ♕  ",
+                "      ♔ ",
+                "        ",
+                "        ",
+                "        ",
+                "        ",
+                "        ",
+            ],
+        );
+        let mode = GameMode { bounds, initial_board };
+        let mut game = game_of_mode(mode);
+        game.history.push(GameMove::default_of('♕', "F6", "F7"));
+        refresh_moves(&mut game);
+        let mut engine = Engine::of(16);
+        let result = engine.search(
+            &game,
+            &SearchLimits { depth: Some(1), ..Default::default() },
+            &StoppedTimeSource,
+            &AtomicBool::new(false),
+            &mut |_| {},
+        );
+        assert_eq!(result.best_move, None);
+    }
+}
@@ -0,0 +1,111 @@
+use crate::game::zobrist::GameHash;
+
+use super::SearchMove;
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum Bound {
+    Exact,
+    Lower,
+    Upper,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct TtEntry {
+    pub hash: GameHash,
+    pub depth: u8,
+    pub score: i32,
+    pub bound: Bound,
+    pub best_move: Option<SearchMove>,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct TranspositionTable {
+    entries: Vec<Option<TtEntry>>,
+}
+
+impl TranspositionTable {
+    pub fn of(size: usize) -> Self {
+        TranspositionTable { entries: vec![None; size.max(1)] }
+    }
+
+    fn index(&self, hash: GameHash) -> usize {
+        (hash % self.entries.len() as u64) as usize
+    }
+
+    pub fn get(&self, hash: GameHash) -> Option<&TtEntry> {
+        self.entries[self.index(hash)].as_ref().filter(|entry| entry.hash == hash)
+    }
+
+    pub fn insert(&mut self, entry: TtEntry) {
+        let index = self.index(entry.hash);
+        let slot = &mut self.entries[index];
+        let replace = match slot {
+            Some(current) => current.hash != entry.hash || entry.depth >= current.depth,
+            None => true,
+        };
+        if replace {
+            *slot = Some(entry);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.iter_mut().for_each(|entry| *entry = None);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{engine::SearchMove, game::mov::PieceMoveType, pos::pos_of};
+
+    use super::{Bound, TranspositionTable, TtEntry};
+
+    fn entry(hash: u64, depth: u8, score: i32) -> TtEntry {
+        TtEntry {
+            hash,
+            depth,
+            score,
+            bound: Bound::Exact,
+            best_move: Some(SearchMove {
+                from: pos_of("E2"),
+                to: pos_of("E4"),
+                typ: PieceMoveType::Default,
+            }),
+        }
+    }
+
+    #[test]
+    fn insert_and_get() {
+        let mut tt = TranspositionTable::of(16);
+        assert_eq!(tt.get(3), None);
+        tt.insert(entry(3, 2, 10));
+        assert_eq!(tt.get(3), Some(&entry(3, 2, 10)));
+        assert_eq!(tt.get(19), None);
+    }
+
+    #[test]
+    fn insert_keeps_deeper_entry_of_same_position() {
+        let mut tt = TranspositionTable::of(16);
+        tt.insert(entry(3, 4, 10));
+        tt.insert(entry(3, 2, 20));
+        assert_eq!(tt.get(3), Some(&entry(3, 4, 10)));
+        tt.insert(entry(3, 5, 30));
+        assert_eq!(tt.get(3), Some(&entry(3, 5, 30)));
+    }
+
+    #[test]
+    fn insert_replaces_other_position() {
+        let mut tt = TranspositionTable::of(16);
+        tt.insert(entry(3, 4, 10));
+        tt.insert(entry(19, 1, 20));
+        assert_eq!(tt.get(3), None);
+        assert_eq!(tt.get(19), Some(&entry(19, 1, 20)));
+    }
+
+    #[test]
+    fn clear() {
+        let mut tt = TranspositionTable::of(16);
+        tt.insert(entry(3, 4, 10));
+        tt.clear();
+        assert_eq!(tt.get(3), None);
+    }
+}
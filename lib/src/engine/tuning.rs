@@ -0,0 +1,108 @@
+use crate::{color::Color, game::game::Game};
+
+use super::eval::{EvalParams, evaluate};
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct TexelSample {
+    pub game: Game,
+    pub result: f64,
+}
+
+pub fn texel_sigmoid(score: i32, k: f64) -> f64 {
+    1.0 / (1.0 + 10f64.powf(-k * f64::from(score) / 400.0))
+}
+
+pub fn texel_error(samples: &[TexelSample], params: &EvalParams, k: f64) -> f64 {
+    if samples.is_empty() {
+        return 0.0;
+    }
+    let sum: f64 = samples
+        .iter()
+        .map(|sample| {
+            let score = evaluate(
+                &sample.game.board,
+                &sample.game.bounds,
+                &sample.game.players,
+                &Color::White,
+                params,
+            );
+            (sample.result - texel_sigmoid(score, k)).powi(2)
+        })
+        .sum();
+    sum / samples.len() as f64
+}
+
+pub fn texel_tune(samples: &[TexelSample], params: &EvalParams, k: f64, passes: u32) -> EvalParams {
+    let mut values = params.to_vec();
+    let mut best_error = texel_error(samples, params, k);
+    for _ in 0..passes {
+        let mut improved = false;
+        for i in 0..values.len() {
+            for delta in [1, -1] {
+                values[i] += delta;
+                let candidate = EvalParams::try_of_vec(&values).unwrap();
+                let error = texel_error(samples, &candidate, k);
+                if error < best_error {
+                    best_error = error;
+                    improved = true;
+                    break;
+                }
+                values[i] -= delta;
+            }
+        }
+        if !improved {
+            break;
+        }
+    }
+    EvalParams::try_of_vec(&values).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::game::{
+        board::board_of_str, game::empty_players, mode::standard_chess, rule::init::game_of_mode,
+    };
+
+    use super::{EvalParams, TexelSample, texel_error, texel_sigmoid, texel_tune};
+
+    #[test]
+    fn texel_sigmoid_bounds() {
+        assert_eq!(texel_sigmoid(0, 1.0), 0.5);
+        assert!(texel_sigmoid(400, 1.0) > 0.9);
+        assert!(texel_sigmoid(-400, 1.0) < 0.1);
+    }
+
+    #[test]
+    fn texel_error_of_samples() {
+        let params = EvalParams::default();
+        assert_eq!(texel_error(&[], &params, 1.0), 0.0);
+        let draw = TexelSample { game: game_of_mode(standard_chess()), result: 0.5 };
+        assert_eq!(texel_error(std::slice::from_ref(&draw), &params, 1.0), 0.0);
+        let win = TexelSample { result: 1.0, ..draw };
+        assert_eq!(texel_error(&[win], &params, 1.0), 0.25);
+    }
+
+    #[test]
+    fn texel_tune_reduces_error() {
+        let mut game = game_of_mode(standard_chess());
+        game.board = board_of_str(
+            &game.bounds,
+            [
+                "    ♚   ",
+                "        ",
+                "        ",
+                "        ",
+                "        ",
+                "        ",
+                "        ",
+                "   ♕♔   ",
+            ],
+        );
+        game.players = empty_players();
+        let samples = [TexelSample { game, result: 0.5 }];
+        let params = EvalParams::default();
+        let tuned = texel_tune(&samples, &params, 1.0, 2);
+        assert!(texel_error(&samples, &tuned, 1.0) < texel_error(&samples, &params, 1.0));
+        assert_ne!(tuned, params);
+    }
+}
@@ -1,7 +1,96 @@
-use crate::piece::Piece;
+use crate::{
+    color::Color,
+    game::board::GameBoard,
+    piece::{Piece, PieceType},
+};
 
 #[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct GameCapture {
     pub piece: Piece,
     pub at: u16,
 }
+
+const CAPTURE_ORDER: [PieceType; 6] = [
+    PieceType::Queen,
+    PieceType::Rook,
+    PieceType::Bishop,
+    PieceType::Knight,
+    PieceType::Pawn,
+    PieceType::King,
+];
+
+pub fn material_points(typ: &PieceType) -> i32 {
+    match typ {
+        PieceType::Pawn => 1,
+        PieceType::Knight | PieceType::Bishop => 3,
+        PieceType::Rook => 5,
+        PieceType::Queen => 9,
+        PieceType::King => 0,
+    }
+}
+
+pub fn material_balance(board: &GameBoard) -> i32 {
+    board
+        .values()
+        .map(|piece| match piece.color {
+            Color::White => material_points(&piece.typ),
+            Color::Black => -material_points(&piece.typ),
+        })
+        .sum()
+}
+
+pub fn captures_by_type(captures: &[GameCapture]) -> Vec<(PieceType, usize)> {
+    CAPTURE_ORDER
+        .into_iter()
+        .map(|typ| (typ, captures.iter().filter(|capture| capture.piece.typ == typ).count()))
+        .filter(|(_, count)| *count > 0)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        game::{board::board_of_str, mode::standard_chess},
+        piece::{Piece, PieceType},
+    };
+
+    use super::{GameCapture, captures_by_type, material_balance, material_points};
+
+    #[test]
+    fn material() {
+        assert_eq!(material_points(&PieceType::Queen), 9);
+        assert_eq!(material_points(&PieceType::King), 0);
+        let mode = standard_chess();
+        assert_eq!(material_balance(&mode.initial_board), 0);
+        let board = board_of_str(
+            &mode.bounds,
+            [
+                "    ♚   ",
+                "   ♟    ",
+                "        ",
+                "        ",
+                "        ",
+                "  ♘     ",
+                "   ♙    ",
+                "   ♕♔   ",
+            ],
+        );
+        assert_eq!(material_balance(&board), 12);
+    }
+
+    #[test]
+    fn captures_grouped_by_type() {
+        let captures = [
+            GameCapture { piece: Piece::of('♟'), at: 3 },
+            GameCapture { piece: Piece::of('♞'), at: 5 },
+            GameCapture { piece: Piece::of('♛'), at: 9 },
+            GameCapture { piece: Piece::of('♟'), at: 11 },
+        ];
+        assert_eq!(
+            captures_by_type(&captures),
+            vec![(PieceType::Queen, 1), (PieceType::Knight, 1), (PieceType::Pawn, 2)]
+        );
+        assert_eq!(captures_by_type(&[]), Vec::new());
+    }
+}
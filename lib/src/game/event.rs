@@ -0,0 +1,166 @@
+use crate::{
+    color::Color,
+    game::{
+        game::Game,
+        result::game_result,
+        rule::repetition::{is_fifty_move_rule, is_threefold_repetition},
+    },
+};
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DrawClaim {
+    ThreefoldRepetition,
+    FiftyMoveRule,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum GameAction {
+    Resign,
+    OfferDraw,
+    AcceptDraw,
+    DeclineDraw,
+    ClaimDraw(DrawClaim),
+    Abort,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GameEvent {
+    pub ply: usize,
+    pub color: Color,
+    pub action: GameAction,
+}
+
+pub fn pending_draw_offer(game: &Game) -> Option<Color> {
+    let event = game.events.last()?;
+    if event.action != GameAction::OfferDraw {
+        return None;
+    }
+    let opponent_moved = (event.ply..game.history.len())
+        .any(|ply| ply.is_multiple_of(2) != (event.color == game.start.turn));
+    if opponent_moved { None } else { Some(event.color) }
+}
+
+pub fn can_claim_draw(game: &Game, claim: &DrawClaim) -> bool {
+    match claim {
+        DrawClaim::ThreefoldRepetition => is_threefold_repetition(game),
+        DrawClaim::FiftyMoveRule => is_fifty_move_rule(game),
+    }
+}
+
+pub fn can_abort(game: &Game) -> bool {
+    game.history.len() < 2
+}
+
+pub fn play_action(game: &mut Game, color: &Color, action: GameAction) -> bool {
+    if game_result(game).is_some() {
+        return false;
+    }
+    let is_valid = match &action {
+        GameAction::Resign => true,
+        GameAction::OfferDraw => pending_draw_offer(game).is_none(),
+        GameAction::AcceptDraw | GameAction::DeclineDraw => {
+            pending_draw_offer(game).is_some_and(|offer| &offer != color)
+        }
+        GameAction::ClaimDraw(claim) => can_claim_draw(game, claim),
+        GameAction::Abort => can_abort(game),
+    };
+    if is_valid {
+        game.events.push(GameEvent { ply: game.history.len(), color: *color, action });
+    }
+    is_valid
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        color::Color,
+        game::{
+            mode::standard_chess,
+            rule::{init::game_of_mode, move_piece::play_move},
+        },
+        pos::pos_of,
+    };
+
+    use super::{
+        DrawClaim, GameAction, GameEvent, can_abort, can_claim_draw, pending_draw_offer,
+        play_action,
+    };
+
+    #[test]
+    fn draw_offer() {
+        let mut game = game_of_mode(standard_chess());
+        assert_eq!(pending_draw_offer(&game), None);
+        assert!(!play_action(&mut game, &Color::Black, GameAction::AcceptDraw));
+        assert!(play_action(&mut game, &Color::White, GameAction::OfferDraw));
+        assert_eq!(pending_draw_offer(&game), Some(Color::White));
+        assert!(!play_action(&mut game, &Color::White, GameAction::OfferDraw));
+        assert!(!play_action(&mut game, &Color::White, GameAction::AcceptDraw));
+        play_move(&mut game, &pos_of("E2"), &pos_of("E4"));
+        assert_eq!(pending_draw_offer(&game), Some(Color::White));
+        assert!(play_action(&mut game, &Color::Black, GameAction::DeclineDraw));
+        assert_eq!(pending_draw_offer(&game), None);
+        assert_eq!(
+            game.events,
+            vec![
+                GameEvent { ply: 0, color: Color::White, action: GameAction::OfferDraw },
+                GameEvent { ply: 1, color: Color::Black, action: GameAction::DeclineDraw },
+            ]
+        );
+    }
+
+    #[test]
+    fn draw_offer_expires() {
+        let mut game = game_of_mode(standard_chess());
+        play_move(&mut game, &pos_of("E2"), &pos_of("E4"));
+        assert!(play_action(&mut game, &Color::White, GameAction::OfferDraw));
+        assert_eq!(pending_draw_offer(&game), Some(Color::White));
+        play_move(&mut game, &pos_of("E7"), &pos_of("E5"));
+        assert_eq!(pending_draw_offer(&game), None);
+        assert!(!play_action(&mut game, &Color::Black, GameAction::AcceptDraw));
+    }
+
+    #[test]
+    fn claim_draw() {
+        let mut game = game_of_mode(standard_chess());
+        assert!(!can_claim_draw(&game, &DrawClaim::ThreefoldRepetition));
+        assert!(!play_action(
+            &mut game,
+            &Color::White,
+            GameAction::ClaimDraw(DrawClaim::ThreefoldRepetition)
+        ));
+        let shuffle = [("G1", "F3"), ("G8", "F6"), ("F3", "G1"), ("F6", "G8")];
+        for (from, to) in shuffle.iter().chain(shuffle.iter()) {
+            play_move(&mut game, &pos_of(from), &pos_of(to));
+        }
+        assert!(can_claim_draw(&game, &DrawClaim::ThreefoldRepetition));
+        assert!(!can_claim_draw(&game, &DrawClaim::FiftyMoveRule));
+        assert!(play_action(
+            &mut game,
+            &Color::White,
+            GameAction::ClaimDraw(DrawClaim::ThreefoldRepetition)
+        ));
+    }
+
+    #[test]
+    fn abort() {
+        let mut game = game_of_mode(standard_chess());
+        assert!(can_abort(&game));
+        play_move(&mut game, &pos_of("E2"), &pos_of("E4"));
+        assert!(can_abort(&game));
+        play_move(&mut game, &pos_of("E7"), &pos_of("E5"));
+        assert!(!can_abort(&game));
+        assert!(!play_action(&mut game, &Color::White, GameAction::Abort));
+    }
+
+    #[test]
+    fn no_action_after_result() {
+        let mut game = game_of_mode(standard_chess());
+        assert!(play_action(&mut game, &Color::Black, GameAction::Resign));
+        assert!(!play_action(&mut game, &Color::White, GameAction::Resign));
+        assert!(!play_action(&mut game, &Color::White, GameAction::OfferDraw));
+        assert_eq!(game.events.len(), 1);
+    }
+}
@@ -0,0 +1,290 @@
+use manfredo::matrix::rect::rect_u8::contains;
+
+use crate::{
+    color::Color,
+    game::{
+        board::GameBoard,
+        game::{CastlingRights, Game, GameBounds, GameStart},
+        rule::init::game_of_board,
+    },
+    piece::{Piece, PieceType},
+    pos::{Pos, pos_to_string, pos_try_of},
+};
+
+fn piece_try_of_fen(c: char) -> Option<Piece> {
+    let color = if c.is_ascii_uppercase() { Color::White } else { Color::Black };
+    let typ = match c.to_ascii_lowercase() {
+        'p' => PieceType::Pawn,
+        'n' => PieceType::Knight,
+        'b' => PieceType::Bishop,
+        'r' => PieceType::Rook,
+        'q' => PieceType::Queen,
+        'k' => PieceType::King,
+        _ => return None,
+    };
+    Some(Piece { typ, color })
+}
+
+fn piece_to_fen(piece: &Piece) -> char {
+    let c = match piece.typ {
+        PieceType::Pawn => 'p',
+        PieceType::Knight => 'n',
+        PieceType::Bishop => 'b',
+        PieceType::Rook => 'r',
+        PieceType::Queen => 'q',
+        PieceType::King => 'k',
+    };
+    if piece.color == Color::White { c.to_ascii_uppercase() } else { c }
+}
+
+fn board_to_fen(board: &GameBoard, bounds: &GameBounds) -> String {
+    let rows: Vec<String> = (bounds.min.row..=bounds.max.row)
+        .rev()
+        .map(|row| {
+            let mut row_str = String::new();
+            let mut empty = 0;
+            for col in bounds.min.col..=bounds.max.col {
+                match board.get(&Pos::of(row, col)) {
+                    Some(piece) => {
+                        if empty > 0 {
+                            row_str += &empty.to_string();
+                            empty = 0;
+                        }
+                        row_str.push(piece_to_fen(piece));
+                    }
+                    None => empty += 1,
+                }
+            }
+            if empty > 0 {
+                row_str += &empty.to_string();
+            }
+            row_str
+        })
+        .collect();
+    rows.join("/")
+}
+
+fn board_try_of_fen(placement: &str) -> Option<(GameBoard, GameBounds)> {
+    let rows: Vec<&str> = placement.split('/').collect();
+    let len_row = u8::try_from(rows.len()).ok()?;
+    let mut board = GameBoard::new();
+    let mut len_col: Option<u8> = None;
+    for (i, row_str) in rows.iter().enumerate() {
+        let row = len_row - 1 - i as u8;
+        let mut col: u16 = 0;
+        let mut empty: u16 = 0;
+        for c in row_str.chars() {
+            if let Some(digit) = c.to_digit(10) {
+                empty = empty * 10 + digit as u16;
+                continue;
+            }
+            col += empty;
+            empty = 0;
+            board.insert(Pos::of(row, u8::try_from(col).ok()?), piece_try_of_fen(c)?);
+            col += 1;
+        }
+        col += empty;
+        let col = u8::try_from(col).ok()?;
+        if col == 0 || len_col.is_some_and(|len_col| len_col != col) {
+            return None;
+        }
+        len_col = Some(col);
+    }
+    Some((board, GameBounds::of(0, 0, len_row - 1, len_col? - 1)))
+}
+
+fn castling_try_of_fen(castling: &str) -> Option<CastlingRights> {
+    if castling != "-" && !castling.chars().all(|c| "KQkq".contains(c)) {
+        return None;
+    }
+    Some(CastlingRights {
+        white_short: castling.contains('K'),
+        white_long: castling.contains('Q'),
+        black_short: castling.contains('k'),
+        black_long: castling.contains('q'),
+    })
+}
+
+fn en_passant_try_of_fen(
+    board: &GameBoard,
+    bounds: &GameBounds,
+    turn: &Color,
+    en_passant: &str,
+) -> Option<Option<Pos>> {
+    if en_passant == "-" {
+        return Some(None);
+    }
+    let target = pos_try_of(&en_passant.to_ascii_uppercase())?;
+    let (color, target_row, from_row, to_row) = match turn {
+        Color::White => (Color::Black, 5, target.row.checked_add(1)?, target.row.checked_sub(1)?),
+        Color::Black => (Color::White, 2, target.row.checked_sub(1)?, target.row.checked_add(1)?),
+    };
+    if target.row != target_row || !contains(bounds, &target) {
+        return None;
+    }
+    let (from, to) = (Pos::of(from_row, target.col), Pos::of(to_row, target.col));
+    if board.contains_key(&target) || board.contains_key(&from) {
+        return None;
+    }
+    if board.get(&to) != Some(&Piece { typ: PieceType::Pawn, color }) {
+        return None;
+    }
+    Some(Some(target))
+}
+
+fn castling_to_fen(castling: &CastlingRights) -> String {
+    let rights = [
+        (castling.white_short, 'K'),
+        (castling.white_long, 'Q'),
+        (castling.black_short, 'k'),
+        (castling.black_long, 'q'),
+    ];
+    let fen: String = rights.into_iter().filter(|(right, _)| *right).map(|(_, c)| c).collect();
+    if fen.is_empty() { "-".into() } else { fen }
+}
+
+// the FEN of a board in the state the start describes, as for a game without moves
+pub fn fen_of(board: &GameBoard, bounds: &GameBounds, start: &GameStart) -> String {
+    format!(
+        "{} {} {} {} {} {}",
+        board_to_fen(board, bounds),
+        if start.turn == Color::White { "w" } else { "b" },
+        castling_to_fen(&start.castling),
+        start.en_passant.as_ref().map_or("-".into(), |pos| pos_to_string(pos).to_ascii_lowercase()),
+        start.halfmove_clock,
+        start.fullmove_number
+    )
+}
+
+pub fn game_try_of_fen(s: &str) -> Option<Game> {
+    let mut parts = s.split_whitespace();
+    let (board, bounds) = board_try_of_fen(parts.next()?)?;
+    let turn = match parts.next().unwrap_or("w") {
+        "w" => Color::White,
+        "b" => Color::Black,
+        _ => return None,
+    };
+    let castling = castling_try_of_fen(parts.next().unwrap_or("-"))?;
+    let en_passant = en_passant_try_of_fen(&board, &bounds, &turn, parts.next().unwrap_or("-"))?;
+    let halfmove_clock = parts.next().map_or(Some(0), |part| part.parse().ok())?;
+    let fullmove_number = parts.next().map_or(Some(1), |part| part.parse().ok())?;
+    if fullmove_number == 0 {
+        return None;
+    }
+    let start = GameStart { turn, castling, en_passant, halfmove_clock, fullmove_number };
+    Some(game_of_board(board, bounds, start))
+}
+
+pub fn game_of_fen(s: &str) -> Game {
+    game_try_of_fen(s).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        color::Color,
+        game::{
+            board::board_of_str,
+            game::GameBounds,
+            mode::standard_chess,
+            mov::special::castling::{long_castling_right, short_castling_right},
+            rule::{init::game_of_mode, repetition::halfmove_clock, turn::evaluate_turn},
+            zobrist::zobrist_of,
+        },
+        pos::pos_of,
+    };
+
+    use super::{fen_of, game_of_fen, game_try_of_fen};
+
+    #[test]
+    fn fen_of_game() {
+        for fen in [
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            "r3k2r/8/8/8/8/8/8/R3K2R b Kq - 0 1",
+            "4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 3",
+            "k9/10/10/10/10/10/10/10/10/9K w - - 7 40",
+        ] {
+            let game = game_of_fen(fen);
+            assert_eq!(fen_of(&game.board, &game.bounds, &game.start), fen);
+        }
+    }
+
+    #[test]
+    fn game_of_fen_start_position() {
+        let game = game_of_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1");
+        assert_eq!(game, game_of_mode(standard_chess()));
+    }
+
+    #[test]
+    fn game_of_fen_turn_and_castling() {
+        let game = game_of_fen("r3k2r/8/8/8/8/8/8/R3K2R b Kq - 0 1");
+        assert_eq!(game.history, Vec::new());
+        assert_eq!(evaluate_turn(&game.start, &game.history), Color::Black);
+        assert!(short_castling_right(&game.board, &game.start, &game.history, &Color::White));
+        assert!(!long_castling_right(&game.board, &game.start, &game.history, &Color::White));
+        assert!(!short_castling_right(&game.board, &game.start, &game.history, &Color::Black));
+        assert!(long_castling_right(&game.board, &game.start, &game.history, &Color::Black));
+        assert_eq!(game.hash, zobrist_of(&game.board, &game.start, &game.history));
+        assert_eq!(
+            game.board,
+            board_of_str(
+                &game.bounds,
+                [
+                    "♜   ♚  ♜",
+                    "        ",
+                    "        ",
+                    "        ",
+                    "        ",
+                    "        ",
+                    "        ",
+                    "♖   ♔  ♖",
+                ]
+            )
+        );
+    }
+
+    #[test]
+    fn game_of_fen_en_passant() {
+        let game = game_of_fen("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 3");
+        assert_eq!(evaluate_turn(&game.start, &game.history), Color::White);
+        let pawn_moves = game.players.get(&Color::White).unwrap().moves.get(&pos_of("E5")).unwrap();
+        assert!(pawn_moves.contains_key(&pos_of("D6")));
+        assert_eq!(game.start.en_passant, Some(pos_of("D6")));
+        assert_eq!(game.start.fullmove_number, 3);
+    }
+
+    #[test]
+    fn game_of_fen_move_counters() {
+        let game = game_of_fen("4k3/8/8/8/8/8/8/4K2R b K - 37 52");
+        assert_eq!(game.start.halfmove_clock, 37);
+        assert_eq!(game.start.fullmove_number, 52);
+        assert_eq!(halfmove_clock(&game.start, &game.history), 37);
+        let game = game_of_fen("4k3/8/8/8/8/8/8/4K2R b K -");
+        assert_eq!(game.start.halfmove_clock, 0);
+        assert_eq!(game.start.fullmove_number, 1);
+    }
+
+    #[test]
+    fn game_of_fen_bounds() {
+        let game = game_of_fen("k9/10/10/10/10/10/10/10/10/9K w - -");
+        assert_eq!(game.bounds, GameBounds::of(0, 0, 9, 9));
+        assert_eq!(game.board.len(), 2);
+    }
+
+    #[test]
+    fn game_try_of_fen_invalid() {
+        assert_eq!(game_try_of_fen(""), None);
+        assert_eq!(game_try_of_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBN w"), None);
+        assert_eq!(game_try_of_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNX w"), None);
+        assert_eq!(game_try_of_fen("4k3/8/8/8/8/8/8/4K3 x"), None);
+        assert_eq!(game_try_of_fen("4k3/8/8/8/8/8/8/4K3 w X"), None);
+        assert_eq!(game_try_of_fen("4k3/8/8/8/8/8/8/4K3 w - - x 1"), None);
+        assert_eq!(game_try_of_fen("4k3/8/8/8/8/8/8/4K3 w - - 0 0"), None);
+        assert_eq!(game_try_of_fen("4k3/8/8/8/8/8/8/4K3 w - e3"), None);
+        assert_eq!(game_try_of_fen("8/8/8/8/8/8/8/K6k w - a1 0 1"), None);
+        assert_eq!(game_try_of_fen("8/8/8/8/8/8/8/K6k b - h8 0 1"), None);
+        assert_eq!(game_try_of_fen("4k3/8/8/8/3p4/8/8/4K3 w - d3 0 1"), None);
+        assert_eq!(game_try_of_fen("4k3/8/8/8/3P4/8/8/4K3 w - d3 0 1"), None);
+        assert_eq!(game_try_of_fen("4k3/8/8/3pP3/8/8/8/4K3 b - d6 0 1"), None);
+    }
+}
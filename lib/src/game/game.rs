@@ -4,19 +4,89 @@ use manfredo::matrix::rect::rect_u8::RectU8;
 
 use crate::{
     color::Color,
-    game::{board::GameBoard, mov::GameMove, player::GamePlayer},
+    game::{
+        board::GameBoard, event::GameEvent, mov::GameMove, player::GamePlayer, zobrist::GameHash,
+    },
+    pos::Pos,
 };
 
 pub type GameBounds = RectU8;
 pub type GamePlayers = HashMap<Color, GamePlayer>;
 pub type GameHistory = Vec<GameMove>;
+pub type GameEvents = Vec<GameEvent>;
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Game {
+    #[cfg_attr(feature = "serde", serde(with = "crate::snapshot::board_serde"))]
     pub board: GameBoard,
+    #[cfg_attr(feature = "serde", serde(with = "crate::snapshot::bounds_serde"))]
     pub bounds: GameBounds,
     pub players: GamePlayers,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub start: GameStart,
     pub history: GameHistory,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) hash: GameHash,
+    pub events: GameEvents,
+}
+
+impl Game {
+    // kept in sync by the move functions, which are the only way to change the position
+    pub fn hash(&self) -> GameHash {
+        self.hash
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CastlingRights {
+    pub white_short: bool,
+    pub white_long: bool,
+    pub black_short: bool,
+    pub black_long: bool,
+}
+
+impl CastlingRights {
+    pub fn of(&self, color: &Color, short: bool) -> bool {
+        match (color, short) {
+            (Color::White, true) => self.white_short,
+            (Color::White, false) => self.white_long,
+            (Color::Black, true) => self.black_short,
+            (Color::Black, false) => self.black_long,
+        }
+    }
+}
+
+impl Default for CastlingRights {
+    fn default() -> Self {
+        CastlingRights { white_short: true, white_long: true, black_short: true, black_long: true }
+    }
+}
+
+// The state of the position before the first move of the history, as the board alone does not
+// tell the side to move, the castling rights, the en passant square and the move counters.
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GameStart {
+    pub turn: Color,
+    pub castling: CastlingRights,
+    #[cfg_attr(feature = "serde", serde(with = "crate::snapshot::pos_option_serde"))]
+    pub en_passant: Option<Pos>,
+    pub halfmove_clock: u16,
+    pub fullmove_number: u16,
+}
+
+impl Default for GameStart {
+    fn default() -> Self {
+        GameStart {
+            turn: Color::White,
+            castling: CastlingRights::default(),
+            en_passant: None,
+            halfmove_clock: 0,
+            fullmove_number: 1,
+        }
+    }
 }
 
 pub fn empty_players() -> GamePlayers {
@@ -0,0 +1,115 @@
+use crate::{
+    game::{
+        game::Game,
+        mov::{GameMove, GameMoveType, PieceMoveType},
+        rule::turn::evaluate_turn,
+    },
+    pos::{Pos, pos_to_string, pos_try_of},
+};
+
+fn square_try_of(s: &str) -> Option<(Pos, &str)> {
+    let letters = s.find(|c: char| !c.is_ascii_lowercase())?;
+    let digits = s[letters..].find(|c: char| !c.is_ascii_digit()).map_or(s.len(), |i| letters + i);
+    if letters == 0 || digits == letters {
+        return None;
+    }
+    Some((pos_try_of(&s[..digits].to_ascii_uppercase())?, &s[digits..]))
+}
+
+pub fn lan_try_of(game: &Game, s: &str) -> Option<(Pos, Pos)> {
+    let (from, rest) = square_try_of(s)?;
+    let (to, rest) = square_try_of(rest)?;
+    if !matches!(rest, "" | "q" | "r" | "b" | "n") {
+        return None;
+    }
+    let moves = game.players.get(&evaluate_turn(&game.start, &game.history))?.moves.get(&from)?;
+    if moves.contains_key(&to) {
+        return Some((from, to));
+    }
+    if from.row != to.row || from.col.abs_diff(to.col) != 2 {
+        return None;
+    }
+    let castling =
+        if to.col > from.col { PieceMoveType::ShortCastling } else { PieceMoveType::LongCastling };
+    moves.iter().find(|(_, typ)| **typ == castling).map(|(rook_pos, _)| (from, rook_pos.clone()))
+}
+
+pub fn lan_promotion_of(s: &str) -> Option<PieceMoveType> {
+    match s.chars().last()? {
+        'q' => Some(PieceMoveType::PromotionToQueen),
+        'r' => Some(PieceMoveType::PromotionToRook),
+        'b' => Some(PieceMoveType::PromotionToBishop),
+        'n' => Some(PieceMoveType::PromotionToKnight),
+        _ => None,
+    }
+}
+
+pub fn lan_to_string(game_move: &GameMove) -> String {
+    let from = &game_move.mov.from;
+    let to = match game_move.typ {
+        GameMoveType::ShortCastling => Pos::of(from.row, 6),
+        GameMoveType::LongCastling => Pos::of(from.row, 2),
+        _ => game_move.mov.to.clone(),
+    };
+    let promotion = match game_move.typ {
+        GameMoveType::PromotionToQueen => "q",
+        GameMoveType::PromotionToRook => "r",
+        GameMoveType::PromotionToBishop => "b",
+        GameMoveType::PromotionToKnight => "n",
+        _ => "",
+    };
+    (pos_to_string(from) + &pos_to_string(&to) + promotion).to_ascii_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        game::{
+            fen::game_of_fen,
+            mode::standard_chess,
+            mov::{GameMove, PieceMoveType},
+            rule::init::game_of_mode,
+        },
+        pos::pos_of,
+    };
+
+    use super::{lan_promotion_of, lan_to_string, lan_try_of};
+
+    #[test]
+    fn lan_try_of_default() {
+        let game = game_of_mode(standard_chess());
+        assert_eq!(lan_try_of(&game, "e2e4"), Some((pos_of("E2"), pos_of("E4"))));
+        assert_eq!(lan_try_of(&game, "g1f3"), Some((pos_of("G1"), pos_of("F3"))));
+        assert_eq!(lan_try_of(&game, "e2e5"), None);
+        assert_eq!(lan_try_of(&game, "e7e5"), None);
+        assert_eq!(lan_try_of(&game, "e2e4x"), None);
+        assert_eq!(lan_try_of(&game, "e2"), None);
+        assert_eq!(lan_try_of(&game, ""), None);
+    }
+
+    #[test]
+    fn lan_try_of_castling() {
+        let game = game_of_fen("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1");
+        assert_eq!(lan_try_of(&game, "e1g1"), Some((pos_of("E1"), pos_of("H1"))));
+        assert_eq!(lan_try_of(&game, "e1c1"), Some((pos_of("E1"), pos_of("A1"))));
+        assert_eq!(lan_try_of(&game, "e1h1"), Some((pos_of("E1"), pos_of("H1"))));
+    }
+
+    #[test]
+    fn lan_to_string_moves() {
+        assert_eq!(lan_to_string(&GameMove::default_of('♙', "E2", "E4")), "e2e4");
+        assert_eq!(lan_to_string(&GameMove::capture_of('♞', "F6", "E4")), "f6e4");
+        assert_eq!(lan_to_string(&GameMove::short_castling_of('♔', "E1", "H1")), "e1g1");
+        assert_eq!(lan_to_string(&GameMove::long_castling_of('♚', "E8", "A8")), "e8c8");
+        assert_eq!(lan_to_string(&GameMove::promotion_to_knight_of('♙', "B7", "B8")), "b7b8n");
+    }
+
+    #[test]
+    fn lan_promotion() {
+        let game = game_of_fen("4k3/2P5/8/8/8/8/8/4K3 w - - 0 1");
+        assert_eq!(lan_try_of(&game, "c7c8n"), Some((pos_of("C7"), pos_of("C8"))));
+        assert_eq!(lan_promotion_of("c7c8n"), Some(PieceMoveType::PromotionToKnight));
+        assert_eq!(lan_promotion_of("c7c8q"), Some(PieceMoveType::PromotionToQueen));
+        assert_eq!(lan_promotion_of("c7c8"), None);
+    }
+}
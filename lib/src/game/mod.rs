@@ -1,8 +1,15 @@
 pub mod board;
-mod capture;
+pub mod capture;
+pub mod event;
+pub mod fen;
 pub mod game;
+pub mod lan;
 pub mod mode;
 pub mod mov;
-mod player;
+pub mod pgn;
+pub mod player;
+pub mod result;
 pub mod rule;
+pub mod san;
 pub mod selection;
+pub mod zobrist;
@@ -4,12 +4,18 @@ use crate::{
     pos::pos_of,
 };
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone)]
 pub struct GameMode {
     pub bounds: GameBounds,
     pub initial_board: GameBoard,
 }
 
+#[derive(Debug, PartialEq, Clone)]
+pub struct GameModePreset {
+    pub id: &'static str,
+    pub name: &'static str,
+}
+
 pub fn standard_chess() -> GameMode {
     GameMode {
         bounds: GameBounds::of(0, 0, 7, 7),
@@ -51,6 +57,20 @@ pub fn standard_chess() -> GameMode {
     }
 }
 
+type GameModeOf = fn() -> GameMode;
+
+// both the mode lookup and the preset list read this table, so a preset is declared once
+const PRESETS: &[(GameModePreset, GameModeOf)] =
+    &[(GameModePreset { id: "standard", name: "Standard" }, standard_chess)];
+
+pub fn try_get_game_mode(preset: &str) -> Option<GameMode> {
+    PRESETS.iter().find(|(p, _)| p.id == preset).map(|(_, mode)| mode())
+}
+
+pub fn get_game_mode_presets() -> Vec<GameModePreset> {
+    PRESETS.iter().map(|(preset, _)| preset.clone()).collect()
+}
+
 // pub fn chess_960() {
 //     let mut order: [Option<Type>; 8] = [None, None, None, None, None, None, None, None];
 //
@@ -85,7 +105,9 @@ pub fn standard_chess() -> GameMode {
 mod tests {
     use crate::game::{board::board_of_str, game::GameBounds};
 
-    use super::{GameMode, standard_chess};
+    use super::{
+        GameMode, GameModePreset, get_game_mode_presets, standard_chess, try_get_game_mode,
+    };
 
     #[test]
     fn test_chess_standard() {
@@ -110,4 +132,17 @@ mod tests {
             }
         );
     }
+
+    #[test]
+    fn game_mode_presets() {
+        assert_eq!(
+            get_game_mode_presets(),
+            vec![GameModePreset { id: "standard", name: "Standard" }]
+        );
+        for preset in get_game_mode_presets() {
+            assert!(try_get_game_mode(preset.id).is_some());
+        }
+        assert_eq!(try_get_game_mode("standard"), Some(standard_chess()));
+        assert_eq!(try_get_game_mode("crazyhouse"), None);
+    }
 }
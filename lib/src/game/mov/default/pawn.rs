@@ -17,6 +17,17 @@ pub fn pawn_moves(
 ) -> HashMap<Pos, PieceMoveType> {
     let mut result = HashMap::new();
     if let Some(piece) = board.get(pos) {
+        let last_row = match &piece.color {
+            Color::White => bounds.max.row,
+            Color::Black => bounds.min.row,
+        };
+        let move_type_of = |to: &Pos| {
+            if to.row == last_row {
+                PieceMoveType::PromotionToQueen
+            } else {
+                PieceMoveType::Default
+            }
+        };
         let move_base = match &piece.color {
             Color::White => {
                 if pos.row == 1 {
@@ -51,7 +62,8 @@ pub fn pawn_moves(
         };
         for curr_pos in move_base {
             if board.get(&curr_pos).is_none() {
-                result.insert(curr_pos, PieceMoveType::Default);
+                let typ = move_type_of(&curr_pos);
+                result.insert(curr_pos, typ);
             }
         }
         for curr_pos in capture_base {
@@ -61,7 +73,8 @@ pub fn pawn_moves(
                 }
                 if let Some(curr_piece) = board.get(&curr_pos) {
                     if curr_piece.color != piece.color {
-                        result.insert(curr_pos, PieceMoveType::Default);
+                        let typ = move_type_of(&curr_pos);
+                        result.insert(curr_pos, typ);
                     }
                 }
             }
@@ -229,4 +242,38 @@ mod tests {
             [(pos_of("C4"), PieceMoveType::Default), (pos_of("D4"), PieceMoveType::Default)].into()
         );
     }
+
+    #[test]
+    fn pawn_moves_promotion() {
+        let mode = standard_chess();
+        let board = board_of_str(
+            &mode.bounds,
+            [
+                "   ♜    ",
+                "  ♙     ",
+                "        ",
+                "        ",
+                "        ",
+                "        ",
+                "      ♟ ",
+                "       ♖",
+            ],
+        );
+        assert_eq!(
+            pawn_moves(&board, &mode.bounds, &pos_of("C7")),
+            [
+                (pos_of("C8"), PieceMoveType::PromotionToQueen),
+                (pos_of("D8"), PieceMoveType::PromotionToQueen),
+            ]
+            .into()
+        );
+        assert_eq!(
+            pawn_moves(&board, &mode.bounds, &pos_of("G2")),
+            [
+                (pos_of("G1"), PieceMoveType::PromotionToQueen),
+                (pos_of("H1"), PieceMoveType::PromotionToQueen),
+            ]
+            .into()
+        );
+    }
 }
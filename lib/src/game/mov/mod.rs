@@ -6,7 +6,7 @@ use crate::{
         game::GameBounds,
     },
     mov::Mov,
-    piece::Piece,
+    piece::{Piece, PieceType},
     pos::Pos,
 };
 
@@ -27,6 +27,7 @@ pub enum HistoryMoveType {
 }
 
 #[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum PieceMoveType {
     Default,
     EnPassant,
@@ -39,6 +40,7 @@ pub enum PieceMoveType {
 }
 
 #[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum GameMoveType {
     Default,
     Capture,
@@ -52,7 +54,39 @@ pub enum GameMoveType {
     PromotionToKnight,
 }
 
+pub const PROMOTIONS: [PieceMoveType; 4] = [
+    PieceMoveType::PromotionToQueen,
+    PieceMoveType::PromotionToRook,
+    PieceMoveType::PromotionToBishop,
+    PieceMoveType::PromotionToKnight,
+];
+
+pub fn is_promotion(typ: &PieceMoveType) -> bool {
+    PROMOTIONS.contains(typ)
+}
+
+pub fn promotion_piece_type(typ: &PieceMoveType) -> Option<PieceType> {
+    match typ {
+        PieceMoveType::PromotionToQueen => Some(PieceType::Queen),
+        PieceMoveType::PromotionToRook => Some(PieceType::Rook),
+        PieceMoveType::PromotionToBishop => Some(PieceType::Bishop),
+        PieceMoveType::PromotionToKnight => Some(PieceType::Knight),
+        _ => None,
+    }
+}
+
+pub fn promotion_of_game_move_type(typ: &GameMoveType) -> Option<PieceMoveType> {
+    match typ {
+        GameMoveType::PromotionToQueen => Some(PieceMoveType::PromotionToQueen),
+        GameMoveType::PromotionToRook => Some(PieceMoveType::PromotionToRook),
+        GameMoveType::PromotionToBishop => Some(PieceMoveType::PromotionToBishop),
+        GameMoveType::PromotionToKnight => Some(PieceMoveType::PromotionToKnight),
+        _ => None,
+    }
+}
+
 #[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct GameMove {
     pub mov: Mov,
     pub typ: GameMoveType,
@@ -1,26 +1,28 @@
 use std::collections::HashMap;
 
 use crate::{
+    color::Color,
     game::{
         board::GameBoard,
-        game::{GameHistory, GamePlayers},
+        game::{GameHistory, GamePlayers, GameStart},
         mov::PieceMoveType,
     },
-    piece::PieceType,
+    piece::{Piece, PieceType},
     pos::Pos,
 };
 
 pub fn castling_moves(
     board: &GameBoard,
+    start: &GameStart,
     history: &GameHistory,
     players: &GamePlayers,
     king_pos: &Pos,
 ) -> HashMap<Pos, PieceMoveType> {
     let mut result = HashMap::new();
-    if let Some(rook_pos) = short_castling(board, history, players, king_pos) {
+    if let Some(rook_pos) = short_castling(board, start, history, players, king_pos) {
         result.insert(rook_pos, PieceMoveType::ShortCastling);
     }
-    if let Some(rook_pos) = long_castling(board, history, players, king_pos) {
+    if let Some(rook_pos) = long_castling(board, start, history, players, king_pos) {
         result.insert(rook_pos, PieceMoveType::LongCastling);
     }
     result
@@ -28,18 +30,14 @@ pub fn castling_moves(
 
 fn short_castling(
     board: &GameBoard,
+    start: &GameStart,
     history: &GameHistory,
     players: &GamePlayers,
     king_pos: &Pos,
 ) -> Option<Pos> {
     let king = board.get(king_pos)?;
-    let (rook_pos, rook) = board.iter().find(|(pos, piece)| {
-        piece.color == king.color && piece.typ == PieceType::Rook && pos.col > king_pos.col
-    })?;
-    let maybe_moved = history
-        .iter()
-        .find(|game_move| &game_move.mov.piece == king || &game_move.mov.piece == rook);
-    if maybe_moved.is_some() {
+    let rook_pos = castling_rook(board, &king.color, king_pos, true)?;
+    if !start.castling.of(&king.color, true) || king_or_rook_moved(history, king, &rook_pos) {
         return None;
     }
     for col in (king_pos.col + 1)..rook_pos.col {
@@ -58,23 +56,19 @@ fn short_castling(
             }
         }
     }
-    Some(rook_pos.clone())
+    Some(rook_pos)
 }
 
 fn long_castling(
     board: &GameBoard,
+    start: &GameStart,
     history: &GameHistory,
     players: &GamePlayers,
     king_pos: &Pos,
 ) -> Option<Pos> {
     let king = board.get(king_pos)?;
-    let (rook_pos, rook) = board.iter().find(|(pos, piece)| {
-        piece.color == king.color && piece.typ == PieceType::Rook && pos.col < king_pos.col
-    })?;
-    let maybe_moved = history
-        .iter()
-        .find(|game_move| &game_move.mov.piece == king || &game_move.mov.piece == rook);
-    if maybe_moved.is_some() {
+    let rook_pos = castling_rook(board, &king.color, king_pos, false)?;
+    if !start.castling.of(&king.color, false) || king_or_rook_moved(history, king, &rook_pos) {
         return None;
     }
     for col in (rook_pos.col + 1)..king_pos.col {
@@ -93,7 +87,70 @@ fn long_castling(
             }
         }
     }
-    Some(rook_pos.clone())
+    Some(rook_pos)
+}
+
+// the castling rook is the outermost rook of the king row on that side, so the choice does not
+// depend on the board iteration order
+fn castling_rook(board: &GameBoard, color: &Color, king_pos: &Pos, short: bool) -> Option<Pos> {
+    let rooks = board.iter().filter(|(pos, piece)| {
+        &piece.color == color
+            && piece.typ == PieceType::Rook
+            && pos.row == king_pos.row
+            && if short { pos.col > king_pos.col } else { pos.col < king_pos.col }
+    });
+    let rook = if short {
+        rooks.max_by_key(|(pos, _)| pos.col)
+    } else {
+        rooks.min_by_key(|(pos, _)| pos.col)
+    };
+    rook.map(|(pos, _)| pos.clone())
+}
+
+fn king_or_rook_moved(history: &GameHistory, king: &Piece, rook_pos: &Pos) -> bool {
+    history.iter().any(|game_move| {
+        &game_move.mov.piece == king
+            || &game_move.mov.from == rook_pos
+            || &game_move.mov.to == rook_pos
+    })
+}
+
+pub fn short_castling_right(
+    board: &GameBoard,
+    start: &GameStart,
+    history: &GameHistory,
+    color: &Color,
+) -> bool {
+    castling_right(board, start, history, color, true)
+}
+
+pub fn long_castling_right(
+    board: &GameBoard,
+    start: &GameStart,
+    history: &GameHistory,
+    color: &Color,
+) -> bool {
+    castling_right(board, start, history, color, false)
+}
+
+fn castling_right(
+    board: &GameBoard,
+    start: &GameStart,
+    history: &GameHistory,
+    color: &Color,
+    short: bool,
+) -> bool {
+    if !start.castling.of(color, short) {
+        return false;
+    }
+    let maybe_king =
+        board.iter().find(|(_, piece)| &piece.color == color && piece.typ == PieceType::King);
+    if let Some((king_pos, king)) = maybe_king
+        && let Some(rook_pos) = castling_rook(board, color, king_pos, short)
+    {
+        return !king_or_rook_moved(history, king, &rook_pos);
+    }
+    false
 }
 
 #[cfg(test)]
@@ -104,15 +161,14 @@ mod tests {
         color::Color,
         game::{
             board::board_of_str,
-            game::empty_players,
+            game::{GameStart, empty_players},
             mode::standard_chess,
             mov::{GameMove, PieceMoveType},
-            player::GamePlayer,
         },
         pos::pos_of,
     };
 
-    use super::castling_moves;
+    use super::{castling_moves, long_castling_right, short_castling_right};
 
     #[test]
     fn white_king_short_castling() {
@@ -134,7 +190,7 @@ mod tests {
         let players = empty_players();
         let pos = pos_of("E1");
         assert_eq!(
-            castling_moves(&board, &history, &players, &pos),
+            castling_moves(&board, &GameStart::default(), &history, &players, &pos),
             [(pos_of("H1"), PieceMoveType::ShortCastling)].into()
         );
     }
@@ -159,7 +215,7 @@ mod tests {
         let players = empty_players();
         let pos = pos_of("E1");
         assert_eq!(
-            castling_moves(&board, &history, &players, &pos),
+            castling_moves(&board, &GameStart::default(), &history, &players, &pos),
             [(pos_of("A1"), PieceMoveType::LongCastling)].into()
         );
     }
@@ -184,7 +240,7 @@ mod tests {
         let players = empty_players();
         let pos = pos_of("E1");
         assert_eq!(
-            castling_moves(&board, &history, &players, &pos),
+            castling_moves(&board, &GameStart::default(), &history, &players, &pos),
             [
                 (pos_of("H1"), PieceMoveType::ShortCastling),
                 (pos_of("A1"), PieceMoveType::LongCastling),
@@ -212,7 +268,10 @@ mod tests {
         );
         let players = empty_players();
         let pos = pos_of("E1");
-        assert_eq!(castling_moves(&board, &history, &players, &pos), HashMap::new());
+        assert_eq!(
+            castling_moves(&board, &GameStart::default(), &history, &players, &pos),
+            HashMap::new()
+        );
     }
 
     #[test]
@@ -247,7 +306,10 @@ mod tests {
         );
         let players = empty_players();
         let pos = pos_of("E1");
-        assert_eq!(castling_moves(&board, &history, &players, &pos), HashMap::new());
+        assert_eq!(
+            castling_moves(&board, &GameStart::default(), &history, &players, &pos),
+            HashMap::new()
+        );
     }
 
     #[test]
@@ -274,7 +336,143 @@ mod tests {
         );
         let players = empty_players();
         let pos = pos_of("E1");
-        assert_eq!(castling_moves(&board, &history, &players, &pos), HashMap::new());
+        assert_eq!(
+            castling_moves(&board, &GameStart::default(), &history, &players, &pos),
+            HashMap::new()
+        );
+    }
+
+    #[test]
+    fn castling_rights() {
+        let mode = standard_chess();
+        let board = board_of_str(
+            &mode.bounds,
+            [
+                "♜   ♚   ",
+                "♟♟♟♟♟♟♟♟",
+                "        ",
+                "        ",
+                "        ",
+                "        ",
+                "♙♙♙♙♙♙♙♙",
+                "    ♔  ♖",
+            ],
+        );
+        let history = Vec::new();
+        assert!(short_castling_right(&board, &GameStart::default(), &history, &Color::White));
+        assert!(!long_castling_right(&board, &GameStart::default(), &history, &Color::White));
+        assert!(!short_castling_right(&board, &GameStart::default(), &history, &Color::Black));
+        assert!(long_castling_right(&board, &GameStart::default(), &history, &Color::Black));
+        let history = vec![
+            GameMove::default_of('♔', "E1", "E2"),
+            GameMove::default_of('♚', "E8", "E7"),
+            GameMove::default_of('♔', "E2", "E1"),
+            GameMove::default_of('♚', "E7", "E8"),
+        ];
+        assert!(!short_castling_right(&board, &GameStart::default(), &history, &Color::White));
+        assert!(!long_castling_right(&board, &GameStart::default(), &history, &Color::Black));
+    }
+
+    #[test]
+    fn castling_rights_by_rook_square() {
+        let mode = standard_chess();
+        let board = board_of_str(
+            &mode.bounds,
+            [
+                "♜   ♚  ♜",
+                " ♟♟♟♟♟♟ ",
+                "        ",
+                "        ",
+                "        ",
+                "        ",
+                " ♙♙♙♙♙♙ ",
+                "♖   ♔  ♖",
+            ],
+        );
+        let history = vec![
+            GameMove::default_of('♖', "H1", "H2"),
+            GameMove::default_of('♜', "A8", "A7"),
+            GameMove::default_of('♖', "H2", "H1"),
+            GameMove::default_of('♜', "A7", "A8"),
+        ];
+        assert!(!short_castling_right(&board, &GameStart::default(), &history, &Color::White));
+        assert!(long_castling_right(&board, &GameStart::default(), &history, &Color::White));
+        assert!(short_castling_right(&board, &GameStart::default(), &history, &Color::Black));
+        assert!(!long_castling_right(&board, &GameStart::default(), &history, &Color::Black));
+    }
+
+    #[test]
+    fn rook_outside_king_row() {
+        let mode = standard_chess();
+        let history = Vec::new();
+        let board = board_of_str(
+            &mode.bounds,
+            [
+                "       ♖",
+                "      ♚ ",
+                "        ",
+                "        ",
+                "        ",
+                "        ",
+                "        ",
+                "♖   ♔   ",
+            ],
+        );
+        let players = empty_players();
+        assert_eq!(
+            castling_moves(&board, &GameStart::default(), &history, &players, &pos_of("E1")),
+            [(pos_of("A1"), PieceMoveType::LongCastling)].into()
+        );
+        assert!(!short_castling_right(&board, &GameStart::default(), &history, &Color::White));
+        assert!(long_castling_right(&board, &GameStart::default(), &history, &Color::White));
+    }
+
+    #[test]
+    fn outermost_rook() {
+        let mode = standard_chess();
+        let players = empty_players();
+        let board = board_of_str(
+            &mode.bounds,
+            [
+                "    ♚   ",
+                "        ",
+                "        ",
+                "        ",
+                "        ",
+                "        ",
+                "        ",
+                "♖ ♖ ♔ ♖♖",
+            ],
+        );
+        for _ in 0..16 {
+            let board = board.clone().into_iter().collect();
+            assert_eq!(
+                castling_moves(&board, &GameStart::default(), &Vec::new(), &players, &pos_of("E1")),
+                HashMap::new()
+            );
+            assert!(short_castling_right(
+                &board,
+                &GameStart::default(),
+                &Vec::new(),
+                &Color::White
+            ));
+            assert!(long_castling_right(&board, &GameStart::default(), &Vec::new(), &Color::White));
+        }
+        let history = vec![
+            GameMove::default_of('♖', "C1", "C2"),
+            GameMove::default_of('♚', "E8", "E7"),
+            GameMove::default_of('♖', "G1", "G2"),
+            GameMove::default_of('♚', "E7", "E8"),
+        ];
+        assert!(short_castling_right(&board, &GameStart::default(), &history, &Color::White));
+        assert!(long_castling_right(&board, &GameStart::default(), &history, &Color::White));
+        let history = vec![
+            GameMove::default_of('♖', "H1", "H2"),
+            GameMove::default_of('♚', "E8", "E7"),
+            GameMove::default_of('♖', "H2", "H1"),
+        ];
+        assert!(!short_castling_right(&board, &GameStart::default(), &history, &Color::White));
+        assert!(long_castling_right(&board, &GameStart::default(), &history, &Color::White));
     }
 }
 /*
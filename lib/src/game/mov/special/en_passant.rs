@@ -1,89 +1,62 @@
 use std::collections::HashMap;
 
-use manfredo::matrix::{point::point_i8::PointI8, point::point_u8::checked_translated};
-
 use crate::{
     color::Color,
-    game::{board::GameBoard, game::GameHistory, mov::PieceMoveType},
-    piece::PieceType,
+    game::{
+        board::GameBoard,
+        game::{GameHistory, GameStart},
+        mov::PieceMoveType,
+    },
+    piece::{Piece, PieceType},
     pos::Pos,
 };
 
 pub fn en_passant_moves(
     board: &GameBoard,
+    start: &GameStart,
     history: &GameHistory,
     pos: &Pos,
 ) -> HashMap<Pos, PieceMoveType> {
-    if let Some(piece) = board.get(pos) {
-        match piece.color {
-            Color::White => white_pawn_en_passant(history, pos),
-            Color::Black => black_pawn_en_passant(history, pos),
-        }
-    } else {
-        HashMap::new()
-    }
-}
-
-fn white_pawn_en_passant(history: &GameHistory, pos: &Pos) -> HashMap<Pos, PieceMoveType> {
     let mut result = HashMap::new();
-    if pos.row == 4 {
-        if let Some(game_move) = history.last() {
-            if game_move.mov.piece.typ == PieceType::Pawn
-                && game_move.mov.piece.color == Color::Black
-                && game_move.mov.from.row == 6
-                && game_move.mov.to.row == 4
-            {
-                if Some(game_move.mov.to.clone()) == checked_translated(pos, &PointI8::of(0, -1)) {
-                    if let Some(capture_pos) = checked_translated(pos, &PointI8::of(1, -1)) {
-                        result.insert(
-                            Pos::of(pos.row + 1, capture_pos.col),
-                            PieceMoveType::EnPassant,
-                        );
-                    }
-                }
-                if Some(game_move.mov.to.clone()) == checked_translated(pos, &PointI8::of(0, 1)) {
-                    if let Some(capture_pos) = checked_translated(pos, &PointI8::of(1, 1)) {
-                        result.insert(
-                            Pos::of(pos.row + 1, capture_pos.col),
-                            PieceMoveType::EnPassant,
-                        );
-                    }
-                }
-            }
+    if let Some(piece) = board.get(pos) {
+        let (row, opponent) = match piece.color {
+            Color::White => (4, Color::Black),
+            Color::Black => (3, Color::White),
+        };
+        if pos.row == row
+            && let Some(target) = en_passant_target(start, history, &opponent)
+            && target.col.abs_diff(pos.col) == 1
+        {
+            result.insert(target, PieceMoveType::EnPassant);
         }
     }
     result
 }
 
-fn black_pawn_en_passant(history: &GameHistory, pos: &Pos) -> HashMap<Pos, PieceMoveType> {
-    let mut result = HashMap::new();
-    if pos.row == 3 {
-        if let Some(game_move) = history.last() {
-            if game_move.mov.piece.typ == PieceType::Pawn
-                && game_move.mov.piece.color == Color::White
-                && game_move.mov.from.row == 1
-                && game_move.mov.to.row == 3
-            {
-                if Some(game_move.mov.to.clone()) == checked_translated(pos, &PointI8::of(0, -1)) {
-                    if let Some(capture_pos) = checked_translated(pos, &PointI8::of(-1, -1)) {
-                        result.insert(
-                            Pos::of(pos.row - 1, capture_pos.col),
-                            PieceMoveType::EnPassant,
-                        );
-                    }
-                }
-                if Some(game_move.mov.to.clone()) == checked_translated(pos, &PointI8::of(0, 1)) {
-                    if let Some(capture_pos) = checked_translated(pos, &PointI8::of(-1, 1)) {
-                        result.insert(
-                            Pos::of(pos.row - 1, capture_pos.col),
-                            PieceMoveType::EnPassant,
-                        );
-                    }
-                }
-            }
+// the square skipped by a double pawn step of the opponent in the last move, or the en passant
+// square of the start position before any move
+pub fn en_passant_target(
+    start: &GameStart,
+    history: &GameHistory,
+    opponent: &Color,
+) -> Option<Pos> {
+    let (from_row, to_row) = match opponent {
+        Color::White => (1, 3),
+        Color::Black => (6, 4),
+    };
+    match history.last() {
+        Some(game_move) => {
+            let mov = &game_move.mov;
+            (mov.piece == Piece { typ: PieceType::Pawn, color: *opponent }
+                && mov.from.row == from_row
+                && mov.to.row == to_row)
+                .then(|| Pos::of((from_row + to_row) / 2, mov.to.col))
         }
+        None => start
+            .en_passant
+            .clone()
+            .filter(|target| &start.turn != opponent && target.row == (from_row + to_row) / 2),
     }
-    result
 }
 
 #[cfg(test)]
@@ -91,7 +64,11 @@ mod tests {
     use std::collections::HashMap;
 
     use crate::{
-        game::mov::{GameMove, PieceMoveType},
+        color::Color,
+        game::{
+            game::GameStart,
+            mov::{GameMove, PieceMoveType},
+        },
         piece::Piece,
         pos::pos_of,
     };
@@ -103,7 +80,7 @@ mod tests {
         let board = [(pos_of("A5"), Piece::of('♟')), (pos_of("B5"), Piece::of('♙'))].into();
         let history = vec![GameMove::default_of('♟', "A7", "A5")];
         assert_eq!(
-            en_passant_moves(&board, &history, &pos_of("B5")),
+            en_passant_moves(&board, &GameStart::default(), &history, &pos_of("B5")),
             [(pos_of("A6"), PieceMoveType::EnPassant)].into()
         );
     }
@@ -113,7 +90,7 @@ mod tests {
         let board = [(pos_of("B5"), Piece::of('♟')), (pos_of("A5"), Piece::of('♙'))].into();
         let history = vec![GameMove::default_of('♟', "B7", "B5")];
         assert_eq!(
-            en_passant_moves(&board, &history, &pos_of("A5")),
+            en_passant_moves(&board, &GameStart::default(), &history, &pos_of("A5")),
             [(pos_of("B6"), PieceMoveType::EnPassant)].into()
         );
     }
@@ -123,7 +100,7 @@ mod tests {
         let board = [(pos_of("G5"), Piece::of('♟')), (pos_of("H5"), Piece::of('♙'))].into();
         let history = vec![GameMove::default_of('♟', "G7", "G5")];
         assert_eq!(
-            en_passant_moves(&board, &history, &pos_of("H5")),
+            en_passant_moves(&board, &GameStart::default(), &history, &pos_of("H5")),
             [(pos_of("G6"), PieceMoveType::EnPassant)].into()
         );
     }
@@ -133,7 +110,7 @@ mod tests {
         let board = [(pos_of("H5"), Piece::of('♟')), (pos_of("G5"), Piece::of('♙'))].into();
         let history = vec![GameMove::default_of('♟', "H7", "H5")];
         assert_eq!(
-            en_passant_moves(&board, &history, &pos_of("G5")),
+            en_passant_moves(&board, &GameStart::default(), &history, &pos_of("G5")),
             [(pos_of("H6"), PieceMoveType::EnPassant)].into()
         );
     }
@@ -143,7 +120,7 @@ mod tests {
         let board = [(pos_of("A4"), Piece::of('♙')), (pos_of("B4"), Piece::of('♟'))].into();
         let history = vec![GameMove::default_of('♙', "A2", "A4")];
         assert_eq!(
-            en_passant_moves(&board, &history, &pos_of("B4")),
+            en_passant_moves(&board, &GameStart::default(), &history, &pos_of("B4")),
             [(pos_of("A3"), PieceMoveType::EnPassant)].into()
         );
     }
@@ -153,7 +130,7 @@ mod tests {
         let board = [(pos_of("B4"), Piece::of('♙')), (pos_of("A4"), Piece::of('♟'))].into();
         let history = vec![GameMove::default_of('♙', "B2", "B4")];
         assert_eq!(
-            en_passant_moves(&board, &history, &pos_of("A4")),
+            en_passant_moves(&board, &GameStart::default(), &history, &pos_of("A4")),
             [(pos_of("B3"), PieceMoveType::EnPassant)].into()
         );
     }
@@ -163,7 +140,7 @@ mod tests {
         let board = [(pos_of("G4"), Piece::of('♙')), (pos_of("H4"), Piece::of('♟'))].into();
         let history = vec![GameMove::default_of('♙', "G2", "G4")];
         assert_eq!(
-            en_passant_moves(&board, &history, &pos_of("H4")),
+            en_passant_moves(&board, &GameStart::default(), &history, &pos_of("H4")),
             [(pos_of("G3"), PieceMoveType::EnPassant)].into()
         );
     }
@@ -173,7 +150,7 @@ mod tests {
         let board = [(pos_of("H4"), Piece::of('♙')), (pos_of("G4"), Piece::of('♟'))].into();
         let history = vec![GameMove::default_of('♙', "H2", "H4")];
         assert_eq!(
-            en_passant_moves(&board, &history, &pos_of("G4")),
+            en_passant_moves(&board, &GameStart::default(), &history, &pos_of("G4")),
             [(pos_of("H3"), PieceMoveType::EnPassant)].into()
         );
     }
@@ -182,13 +159,33 @@ mod tests {
     fn pawn_moved_e6_to_e5() {
         let board = [(pos_of("E5"), Piece::of('♟')), (pos_of("D5"), Piece::of('♙'))].into();
         let history = vec![GameMove::default_of('♟', "E6", "E5")];
-        assert_eq!(en_passant_moves(&board, &history, &pos_of("D5")), HashMap::new());
+        assert_eq!(
+            en_passant_moves(&board, &GameStart::default(), &history, &pos_of("D5")),
+            HashMap::new()
+        );
     }
 
     #[test]
     fn pawn_moved_d3_to_d4() {
         let board = [(pos_of("D4"), Piece::of('♙')), (pos_of("E4"), Piece::of('♟'))].into();
         let history = vec![GameMove::default_of('♙', "D3", "D4")];
-        assert_eq!(en_passant_moves(&board, &history, &pos_of("E4")), HashMap::new());
+        assert_eq!(
+            en_passant_moves(&board, &GameStart::default(), &history, &pos_of("E4")),
+            HashMap::new()
+        );
+    }
+
+    #[test]
+    fn start_en_passant_square() {
+        let board = [(pos_of("D5"), Piece::of('♟')), (pos_of("E5"), Piece::of('♙'))].into();
+        let start = GameStart { en_passant: Some(pos_of("D6")), ..GameStart::default() };
+        assert_eq!(
+            en_passant_moves(&board, &start, &Vec::new(), &pos_of("E5")),
+            [(pos_of("D6"), PieceMoveType::EnPassant)].into()
+        );
+        let history = vec![GameMove::default_of('♙', "E5", "E6")];
+        assert_eq!(en_passant_moves(&board, &start, &history, &pos_of("E5")), HashMap::new());
+        let start = GameStart { turn: Color::Black, ..start };
+        assert_eq!(en_passant_moves(&board, &start, &Vec::new(), &pos_of("E5")), HashMap::new());
     }
 }
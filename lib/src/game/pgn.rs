@@ -0,0 +1,412 @@
+use crate::{
+    book::eco::eco_classify,
+    color::Color,
+    game::{
+        fen::{fen_of, game_try_of_fen},
+        game::{Game, GameStart},
+        mode::GameMode,
+        result::{GameResult, WinReason},
+        rule::{
+            init::{game_of_mode, game_of_start},
+            move_piece::play_move_with_promotion,
+        },
+        san::{san_history_of, san_promotion_of, san_try_of},
+        selection::{AnnotationColor, Selection},
+    },
+    pos::{Pos, pos_to_string},
+};
+
+const SEVEN_TAG_ROSTER: [&str; 6] = ["Event", "Site", "Date", "Round", "White", "Black"];
+
+const LINE_LEN: usize = 80;
+
+pub fn pgn_result_to_string(result: Option<&GameResult>) -> &'static str {
+    match result {
+        Some(GameResult::Win(Color::White, _)) => "1-0",
+        Some(GameResult::Win(Color::Black, _)) => "0-1",
+        Some(GameResult::Draw(_)) => "1/2-1/2",
+        Some(GameResult::Aborted) | None => "*",
+    }
+}
+
+pub fn pgn_termination_to_string(result: Option<&GameResult>) -> &'static str {
+    match result {
+        Some(GameResult::Win(_, WinReason::Timeout)) => "time forfeit",
+        Some(GameResult::Aborted) => "abandoned",
+        Some(_) => "normal",
+        None => "unterminated",
+    }
+}
+
+fn tag_to_string(name: &str, value: &str) -> String {
+    format!("[{} \"{}\"]\n", name, value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+fn annotation_color_to_char(color: &AnnotationColor) -> char {
+    match color {
+        AnnotationColor::Green => 'G',
+        AnnotationColor::Red => 'R',
+        AnnotationColor::Yellow => 'Y',
+        AnnotationColor::Blue => 'B',
+    }
+}
+
+fn square_to_string(pos: &Pos) -> String {
+    pos_to_string(pos).to_ascii_lowercase()
+}
+
+pub fn pgn_annotations_of(selection: &Selection) -> Option<String> {
+    let mut squares: Vec<String> = selection
+        .selected_squares
+        .iter()
+        .map(|(pos, color)| format!("{}{}", annotation_color_to_char(color), square_to_string(pos)))
+        .collect();
+    let mut arrows: Vec<String> = selection
+        .arrows
+        .iter()
+        .map(|((from, to), color)| {
+            format!(
+                "{}{}{}",
+                annotation_color_to_char(color),
+                square_to_string(from),
+                square_to_string(to)
+            )
+        })
+        .collect();
+    squares.sort();
+    arrows.sort();
+    let mut comment = String::new();
+    if !squares.is_empty() {
+        comment += &format!("[%csl {}]", squares.join(","));
+    }
+    if !arrows.is_empty() {
+        comment += &format!("[%cal {}]", arrows.join(","));
+    }
+    if comment.is_empty() { None } else { Some(format!("{{{comment}}}")) }
+}
+
+pub fn pgn_of(
+    mode: GameMode,
+    game: &Game,
+    result: Option<&GameResult>,
+    tags: &[(&str, &str)],
+) -> Option<String> {
+    pgn_of_with_annotations(mode, game, result, tags, None)
+}
+
+pub fn pgn_of_with_annotations(
+    mode: GameMode,
+    game: &Game,
+    result: Option<&GameResult>,
+    tags: &[(&str, &str)],
+    annotations: Option<&Selection>,
+) -> Option<String> {
+    let start = game_of_start(game)?;
+    let is_mode_start = start.board == mode.initial_board
+        && start.bounds == mode.bounds
+        && start.start == GameStart::default();
+    let mut tokens = Vec::new();
+    let first_ply = usize::from(game.start.turn == Color::Black);
+    for (i, san) in san_history_of(game)?.into_iter().enumerate() {
+        let ply = first_ply + i;
+        let number = usize::from(game.start.fullmove_number) + ply / 2;
+        if ply.is_multiple_of(2) {
+            tokens.push(format!("{number}."));
+        } else if i == 0 {
+            tokens.push(format!("{number}..."));
+        }
+        tokens.push(san);
+    }
+    if let Some(comment) = annotations.and_then(pgn_annotations_of) {
+        tokens.push(comment);
+    }
+    let result_str = pgn_result_to_string(result);
+    tokens.push(result_str.into());
+
+    let mut pgn = String::new();
+    for name in SEVEN_TAG_ROSTER {
+        let value = tags.iter().find(|(tag, _)| *tag == name).map_or("?", |(_, value)| *value);
+        pgn += &tag_to_string(name, value);
+    }
+    pgn += &tag_to_string("Result", result_str);
+    if !is_mode_start {
+        pgn += &tag_to_string("SetUp", "1");
+        pgn += &tag_to_string("FEN", &fen_of(&start.board, &start.bounds, &start.start));
+    }
+    if let Some(opening) = eco_classify(game) {
+        pgn += &tag_to_string("ECO", opening.code);
+        pgn += &tag_to_string("Opening", opening.name);
+    }
+    pgn += &tag_to_string("Termination", pgn_termination_to_string(result));
+    for (name, value) in tags.iter().filter(|(tag, _)| !SEVEN_TAG_ROSTER.contains(tag)) {
+        pgn += &tag_to_string(name, value);
+    }
+    pgn.push('\n');
+    let mut line = String::new();
+    for token in tokens {
+        if !line.is_empty() && line.len() + 1 + token.len() > LINE_LEN {
+            pgn += &line;
+            pgn.push('\n');
+            line.clear();
+        }
+        if !line.is_empty() {
+            line.push(' ');
+        }
+        line += &token;
+    }
+    pgn += &line;
+    pgn.push('\n');
+    Some(pgn)
+}
+
+fn tag_value_of(s: &str, name: &str) -> Option<String> {
+    s.lines().find_map(|line| {
+        let tag = line.trim().strip_prefix('[')?.strip_suffix(']')?;
+        let (tag_name, value) = tag.split_once(char::is_whitespace)?;
+        if tag_name != name {
+            return None;
+        }
+        let value = value.trim().strip_prefix('"')?.strip_suffix('"')?;
+        let mut unescaped = String::new();
+        let mut chars = value.chars();
+        while let Some(c) = chars.next() {
+            unescaped.push(if c == '\\' { chars.next()? } else { c });
+        }
+        Some(unescaped)
+    })
+}
+
+fn movetext_tokens(s: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut token = String::new();
+    let mut depth = 0;
+    let mut in_comment = false;
+    for line in s.lines().filter(|line| !line.trim_start().starts_with('[')) {
+        let mut in_line_comment = false;
+        for c in line.chars() {
+            match c {
+                _ if in_line_comment => {}
+                '}' if in_comment => in_comment = false,
+                _ if in_comment => {}
+                '{' => in_comment = true,
+                ';' => in_line_comment = true,
+                '(' => depth += 1,
+                ')' => depth -= 1,
+                _ if depth > 0 => {}
+                c if c.is_whitespace() || c == '.' => {
+                    if !token.is_empty() {
+                        tokens.push(std::mem::take(&mut token));
+                    }
+                }
+                c => token.push(c),
+            }
+        }
+        if !token.is_empty() {
+            tokens.push(std::mem::take(&mut token));
+        }
+    }
+    tokens
+        .into_iter()
+        .filter(|token| {
+            !token.starts_with('$')
+                && !token.chars().all(|c| c.is_ascii_digit())
+                && !matches!(token.as_str(), "1-0" | "0-1" | "1/2-1/2" | "*")
+        })
+        .collect()
+}
+
+pub fn pgn_try_of(mode: GameMode, s: &str) -> Option<Game> {
+    let mut game = match tag_value_of(s, "FEN") {
+        Some(fen) => game_try_of_fen(&fen)?,
+        None => game_of_mode(mode),
+    };
+    for token in movetext_tokens(s) {
+        let (from, to) = san_try_of(&game, &token)?;
+        play_move_with_promotion(&mut game, &from, &to, san_promotion_of(&token).as_ref());
+    }
+    Some(game)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::{
+        color::Color,
+        game::{
+            event::{GameAction, play_action},
+            fen::game_of_fen,
+            mode::standard_chess,
+            result::{DrawReason, GameResult, WinReason, game_result},
+            rule::{init::game_of_mode, move_piece::play_move},
+            selection::{AnnotationColor, Selection},
+        },
+        pos::pos_of,
+    };
+
+    use super::{
+        pgn_annotations_of, pgn_of, pgn_of_with_annotations, pgn_result_to_string,
+        pgn_termination_to_string, pgn_try_of,
+    };
+
+    #[test]
+    fn pgn_result() {
+        let win = GameResult::Win(Color::White, WinReason::Checkmate);
+        let loss = GameResult::Win(Color::Black, WinReason::Timeout);
+        let draw = GameResult::Draw(DrawReason::Agreement);
+        assert_eq!(pgn_result_to_string(Some(&win)), "1-0");
+        assert_eq!(pgn_result_to_string(Some(&loss)), "0-1");
+        assert_eq!(pgn_result_to_string(Some(&draw)), "1/2-1/2");
+        assert_eq!(pgn_result_to_string(Some(&GameResult::Aborted)), "*");
+        assert_eq!(pgn_result_to_string(None), "*");
+        assert_eq!(pgn_termination_to_string(Some(&win)), "normal");
+        assert_eq!(pgn_termination_to_string(Some(&loss)), "time forfeit");
+        assert_eq!(pgn_termination_to_string(Some(&GameResult::Aborted)), "abandoned");
+        assert_eq!(pgn_termination_to_string(None), "unterminated");
+    }
+
+    #[test]
+    fn pgn_of_game() {
+        let mut game = game_of_mode(standard_chess());
+        for (from, to) in [("F2", "F3"), ("E7", "E5"), ("G2", "G4"), ("D8", "H4")] {
+            play_move(&mut game, &pos_of(from), &pos_of(to));
+        }
+        let result = game_result(&game);
+        assert_eq!(
+            pgn_of(
+                standard_chess(),
+                &game,
+                result.as_ref(),
+                &[("White", "Ana \"A\""), ("Annotator", "Bia")]
+            ),
+            Some(
+                [
+                    "[Event \"?\"]",
+                    "[Site \"?\"]",
+                    "[Date \"?\"]",
+                    "[Round \"?\"]",
+                    "[White \"Ana \\\"A\\\"\"]",
+                    "[Black \"?\"]",
+                    "[Result \"0-1\"]",
+                    "[ECO \"A00\"]",
+                    "[Opening \"Barnes Opening: Fool's Mate\"]",
+                    "[Termination \"normal\"]",
+                    "[Annotator \"Bia\"]",
+                    "",
+                    "1. f3 e5 2. g4 Qh4# 0-1",
+                    "",
+                ]
+                .join("\n")
+            )
+        );
+    }
+
+    #[test]
+    fn pgn_of_resignation() {
+        let mut game = game_of_mode(standard_chess());
+        play_move(&mut game, &pos_of("E2"), &pos_of("E4"));
+        assert!(play_action(&mut game, &Color::Black, GameAction::Resign));
+        let pgn = pgn_of(standard_chess(), &game, game_result(&game).as_ref(), &[]).unwrap();
+        assert!(pgn.contains("[Result \"1-0\"]"));
+        assert!(pgn.ends_with("\n1. e4 1-0\n"));
+    }
+
+    #[test]
+    fn pgn_of_line_wrap() {
+        let mut game = game_of_mode(standard_chess());
+        let shuffle = [("G1", "F3"), ("G8", "F6"), ("F3", "G1"), ("F6", "G8")];
+        for (from, to) in shuffle.iter().cycle().take(40) {
+            play_move(&mut game, &pos_of(from), &pos_of(to));
+        }
+        let pgn = pgn_of(standard_chess(), &game, None, &[]).unwrap();
+        let movetext = pgn.split("\n\n").nth(1).unwrap();
+        assert!(movetext.lines().count() > 1);
+        assert!(movetext.lines().all(|line| line.len() <= 80));
+        assert!(movetext.ends_with(" *\n"));
+    }
+
+    #[test]
+    fn pgn_of_other_start() {
+        let fen = "4k3/8/8/8/8/8/4P3/4K2R w K - 0 1";
+        let mut game = game_of_fen(fen);
+        for (from, to) in [("E1", "H1"), ("E8", "D7"), ("E2", "E4")] {
+            play_move(&mut game, &pos_of(from), &pos_of(to));
+        }
+        let pgn = pgn_of(standard_chess(), &game, None, &[]).unwrap();
+        assert!(pgn.contains(&format!("[SetUp \"1\"]\n[FEN \"{fen}\"]\n")));
+        assert!(!pgn.contains("[ECO "));
+        assert!(pgn.ends_with("\n1. O-O Kd7 2. e4 *\n"));
+        assert_eq!(pgn_try_of(standard_chess(), &pgn), Some(game));
+        assert_eq!(pgn_try_of(standard_chess(), "[FEN \"9x/8 w\"]\n\n*"), None);
+    }
+
+    #[test]
+    fn pgn_of_move_numbers_of_start() {
+        let mut game = game_of_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR b KQkq - 0 7");
+        for (from, to) in [("E7", "E5"), ("G1", "F3"), ("B8", "C6")] {
+            play_move(&mut game, &pos_of(from), &pos_of(to));
+        }
+        assert_eq!(
+            pgn_of(standard_chess(), &game, None, &[]).unwrap().lines().last(),
+            Some("7... e5 8. Nf3 Nc6 *")
+        );
+        let pgn = pgn_of(standard_chess(), &game, None, &[]).unwrap();
+        assert_eq!(pgn_try_of(standard_chess(), &pgn), Some(game));
+    }
+
+    #[test]
+    fn pgn_try_of_game() {
+        let pgn = [
+            "[Event \"?\"]",
+            "[Result \"1-0\"]",
+            "",
+            "1. e4 e5 2.Nf3 {the knight} Nc6 (2... d6 3. d4) 3. Bb5 $1 a6 ; Morphy",
+            "4. Ba4 Nf6 5. O-O 1-0",
+        ]
+        .join("\n");
+        let game = pgn_try_of(standard_chess(), &pgn).unwrap();
+        assert_eq!(game.history.len(), 9);
+        assert_eq!(
+            pgn_of(standard_chess(), &game, None, &[]).unwrap().lines().last(),
+            Some("1. e4 e5 2. Nf3 Nc6 3. Bb5 a6 4. Ba4 Nf6 5. O-O *")
+        );
+        assert_eq!(pgn_try_of(standard_chess(), "").map(|game| game.history.len()), Some(0));
+        assert_eq!(pgn_try_of(standard_chess(), "1. e5"), None);
+    }
+
+    #[test]
+    fn pgn_round_trip() {
+        let mut game = game_of_mode(standard_chess());
+        let shuffle = [("E2", "E4"), ("D7", "D5"), ("E4", "D5"), ("D8", "D5"), ("B1", "C3")];
+        for (from, to) in shuffle {
+            play_move(&mut game, &pos_of(from), &pos_of(to));
+        }
+        let pgn = pgn_of(standard_chess(), &game, None, &[]).unwrap();
+        assert_eq!(pgn_try_of(standard_chess(), &pgn), Some(game));
+    }
+
+    #[test]
+    fn pgn_annotations() {
+        let mut selection = Selection {
+            selected_squares: HashMap::new(),
+            arrows: HashMap::new(),
+            selected_pos: None,
+        };
+        assert_eq!(pgn_annotations_of(&selection), None);
+        selection.selected_squares.insert(pos_of("E4"), AnnotationColor::Red);
+        selection.selected_squares.insert(pos_of("D4"), AnnotationColor::Green);
+        assert_eq!(pgn_annotations_of(&selection), Some("{[%csl Gd4,Re4]}".into()));
+        selection.arrows.insert((pos_of("G1"), pos_of("F3")), AnnotationColor::Blue);
+        selection.arrows.insert((pos_of("E2"), pos_of("E4")), AnnotationColor::Yellow);
+        assert_eq!(
+            pgn_annotations_of(&selection),
+            Some("{[%csl Gd4,Re4][%cal Bg1f3,Ye2e4]}".into())
+        );
+        let mut game = game_of_mode(standard_chess());
+        play_move(&mut game, &pos_of("E2"), &pos_of("E4"));
+        let pgn =
+            pgn_of_with_annotations(standard_chess(), &game, None, &[], Some(&selection)).unwrap();
+        assert!(pgn.ends_with("\n1. e4 {[%csl Gd4,Re4][%cal Bg1f3,Ye2e4]} *\n"));
+        assert_eq!(pgn_try_of(standard_chess(), &pgn), Some(game));
+    }
+}
@@ -7,9 +7,11 @@ use super::capture::GameCapture;
 pub type PlayerMoves = HashMap<Pos, HashMap<Pos, PieceMoveType>>;
 
 #[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct GamePlayer {
     pub color: Color,
     pub captures: Vec<GameCapture>,
+    #[cfg_attr(feature = "serde", serde(skip))]
     pub moves: PlayerMoves,
 }
 
@@ -0,0 +1,413 @@
+use crate::{
+    clock::Clock,
+    color::Color,
+    game::{
+        board::GameBoard,
+        event::{DrawClaim, GameAction},
+        game::Game,
+        rule::{check::is_in_check, turn::evaluate_turn},
+    },
+    piece::PieceType,
+};
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum WinReason {
+    Checkmate,
+    Resignation,
+    Timeout,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DrawReason {
+    Stalemate,
+    InsufficientMaterial,
+    TimeoutVsInsufficientMaterial,
+    Agreement,
+    ThreefoldRepetition,
+    FiftyMoveRule,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum GameResult {
+    Win(Color, WinReason),
+    Draw(DrawReason),
+    Aborted,
+}
+
+fn opponent_of(color: &Color) -> Color {
+    match color {
+        Color::White => Color::Black,
+        Color::Black => Color::White,
+    }
+}
+
+fn bishops_on_one_color(board: &GameBoard, color: Option<&Color>) -> bool {
+    let squares: Vec<bool> = board
+        .iter()
+        .filter(|(_, piece)| piece.typ == PieceType::Bishop)
+        .filter(|(_, piece)| color.is_none_or(|color| &piece.color == color))
+        .map(|(pos, _)| (pos.row + pos.col).is_multiple_of(2))
+        .collect();
+    squares.iter().all(|dark| *dark == squares[0])
+}
+
+pub fn has_mating_material(board: &GameBoard, color: &Color) -> bool {
+    let own: Vec<PieceType> = board
+        .values()
+        .filter(|piece| &piece.color == color && piece.typ != PieceType::King)
+        .map(|piece| piece.typ)
+        .collect();
+    if own.iter().any(|typ| matches!(typ, PieceType::Pawn | PieceType::Rook | PieceType::Queen)) {
+        return true;
+    }
+    // bishops that all share a square color mate no better than a single one
+    let is_single = own.len() == 1
+        || (!own.is_empty()
+            && own.iter().all(|typ| *typ == PieceType::Bishop)
+            && bishops_on_one_color(board, Some(color)));
+    if own.len() > 1 && !is_single {
+        return true;
+    }
+    is_single && board.values().any(|piece| &piece.color != color && piece.typ != PieceType::King)
+}
+
+pub fn is_insufficient_material(board: &GameBoard) -> bool {
+    let non_kings: Vec<&PieceType> = board
+        .values()
+        .filter(|piece| piece.typ != PieceType::King)
+        .map(|piece| &piece.typ)
+        .collect();
+    if non_kings.len() <= 1 {
+        return non_kings.iter().all(|typ| matches!(typ, PieceType::Bishop | PieceType::Knight));
+    }
+    non_kings.iter().all(|typ| **typ == PieceType::Bishop) && bishops_on_one_color(board, None)
+}
+
+pub fn timeout_result(board: &GameBoard, flagged: &Color) -> GameResult {
+    let opponent = opponent_of(flagged);
+    if has_mating_material(board, &opponent) {
+        GameResult::Win(opponent, WinReason::Timeout)
+    } else {
+        GameResult::Draw(DrawReason::TimeoutVsInsufficientMaterial)
+    }
+}
+
+fn event_result(game: &Game) -> Option<GameResult> {
+    let event = game.events.last()?;
+    match &event.action {
+        GameAction::Resign => {
+            Some(GameResult::Win(opponent_of(&event.color), WinReason::Resignation))
+        }
+        GameAction::AcceptDraw => Some(GameResult::Draw(DrawReason::Agreement)),
+        GameAction::ClaimDraw(DrawClaim::ThreefoldRepetition) => {
+            Some(GameResult::Draw(DrawReason::ThreefoldRepetition))
+        }
+        GameAction::ClaimDraw(DrawClaim::FiftyMoveRule) => {
+            Some(GameResult::Draw(DrawReason::FiftyMoveRule))
+        }
+        GameAction::Abort => Some(GameResult::Aborted),
+        GameAction::OfferDraw | GameAction::DeclineDraw => None,
+    }
+}
+
+pub fn game_result(game: &Game) -> Option<GameResult> {
+    if let Some(result) = event_result(game) {
+        return Some(result);
+    }
+    let turn = evaluate_turn(&game.start, &game.history);
+    let player = game.players.get(&turn)?;
+    if player.moves.values().all(|moves| moves.is_empty()) {
+        if is_in_check(&game.board, &game.players, &game.start, &game.history) {
+            return Some(GameResult::Win(opponent_of(&turn), WinReason::Checkmate));
+        }
+        return Some(GameResult::Draw(DrawReason::Stalemate));
+    }
+    if is_insufficient_material(&game.board) {
+        return Some(GameResult::Draw(DrawReason::InsufficientMaterial));
+    }
+    None
+}
+
+pub fn game_result_with_clock(game: &Game, clock: &Clock) -> Option<GameResult> {
+    game_result(game).or_else(|| clock.flagged.map(|color| timeout_result(&game.board, &color)))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        clock::{Clock, TimeControl},
+        color::Color,
+        game::{
+            board::board_of_str,
+            event::{GameAction, play_action},
+            fen::game_of_fen,
+            game::GameBounds,
+            mode::standard_chess,
+            rule::{init::game_of_mode, move_piece::play_move},
+        },
+        pos::pos_of,
+        time::ManualTimeSource,
+    };
+
+    use super::{
+        DrawReason, GameResult, WinReason, game_result, game_result_with_clock,
+        has_mating_material, is_insufficient_material, timeout_result,
+    };
+
+    #[test]
+    fn has_mating_material_of() {
+        let bounds = GameBounds::of(0, 0, 7, 7);
+        let board = board_of_str(
+            &bounds,
+            [
+                "    ♚   ",
+                "        ",
+                "        ",
+                "        ",
+                "        ",
+                "        ",
+                "        ",
+                "    ♔   ",
+            ],
+        );
+        assert!(!has_mating_material(&board, &Color::White));
+        let board = board_of_str(
+            &bounds,
+            [
+                "    ♚   ",
+                "        ",
+                "        ",
+                "        ",
+                "        ",
+                "        ",
+                "   ♙    ",
+                "    ♔   ",
+            ],
+        );
+        assert!(has_mating_material(&board, &Color::White));
+        assert!(!has_mating_material(&board, &Color::Black));
+        let board = board_of_str(
+            &bounds,
+            [
+                "    ♚   ",
+                "        ",
+                "        ",
+                "        ",
+                "        ",
+                "        ",
+                "   ♘    ",
+                "    ♔   ",
+            ],
+        );
+        assert!(!has_mating_material(&board, &Color::White));
+        let board = board_of_str(
+            &bounds,
+            [
+                "    ♚   ",
+                "   ♟    ",
+                "        ",
+                "        ",
+                "        ",
+                "        ",
+                "   ♘    ",
+                "    ♔   ",
+            ],
+        );
+        assert!(has_mating_material(&board, &Color::White));
+        let board = board_of_str(
+            &bounds,
+            [
+                "    ♚   ",
+                "        ",
+                "        ",
+                "        ",
+                "        ",
+                "        ",
+                "   ♘♗   ",
+                "    ♔   ",
+            ],
+        );
+        assert!(has_mating_material(&board, &Color::White));
+        let board = board_of_str(
+            &bounds,
+            [
+                "    ♚   ",
+                "        ",
+                "        ",
+                "        ",
+                "        ",
+                "        ",
+                "  ♗ ♗   ",
+                "    ♔   ",
+            ],
+        );
+        assert!(!has_mating_material(&board, &Color::White));
+        let board = board_of_str(
+            &bounds,
+            [
+                "    ♚   ",
+                "        ",
+                "        ",
+                "        ",
+                "        ",
+                "        ",
+                "   ♗♗   ",
+                "    ♔   ",
+            ],
+        );
+        assert!(has_mating_material(&board, &Color::White));
+    }
+
+    #[test]
+    fn is_insufficient_material_of() {
+        let bounds = GameBounds::of(0, 0, 7, 7);
+        let board = board_of_str(
+            &bounds,
+            [
+                "    ♚   ",
+                "        ",
+                "        ",
+                "        ",
+                "        ",
+                "        ",
+                "        ",
+                "    ♔   ",
+            ],
+        );
+        assert!(is_insufficient_material(&board));
+        let board = board_of_str(
+            &bounds,
+            [
+                "    ♚   ",
+                "        ",
+                "        ",
+                "        ",
+                "        ",
+                "        ",
+                "   ♘    ",
+                "    ♔   ",
+            ],
+        );
+        assert!(is_insufficient_material(&board));
+        let board = board_of_str(
+            &bounds,
+            [
+                "  ♝ ♚   ",
+                "        ",
+                "        ",
+                "        ",
+                "        ",
+                "        ",
+                "        ",
+                "    ♔♗  ",
+            ],
+        );
+        assert!(is_insufficient_material(&board));
+        let board = board_of_str(
+            &bounds,
+            [
+                "   ♝♚   ",
+                "        ",
+                "        ",
+                "        ",
+                "        ",
+                "        ",
+                "        ",
+                "    ♔♗  ",
+            ],
+        );
+        assert!(!is_insufficient_material(&board));
+        let board = board_of_str(
+            &bounds,
+            [
+                "    ♚   ",
+                "        ",
+                "        ",
+                "        ",
+                "        ",
+                "        ",
+                "   ♖    ",
+                "    ♔   ",
+            ],
+        );
+        assert!(!is_insufficient_material(&board));
+        assert!(!is_insufficient_material(&game_of_mode(standard_chess()).board));
+    }
+
+    #[test]
+    fn timeout_result_of() {
+        let bounds = GameBounds::of(0, 0, 7, 7);
+        let board = board_of_str(
+            &bounds,
+            [
+                "    ♚   ",
+                "        ",
+                "        ",
+                "        ",
+                "        ",
+                "        ",
+                "   ♕    ",
+                "    ♔   ",
+            ],
+        );
+        assert_eq!(
+            timeout_result(&board, &Color::Black),
+            GameResult::Win(Color::White, WinReason::Timeout)
+        );
+        assert_eq!(
+            timeout_result(&board, &Color::White),
+            GameResult::Draw(DrawReason::TimeoutVsInsufficientMaterial)
+        );
+    }
+
+    #[test]
+    fn game_result_of() {
+        assert_eq!(game_result(&game_of_mode(standard_chess())), None);
+        let mut game = game_of_mode(standard_chess());
+        for (from, to) in [("F2", "F3"), ("E7", "E5"), ("G2", "G4"), ("D8", "H4")] {
+            play_move(&mut game, &pos_of(from), &pos_of(to));
+        }
+        assert_eq!(game_result(&game), Some(GameResult::Win(Color::Black, WinReason::Checkmate)));
+        assert_eq!(
+            game_result(&game_of_fen("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1")),
+            Some(GameResult::Draw(DrawReason::Stalemate))
+        );
+        assert_eq!(
+            game_result(&game_of_fen("7k/8/6K1/8/8/8/8/6B1 b - - 0 1")),
+            Some(GameResult::Draw(DrawReason::InsufficientMaterial))
+        );
+    }
+
+    #[test]
+    fn game_result_of_events() {
+        let mut game = game_of_mode(standard_chess());
+        assert!(play_action(&mut game, &Color::White, GameAction::Resign));
+        assert_eq!(game_result(&game), Some(GameResult::Win(Color::Black, WinReason::Resignation)));
+        let mut game = game_of_mode(standard_chess());
+        assert!(play_action(&mut game, &Color::White, GameAction::OfferDraw));
+        assert_eq!(game_result(&game), None);
+        assert!(play_action(&mut game, &Color::Black, GameAction::AcceptDraw));
+        assert_eq!(game_result(&game), Some(GameResult::Draw(DrawReason::Agreement)));
+        let mut game = game_of_mode(standard_chess());
+        assert!(play_action(&mut game, &Color::Black, GameAction::Abort));
+        assert_eq!(game_result(&game), Some(GameResult::Aborted));
+    }
+
+    #[test]
+    fn game_result_with_clock_of() {
+        let game = game_of_fen("7k/8/6K1/8/8/8/8/6Q1 b - - 0 1");
+        let time_source = ManualTimeSource::of(0);
+        let mut clock = Clock::of(TimeControl::sudden_death(1_000));
+        clock.start(&Color::Black, &time_source);
+        assert_eq!(game_result_with_clock(&game, &clock), None);
+        time_source.advance(1_000);
+        clock.check_flag(&time_source);
+        assert_eq!(
+            game_result_with_clock(&game, &clock),
+            Some(GameResult::Win(Color::White, WinReason::Timeout))
+        );
+    }
+}
@@ -1,14 +1,19 @@
 use crate::{
     game::{
         board::GameBoard,
-        game::{GameHistory, GamePlayers},
+        game::{GameHistory, GamePlayers, GameStart},
         rule::turn::evaluate_turn,
     },
     piece::PieceType,
 };
 
-pub fn is_in_check(board: &GameBoard, players: &GamePlayers, history: &GameHistory) -> bool {
-    let turn = evaluate_turn(history);
+pub fn is_in_check(
+    board: &GameBoard,
+    players: &GamePlayers,
+    start: &GameStart,
+    history: &GameHistory,
+) -> bool {
+    let turn = evaluate_turn(start, history);
     let maybe_king =
         board.iter().find(|(_, piece)| piece.typ == PieceType::King && piece.color == turn);
     if let Some((king_pos, _)) = maybe_king {
@@ -32,6 +37,7 @@ mod tests {
         color::Color,
         game::{
             board::board_of_str,
+            game::GameStart,
             mode::standard_chess,
             mov::{GameMove, PieceMoveType},
             player::GamePlayer,
@@ -118,7 +124,7 @@ mod tests {
             ),
         ]
         .into();
-        assert!(!is_in_check(&board, &players, &history));
+        assert!(!is_in_check(&board, &players, &GameStart::default(), &history));
     }
 
     #[test]
@@ -199,6 +205,6 @@ mod tests {
             ),
         ]
         .into();
-        assert!(is_in_check(&board, &players, &history));
+        assert!(is_in_check(&board, &players, &GameStart::default(), &history));
     }
 }
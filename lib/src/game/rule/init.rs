@@ -1,26 +1,29 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::HashMap;
 
 use crate::{
     color::Color,
     game::{
+        board::GameBoard,
         capture::GameCapture,
-        game::{Game, GameHistory, empty_players},
+        game::{Game, GameBounds, GameHistory, GameStart, empty_players},
         mode::GameMode,
         mov::GameMoveType,
         player::GamePlayer,
         rule::{
             legal_moves::legal_moves_of_player,
-            move_piece::{self, move_piece},
+            move_piece::{self, play_game_move, refresh_moves},
             turn::evaluate_turn,
         },
-        selection::Selection,
+        zobrist::zobrist_of,
     },
+    mov::Mov,
     pos::Pos,
 };
 
 pub fn game_of_mode(mode: GameMode) -> Game {
     let board = mode.initial_board;
     let bounds = mode.bounds;
+    let start = GameStart::default();
     let history = Vec::new();
     let players = [
         (
@@ -31,6 +34,7 @@ pub fn game_of_mode(mode: GameMode) -> Game {
                 moves: legal_moves_of_player(
                     &board,
                     &bounds,
+                    &start,
                     &history,
                     &empty_players(),
                     &Color::Black,
@@ -45,6 +49,7 @@ pub fn game_of_mode(mode: GameMode) -> Game {
                 moves: legal_moves_of_player(
                     &board,
                     &bounds,
+                    &start,
                     &history,
                     &empty_players(),
                     &Color::White,
@@ -53,31 +58,85 @@ pub fn game_of_mode(mode: GameMode) -> Game {
         ),
     ]
     .into();
+    let hash = zobrist_of(&board, &start, &history);
 
-    Game { board, bounds, players, history }
+    Game { board, bounds, players, start, history, hash, events: Vec::new() }
 }
 
-pub fn game_of_mode_and_history(mode: GameMode, base_history: GameHistory) -> Game {
-    let bounds = mode.bounds.clone();
+pub fn game_of_mode_and_start(mode: GameMode, start: GameStart) -> Game {
     let mut game = game_of_mode(mode);
+    game.hash = zobrist_of(&game.board, &start, &game.history);
+    game.start = start;
+    refresh_moves(&mut game);
+    game
+}
 
+pub fn game_of_mode_and_history(mode: GameMode, base_history: GameHistory) -> Game {
+    let mut game = game_of_mode(mode);
     for game_move in base_history.iter() {
-        move_piece(
-            &mut game.board,
-            &mut game.history,
-            &mut game.players,
-            &bounds,
-            &Selection {
-                selected_pos: Some(game_move.mov.from.clone()),
-                selected_squares: HashSet::new(),
-            },
-            &game_move.mov.to,
-        );
+        play_game_move(&mut game, game_move);
     }
+    game
+}
 
+pub fn game_of_board(board: GameBoard, bounds: GameBounds, start: GameStart) -> Game {
+    let history = Vec::new();
+    let hash = zobrist_of(&board, &start, &history);
+    let mut game =
+        Game { board, bounds, players: empty_players(), start, history, hash, events: Vec::new() };
+    refresh_moves(&mut game);
     game
 }
 
+// the board before the first move, found by taking the history back from the current board
+pub fn start_board_of(game: &Game) -> Option<GameBoard> {
+    let mut board = game.board.clone();
+    for (ply, game_move) in game.history.iter().enumerate().rev() {
+        let Mov { piece, from, to } = &game_move.mov;
+        match game_move.typ {
+            GameMoveType::ShortCastling | GameMoveType::LongCastling => {
+                let (king_col, rook_col) =
+                    if game_move.typ == GameMoveType::ShortCastling { (6, 5) } else { (2, 3) };
+                let king = board.remove(&Pos::of(from.row, king_col))?;
+                let rook = board.remove(&Pos::of(from.row, rook_col))?;
+                board.insert(from.clone(), king);
+                board.insert(to.clone(), rook);
+            }
+            _ => {
+                board.remove(to)?;
+                board.insert(from.clone(), *piece);
+                let captured = game
+                    .players
+                    .get(&piece.color)?
+                    .captures
+                    .iter()
+                    .find(|capture| usize::from(capture.at) == ply);
+                if let Some(capture) = captured {
+                    let at = match game_move.typ {
+                        GameMoveType::EnPassant => Pos::of(from.row, to.col),
+                        _ => to.clone(),
+                    };
+                    board.insert(at, capture.piece);
+                }
+            }
+        }
+    }
+    Some(board)
+}
+
+pub fn game_of_start(game: &Game) -> Option<Game> {
+    Some(game_of_board(start_board_of(game)?, game.bounds.clone(), game.start.clone()))
+}
+
+// the position after the first plies of the history, replayed from the game's own start
+pub fn game_of_ply(game: &Game, ply: usize) -> Option<Game> {
+    let mut replay = game_of_start(game)?;
+    for game_move in game.history.get(..ply)? {
+        play_game_move(&mut replay, game_move);
+    }
+    Some(replay)
+}
+
 #[cfg(test)]
 mod tests {
     use std::collections::HashMap;
@@ -87,16 +146,19 @@ mod tests {
         game::{
             board::board_of_str,
             capture::GameCapture,
-            game::{Game, GameBounds},
+            fen::game_of_fen,
+            game::{Game, GameBounds, GameStart},
             mode::standard_chess,
             mov::{GameMove, PieceMoveType},
             player::GamePlayer,
+            rule::move_piece::play_move,
+            zobrist::zobrist_of,
         },
         piece::Piece,
         pos::pos_of,
     };
 
-    use super::{game_of_mode, game_of_mode_and_history};
+    use super::{game_of_mode, game_of_mode_and_history, game_of_ply, game_of_start};
 
     #[test]
     fn game_of_mode_standard_chess() {
@@ -323,7 +385,14 @@ mod tests {
                     ),
                 ]
                 .into(),
+                start: GameStart::default(),
                 history: Vec::new(),
+                hash: zobrist_of(
+                    &standard_chess().initial_board,
+                    &GameStart::default(),
+                    &Vec::new()
+                ),
+                events: Vec::new(),
             }
         );
     }
@@ -523,4 +592,19 @@ mod tests {
     //        }
     //    );
     //}
+
+    #[test]
+    fn game_of_start_of_fen() {
+        let fen_game = game_of_fen("r3k2r/pp1p4/8/2P5/8/8/1p6/R2QK2R b KQkq - 0 1");
+        let mut game = fen_game.clone();
+        let moves = [("D7", "D5"), ("C5", "D6"), ("B2", "A1"), ("E1", "H1"), ("E8", "A8")];
+        for (from, to) in moves {
+            play_move(&mut game, &pos_of(from), &pos_of(to));
+        }
+        assert_eq!(game.history.len(), 5);
+        assert_eq!(game_of_start(&game), Some(fen_game.clone()));
+        assert_eq!(game_of_ply(&game, 0), Some(fen_game));
+        assert_eq!(game_of_ply(&game, 5).map(|replay| replay.board), Some(game.board.clone()));
+        assert_eq!(game_of_ply(&game, 6), None);
+    }
 }
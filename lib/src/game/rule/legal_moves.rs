@@ -4,8 +4,8 @@ use crate::{
     color::Color,
     game::{
         board::{GameBoard, board_to_string},
-        game::{GameBounds, GameHistory, GamePlayers},
-        mov::{GameMove, GameMoveType},
+        game::{GameBounds, GameHistory, GamePlayers, GameStart},
+        mov::{GameMove, GameMoveType, PieceMoveType},
         player::PlayerMoves,
     },
     piece::PieceType,
@@ -19,33 +19,47 @@ use super::{
 pub fn legal_moves_of_player(
     board: &GameBoard,
     bounds: &GameBounds,
+    start: &GameStart,
     history: &GameHistory,
     players: &GamePlayers,
     color: &Color,
 ) -> PlayerMoves {
-    let turn = evaluate_turn(history);
-    let in_check = is_in_check(board, players, history);
+    let turn = evaluate_turn(start, history);
+    let in_check = is_in_check(board, players, start, history);
     let mut pseudo_legal_moves =
-        pseudo_legal_moves_of_player(board, bounds, history, players, color);
+        pseudo_legal_moves_of_player(board, bounds, start, history, players, color);
     for (from, piece_moves) in pseudo_legal_moves.clone() {
         let actual_moves = pseudo_legal_moves.get_mut(&from).unwrap();
         for (to, move_type) in piece_moves {
             let mut temp_board = board.clone();
             if let Some(piece) = temp_board.remove(&from) {
-                temp_board.insert(to.clone(), piece);
+                match move_type {
+                    PieceMoveType::ShortCastling | PieceMoveType::LongCastling => {
+                        let (king_col, rook_col) =
+                            if move_type == PieceMoveType::ShortCastling { (6, 5) } else { (2, 3) };
+                        if let Some(rook) = temp_board.remove(&to) {
+                            temp_board.insert(Pos::of(from.row, rook_col), rook);
+                        }
+                        temp_board.insert(Pos::of(from.row, king_col), piece);
+                    }
+                    _ => {
+                        temp_board.insert(to.clone(), piece);
+                    }
+                }
                 let mut temp_players = players.clone();
                 for player in temp_players.values_mut() {
                     if &player.color != color {
                         player.moves = pseudo_legal_moves_of_player(
                             &temp_board,
                             bounds,
+                            start,
                             history,
                             players,
                             &player.color,
                         );
                     }
                 }
-                if is_in_check(&temp_board, &temp_players, history) {
+                if is_in_check(&temp_board, &temp_players, start, history) {
                     actual_moves.remove(&to);
                 }
             }
@@ -84,7 +98,7 @@ mod tests {
         color::Color,
         game::{
             board::board_of_str,
-            game::empty_players,
+            game::{GameStart, empty_players},
             mode::standard_chess,
             mov::{GameMove, PieceMoveType},
             player::GamePlayer,
@@ -115,7 +129,14 @@ mod tests {
         let players = empty_players();
         let color = Color::White;
         assert_eq!(
-            legal_moves_of_player(&board, &bounds, &history, &players, &color),
+            legal_moves_of_player(
+                &board,
+                &bounds,
+                &GameStart::default(),
+                &history,
+                &players,
+                &color
+            ),
             [
                 (
                     pos_of("A2"),
@@ -162,7 +183,14 @@ mod tests {
         let players = empty_players();
         let color = Color::Black;
         assert_eq!(
-            legal_moves_of_player(&board, &bounds, &history, &players, &color),
+            legal_moves_of_player(
+                &board,
+                &bounds,
+                &GameStart::default(),
+                &history,
+                &players,
+                &color
+            ),
             [
                 (
                     pos_of("A8"),
@@ -224,6 +252,60 @@ mod tests {
         );
     }
 
+    #[test]
+    fn legal_moves_of_player_castling_destination() {
+        let mode = standard_chess();
+        let bounds = mode.bounds;
+        let history = Vec::new();
+        let players = empty_players();
+        let color = Color::White;
+        let board = board_of_str(
+            &bounds,
+            [
+                "♚      ♜",
+                "        ",
+                "        ",
+                "        ",
+                "        ",
+                "        ",
+                "        ",
+                "    ♔  ♖",
+            ],
+        );
+        let moves = legal_moves_of_player(
+            &board,
+            &bounds,
+            &GameStart::default(),
+            &history,
+            &players,
+            &color,
+        );
+        assert_eq!(moves[&pos_of("E1")].get(&pos_of("H1")), Some(&PieceMoveType::ShortCastling));
+        let board = board_of_str(
+            &bounds,
+            [
+                "♚     ♜ ",
+                "        ",
+                "        ",
+                "        ",
+                "        ",
+                "        ",
+                "        ",
+                "♖   ♔  ♖",
+            ],
+        );
+        let moves = legal_moves_of_player(
+            &board,
+            &bounds,
+            &GameStart::default(),
+            &history,
+            &players,
+            &color,
+        );
+        assert_eq!(moves[&pos_of("E1")].get(&pos_of("H1")), None);
+        assert_eq!(moves[&pos_of("E1")].get(&pos_of("A1")), Some(&PieceMoveType::LongCastling));
+    }
+
     #[test]
     fn legal_moves_of_player_free_king() {
         let mode = standard_chess();
@@ -245,7 +327,14 @@ mod tests {
         let players = empty_players();
         let color = Color::White;
         assert_eq!(
-            legal_moves_of_player(&board, &bounds, &history, &players, &color),
+            legal_moves_of_player(
+                &board,
+                &bounds,
+                &GameStart::default(),
+                &history,
+                &players,
+                &color
+            ),
             [(
                 pos_of("D4"),
                 [
@@ -300,7 +389,14 @@ mod tests {
         .into();
         let color = Color::White;
         assert_eq!(
-            legal_moves_of_player(&board, &bounds, &history, &players, &color),
+            legal_moves_of_player(
+                &board,
+                &bounds,
+                &GameStart::default(),
+                &history,
+                &players,
+                &color
+            ),
             [(
                 pos_of("D4"),
                 [(pos_of("D3"), PieceMoveType::Default), (pos_of("D5"), PieceMoveType::Default)]
@@ -424,7 +520,14 @@ mod tests {
         .into();
         let color = Color::White;
         assert_eq!(
-            legal_moves_of_player(&board, &bounds, &history, &players, &color),
+            legal_moves_of_player(
+                &board,
+                &bounds,
+                &GameStart::default(),
+                &history,
+                &players,
+                &color
+            ),
             [(pos_of("D4"), HashMap::new())].into()
         );
     }
@@ -481,7 +584,14 @@ mod tests {
         .into();
         let color = Color::White;
         assert_eq!(
-            legal_moves_of_player(&board, &bounds, &history, &players, &color),
+            legal_moves_of_player(
+                &board,
+                &bounds,
+                &GameStart::default(),
+                &history,
+                &players,
+                &color
+            ),
             [
                 (
                     pos_of("E1"),
@@ -566,7 +676,14 @@ mod tests {
         .into();
         let color = Color::White;
         assert_eq!(
-            legal_moves_of_player(&board, &bounds, &history, &players, &color),
+            legal_moves_of_player(
+                &board,
+                &bounds,
+                &GameStart::default(),
+                &history,
+                &players,
+                &color
+            ),
             [
                 (
                     pos_of("C4"),
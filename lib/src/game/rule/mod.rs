@@ -3,4 +3,5 @@ pub mod init;
 pub mod legal_moves;
 pub mod move_piece;
 pub mod pseudo_legal_moves;
+pub mod repetition;
 pub mod turn;
@@ -1,37 +1,68 @@
+use std::collections::HashMap;
+
 use crate::{
+    color::Color,
     game::{
         board::GameBoard,
         capture::GameCapture,
-        game::{GameBounds, GameHistory, GamePlayers},
-        mov::{GameMove, GameMoveType, PieceMoveType},
-        rule::{legal_moves::legal_moves_of_player, turn::evaluate_turn},
+        game::{Game, GameHistory, GameStart},
+        mov::{
+            GameMove, GameMoveType, PieceMoveType, is_promotion, promotion_of_game_move_type,
+            promotion_piece_type,
+        },
+        rule::{
+            legal_moves::legal_moves_of_player, pseudo_legal_moves::pseudo_legal_moves_of_player,
+            turn::evaluate_turn,
+        },
         selection::Selection,
+        zobrist::{
+            GameHash, zobrist_castling_of, zobrist_en_passant_of, zobrist_piece, zobrist_turn,
+        },
     },
     mov::Mov,
+    piece::{Piece, PieceType},
     pos::Pos,
 };
 
-pub fn move_piece(
-    board: &mut GameBoard,
-    history: &mut GameHistory,
-    players: &mut GamePlayers,
-    bounds: &GameBounds,
-    selection: &Selection,
-    to: &Pos,
-) {
+// the castling keys of the given colors and the en passant key, the only state keys besides the
+// turn that a move can change
+fn zobrist_move_state(
+    board: &GameBoard,
+    start: &GameStart,
+    history: &GameHistory,
+    castling_colors: &[Color],
+) -> GameHash {
+    castling_colors.iter().fold(zobrist_en_passant_of(board, start, history), |hash, color| {
+        hash ^ zobrist_castling_of(board, start, history, color)
+    })
+}
+
+pub fn move_piece(game: &mut Game, selection: &Selection, to: &Pos) {
+    let Game { board, bounds, players, start, history, hash, .. } = game;
     let _: Option<()> = (|| {
-        let turn = evaluate_turn(history);
+        let turn = evaluate_turn(start, history);
         let from = selection.selected_pos.clone()?;
         let selected_piece = board.get(&from)?;
         let selected_player = players.get_mut(&selected_piece.color)?;
         if selected_player.color == turn {
             let selected_piece_moves = selected_player.moves.get(&from).cloned()?;
             let game_move = selected_piece_moves.get(to)?;
+            // castling rights only change when a king or a rook moves or a rook is captured
+            let mut castling_colors: Vec<Color> = [board.get(&from), board.get(to)]
+                .into_iter()
+                .flatten()
+                .filter(|piece| matches!(piece.typ, PieceType::King | PieceType::Rook))
+                .map(|piece| piece.color)
+                .collect();
+            castling_colors.dedup();
+            let state_before = zobrist_move_state(board, start, history, &castling_colors);
             match *game_move {
                 PieceMoveType::Default => {
                     let piece = board.remove(&from)?;
                     let maybe_captured_piece = board.insert(to.clone(), piece);
+                    *hash ^= zobrist_piece(&piece, &from) ^ zobrist_piece(&piece, to);
                     if let Some(captured_piece) = maybe_captured_piece {
+                        *hash ^= zobrist_piece(&captured_piece, to);
                         selected_player
                             .captures
                             .push(GameCapture { piece: captured_piece, at: history.len() as u16 });
@@ -54,6 +85,9 @@ pub fn move_piece(
                     board.insert(to.clone(), pawn);
                     let capture_pos = Pos::of(from.row, to.col);
                     let captured_piece = board.remove(&capture_pos)?;
+                    *hash ^= zobrist_piece(&pawn, &from)
+                        ^ zobrist_piece(&pawn, to)
+                        ^ zobrist_piece(&captured_piece, &capture_pos);
                     selected_player
                         .captures
                         .push(GameCapture { piece: captured_piece, at: history.len() as u16 });
@@ -68,9 +102,11 @@ pub fn move_piece(
                 PieceMoveType::ShortCastling => {
                     let king = board.remove(&from)?;
                     let new_king_pos = Pos::of(from.row, 6);
+                    *hash ^= zobrist_piece(&king, &from) ^ zobrist_piece(&king, &new_king_pos);
                     board.insert(new_king_pos, king);
                     let rook = board.remove(to)?;
                     let new_rook_pos = Pos::of(from.row, 5);
+                    *hash ^= zobrist_piece(&rook, to) ^ zobrist_piece(&rook, &new_rook_pos);
                     board.insert(new_rook_pos, rook);
                     history.push(GameMove {
                         mov: Mov { from: from.clone(), to: to.clone(), piece: king },
@@ -80,66 +116,163 @@ pub fn move_piece(
                 PieceMoveType::LongCastling => {
                     let king = board.remove(&from)?;
                     let new_king_pos = Pos::of(from.row, 2);
+                    *hash ^= zobrist_piece(&king, &from) ^ zobrist_piece(&king, &new_king_pos);
                     board.insert(new_king_pos, king);
                     let rook = board.remove(to)?;
                     let new_rook_pos = Pos::of(from.row, 3);
+                    *hash ^= zobrist_piece(&rook, to) ^ zobrist_piece(&rook, &new_rook_pos);
                     board.insert(new_rook_pos, rook);
                     history.push(GameMove {
                         mov: Mov { from: from.clone(), to: to.clone(), piece: king },
                         typ: GameMoveType::LongCastling,
                     });
                 }
-                PieceMoveType::PromotionToQueen => {
-                    // board.insert(game_move.mov.to.clone(), promotion.piece);
-                    // edit the pawn mov
-                }
-                PieceMoveType::PromotionToKnight => {
-                    // board.insert(game_move.mov.to.clone(), promotion.piece);
-                    // edit the pawn mov
-                }
-                PieceMoveType::PromotionToRook => {
-                    // board.insert(game_move.mov.to.clone(), promotion.piece);
-                    // edit the pawn mov
-                }
-                PieceMoveType::PromotionToBishop => {
-                    // board.insert(game_move.mov.to.clone(), promotion.piece);
-                    // edit the pawn mov
+                PieceMoveType::PromotionToQueen
+                | PieceMoveType::PromotionToRook
+                | PieceMoveType::PromotionToBishop
+                | PieceMoveType::PromotionToKnight => {
+                    let pawn = board.remove(&from)?;
+                    let promoted =
+                        Piece { typ: promotion_piece_type(game_move)?, color: pawn.color };
+                    let maybe_captured_piece = board.insert(to.clone(), promoted);
+                    *hash ^= zobrist_piece(&pawn, &from) ^ zobrist_piece(&promoted, to);
+                    if let Some(captured_piece) = maybe_captured_piece {
+                        *hash ^= zobrist_piece(&captured_piece, to);
+                        selected_player
+                            .captures
+                            .push(GameCapture { piece: captured_piece, at: history.len() as u16 });
+                        if let Some(affected_player) = players.get_mut(&captured_piece.color) {
+                            affected_player.moves.remove(to);
+                        }
+                    }
+                    history.push(GameMove {
+                        mov: Mov { from: from.clone(), to: to.clone(), piece: pawn },
+                        typ: match game_move {
+                            PieceMoveType::PromotionToRook => GameMoveType::PromotionToRook,
+                            PieceMoveType::PromotionToBishop => GameMoveType::PromotionToBishop,
+                            PieceMoveType::PromotionToKnight => GameMoveType::PromotionToKnight,
+                            _ => GameMoveType::PromotionToQueen,
+                        },
+                    });
                 }
             }
-            let new_moves = legal_moves_of_player(board, bounds, history, &players.clone(), &turn);
+            *hash ^= state_before
+                ^ zobrist_move_state(board, start, history, &castling_colors)
+                ^ zobrist_turn(&Color::White)
+                ^ zobrist_turn(&Color::Black);
+            let new_moves =
+                legal_moves_of_player(board, bounds, start, history, &players.clone(), &turn);
             players.get_mut(&turn).unwrap().moves = new_moves;
         }
         None
     })();
 }
 
+pub fn play_move(game: &mut Game, from: &Pos, to: &Pos) {
+    let selection = Selection {
+        selected_pos: Some(from.clone()),
+        selected_squares: HashMap::new(),
+        arrows: HashMap::new(),
+    };
+    move_piece(game, &selection, to);
+    refresh_moves(game);
+}
+
+pub fn play_move_with_promotion(
+    game: &mut Game,
+    from: &Pos,
+    to: &Pos,
+    promotion: Option<&PieceMoveType>,
+) {
+    if let Some(promotion) = promotion.filter(|promotion| is_promotion(promotion)) {
+        let turn = evaluate_turn(&game.start, &game.history);
+        if let Some(typ) = game
+            .players
+            .get_mut(&turn)
+            .and_then(|player| player.moves.get_mut(from))
+            .and_then(|moves| moves.get_mut(to))
+            .filter(|typ| is_promotion(typ))
+        {
+            *typ = promotion.clone();
+        }
+    }
+    play_move(game, from, to);
+}
+
+pub fn play_game_move(game: &mut Game, game_move: &GameMove) {
+    let promotion = promotion_of_game_move_type(&game_move.typ);
+    play_move_with_promotion(game, &game_move.mov.from, &game_move.mov.to, promotion.as_ref());
+}
+
+pub fn refresh_moves(game: &mut Game) {
+    let turn = evaluate_turn(&game.start, &game.history);
+    let menaces: Vec<_> = game
+        .players
+        .keys()
+        .filter(|color| *color != &turn)
+        .map(|color| {
+            let moves = pseudo_legal_moves_of_player(
+                &game.board,
+                &game.bounds,
+                &game.start,
+                &game.history,
+                &game.players,
+                color,
+            );
+            (*color, moves)
+        })
+        .collect();
+    for (color, moves) in menaces {
+        if let Some(player) = game.players.get_mut(&color) {
+            player.moves = moves;
+        }
+    }
+    let moves = legal_moves_of_player(
+        &game.board,
+        &game.bounds,
+        &game.start,
+        &game.history,
+        &game.players,
+        &turn,
+    );
+    if let Some(player) = game.players.get_mut(&turn) {
+        player.moves = moves;
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use std::collections::HashSet;
+    use std::collections::HashMap;
 
     use crate::{
         color::Color,
         game::{
             board::board_of_str,
             capture::GameCapture,
+            fen::game_of_fen,
+            game::{Game, GameStart},
             mode::standard_chess,
-            mov::{GameMove, PieceMoveType},
+            mov::{GameMove, GameMoveType, PieceMoveType},
             player::GamePlayer,
             selection::Selection,
+            zobrist::zobrist_of,
         },
         piece::Piece,
         pos::pos_of,
     };
 
-    use super::move_piece;
+    use super::{move_piece, play_game_move, play_move, play_move_with_promotion};
 
     #[test]
     fn move_piece_default_move() {
         let mode = standard_chess();
-        let selection =
-            Selection { selected_pos: Some(pos_of("A2")), selected_squares: HashSet::new() };
+        let selection = Selection {
+            selected_pos: Some(pos_of("A2")),
+            selected_squares: HashMap::new(),
+            arrows: HashMap::new(),
+        };
 
-        let mut board = board_of_str(
+        let board = board_of_str(
             &mode.bounds,
             [
                 "    ♚   ",
@@ -152,8 +285,8 @@ mod tests {
                 "    ♔   ",
             ],
         );
-        let mut history = Vec::new();
-        let mut players = [
+        let history = Vec::new();
+        let players = [
             (
                 Color::Black,
                 GamePlayer {
@@ -215,7 +348,17 @@ mod tests {
         ]
         .into();
 
-        move_piece(&mut board, &mut history, &mut players, &mode.bounds, &selection, &pos_of("A4"));
+        let hash = zobrist_of(&board, &GameStart::default(), &history);
+        let mut game = Game {
+            board,
+            bounds: mode.bounds.clone(),
+            players,
+            start: GameStart::default(),
+            history,
+            hash,
+            events: Vec::new(),
+        };
+        move_piece(&mut game, &selection, &pos_of("A4"));
 
         let board_after = board_of_str(
             &mode.bounds,
@@ -286,18 +429,22 @@ mod tests {
         ]
         .into();
 
-        assert_eq!(board, board_after);
-        assert_eq!(history, history_after);
-        assert_eq!(players, players_after);
+        assert_eq!(game.board, board_after);
+        assert_eq!(game.history, history_after);
+        assert_eq!(game.players, players_after);
+        assert_eq!(game.hash, zobrist_of(&game.board, &game.start, &game.history));
     }
 
     #[test]
     fn move_piece_capture_move() {
         let mode = standard_chess();
-        let selection =
-            Selection { selected_pos: Some(pos_of("E5")), selected_squares: HashSet::new() };
+        let selection = Selection {
+            selected_pos: Some(pos_of("E5")),
+            selected_squares: HashMap::new(),
+            arrows: HashMap::new(),
+        };
 
-        let mut board = board_of_str(
+        let board = board_of_str(
             &mode.bounds,
             [
                 "    ♚   ",
@@ -310,8 +457,8 @@ mod tests {
                 "    ♔   ",
             ],
         );
-        let mut history = Vec::new();
-        let mut players = [
+        let history = Vec::new();
+        let players = [
             (
                 Color::Black,
                 GamePlayer {
@@ -373,7 +520,17 @@ mod tests {
         ]
         .into();
 
-        move_piece(&mut board, &mut history, &mut players, &mode.bounds, &selection, &pos_of("D6"));
+        let hash = zobrist_of(&board, &GameStart::default(), &history);
+        let mut game = Game {
+            board,
+            bounds: mode.bounds.clone(),
+            players,
+            start: GameStart::default(),
+            history,
+            hash,
+            events: Vec::new(),
+        };
+        move_piece(&mut game, &selection, &pos_of("D6"));
 
         let board_after = board_of_str(
             &mode.bounds,
@@ -434,18 +591,22 @@ mod tests {
         ]
         .into();
 
-        assert_eq!(board, board_after);
-        assert_eq!(history, history_after);
-        assert_eq!(players, players_after);
+        assert_eq!(game.board, board_after);
+        assert_eq!(game.history, history_after);
+        assert_eq!(game.players, players_after);
+        assert_eq!(game.hash, zobrist_of(&game.board, &game.start, &game.history));
     }
 
     #[test]
     fn move_piece_capture_en_passant_move() {
         let mode = standard_chess();
-        let selection =
-            Selection { selected_pos: Some(pos_of("E5")), selected_squares: HashSet::new() };
+        let selection = Selection {
+            selected_pos: Some(pos_of("E5")),
+            selected_squares: HashMap::new(),
+            arrows: HashMap::new(),
+        };
 
-        let mut board = board_of_str(
+        let board = board_of_str(
             &mode.bounds,
             [
                 "    ♚   ",
@@ -458,8 +619,8 @@ mod tests {
                 "    ♔   ",
             ],
         );
-        let mut history = Vec::new();
-        let mut players = [
+        let history = Vec::new();
+        let players = [
             (
                 Color::Black,
                 GamePlayer {
@@ -514,7 +675,17 @@ mod tests {
         ]
         .into();
 
-        move_piece(&mut board, &mut history, &mut players, &mode.bounds, &selection, &pos_of("D6"));
+        let hash = zobrist_of(&board, &GameStart::default(), &history);
+        let mut game = Game {
+            board,
+            bounds: mode.bounds.clone(),
+            players,
+            start: GameStart::default(),
+            history,
+            hash,
+            events: Vec::new(),
+        };
+        move_piece(&mut game, &selection, &pos_of("D6"));
 
         let board_after = board_of_str(
             &mode.bounds,
@@ -575,18 +746,22 @@ mod tests {
         ]
         .into();
 
-        assert_eq!(board, board_after);
-        assert_eq!(history, history_after);
-        assert_eq!(players, players_after);
+        assert_eq!(game.board, board_after);
+        assert_eq!(game.history, history_after);
+        assert_eq!(game.players, players_after);
+        assert_eq!(game.hash, zobrist_of(&game.board, &game.start, &game.history));
     }
 
     #[test]
     fn move_piece_capture_short_castling_move() {
         let mode = standard_chess();
-        let selection =
-            Selection { selected_pos: Some(pos_of("E1")), selected_squares: HashSet::new() };
+        let selection = Selection {
+            selected_pos: Some(pos_of("E1")),
+            selected_squares: HashMap::new(),
+            arrows: HashMap::new(),
+        };
 
-        let mut board = board_of_str(
+        let board = board_of_str(
             &mode.bounds,
             [
                 "    ♚   ",
@@ -599,8 +774,8 @@ mod tests {
                 "    ♔  ♖",
             ],
         );
-        let mut history = Vec::new();
-        let mut players = [
+        let history = Vec::new();
+        let players = [
             (
                 Color::Black,
                 GamePlayer {
@@ -668,7 +843,17 @@ mod tests {
         ]
         .into();
 
-        move_piece(&mut board, &mut history, &mut players, &mode.bounds, &selection, &pos_of("H1"));
+        let hash = zobrist_of(&board, &GameStart::default(), &history);
+        let mut game = Game {
+            board,
+            bounds: mode.bounds.clone(),
+            players,
+            start: GameStart::default(),
+            history,
+            hash,
+            events: Vec::new(),
+        };
+        move_piece(&mut game, &selection, &pos_of("H1"));
 
         let board_after = board_of_str(
             &mode.bounds,
@@ -752,18 +937,22 @@ mod tests {
         ]
         .into();
 
-        assert_eq!(board, board_after);
-        assert_eq!(history, history_after);
-        assert_eq!(players, players_after);
+        assert_eq!(game.board, board_after);
+        assert_eq!(game.history, history_after);
+        assert_eq!(game.players, players_after);
+        assert_eq!(game.hash, zobrist_of(&game.board, &game.start, &game.history));
     }
 
     #[test]
     fn move_piece_capture_long_castling_move() {
         let mode = standard_chess();
-        let selection =
-            Selection { selected_pos: Some(pos_of("E1")), selected_squares: HashSet::new() };
+        let selection = Selection {
+            selected_pos: Some(pos_of("E1")),
+            selected_squares: HashMap::new(),
+            arrows: HashMap::new(),
+        };
 
-        let mut board = board_of_str(
+        let board = board_of_str(
             &mode.bounds,
             [
                 "    ♚   ",
@@ -776,8 +965,8 @@ mod tests {
                 "♖   ♔   ",
             ],
         );
-        let mut history = Vec::new();
-        let mut players = [
+        let history = Vec::new();
+        let players = [
             (
                 Color::Black,
                 GamePlayer {
@@ -846,7 +1035,17 @@ mod tests {
         ]
         .into();
 
-        move_piece(&mut board, &mut history, &mut players, &mode.bounds, &selection, &pos_of("A1"));
+        let hash = zobrist_of(&board, &GameStart::default(), &history);
+        let mut game = Game {
+            board,
+            bounds: mode.bounds.clone(),
+            players,
+            start: GameStart::default(),
+            history,
+            hash,
+            events: Vec::new(),
+        };
+        move_piece(&mut game, &selection, &pos_of("A1"));
 
         let board_after = board_of_str(
             &mode.bounds,
@@ -930,8 +1129,68 @@ mod tests {
         ]
         .into();
 
-        assert_eq!(board, board_after);
-        assert_eq!(history, history_after);
-        assert_eq!(players, players_after);
+        assert_eq!(game.board, board_after);
+        assert_eq!(game.history, history_after);
+        assert_eq!(game.players, players_after);
+        assert_eq!(game.hash, zobrist_of(&game.board, &game.start, &game.history));
+    }
+
+    #[test]
+    fn play_move_promotion() {
+        let mut game = game_of_fen("1r2k3/2P5/8/8/8/8/8/4K3 w - - 0 1");
+        let mut queen = game.clone();
+        play_move(&mut queen, &pos_of("C7"), &pos_of("C8"));
+        assert_eq!(queen.board.get(&pos_of("C8")), Some(&Piece::of('♕')));
+        assert_eq!(queen.board.get(&pos_of("C7")), None);
+        assert_eq!(
+            queen.history.last(),
+            Some(&GameMove::of('♙', "C7", "C8", GameMoveType::PromotionToQueen))
+        );
+        assert_eq!(queen.hash, zobrist_of(&queen.board, &queen.start, &queen.history));
+
+        play_move_with_promotion(
+            &mut game,
+            &pos_of("C7"),
+            &pos_of("B8"),
+            Some(&PieceMoveType::PromotionToKnight),
+        );
+        assert_eq!(game.board.get(&pos_of("B8")), Some(&Piece::of('♘')));
+        assert_eq!(
+            game.history.last(),
+            Some(&GameMove::of('♙', "C7", "B8", GameMoveType::PromotionToKnight))
+        );
+        assert_eq!(
+            game.players[&Color::White].captures,
+            vec![GameCapture { piece: Piece::of('♜'), at: 0 }]
+        );
+        assert_eq!(game.hash, zobrist_of(&game.board, &game.start, &game.history));
+    }
+
+    #[test]
+    fn play_game_move_promotion() {
+        let mut game = game_of_fen("4k3/2P5/8/8/8/8/8/4K3 w - - 0 1");
+        play_game_move(&mut game, &GameMove::of('♙', "C7", "C8", GameMoveType::PromotionToRook));
+        assert_eq!(game.board.get(&pos_of("C8")), Some(&Piece::of('♖')));
+    }
+
+    #[test]
+    fn play_move_hash_of_state_changes() {
+        let mut game = game_of_fen("r3k2r/p7/8/8/1p6/8/2P4P/R3K2R w KQkq - 0 1");
+        let moves = [
+            ("C2", "C4"),
+            ("B4", "C3"),
+            ("A1", "A7"),
+            ("H8", "H7"),
+            ("E1", "H1"),
+            ("E8", "A8"),
+            ("H2", "H3"),
+            ("H7", "H3"),
+        ];
+        for (from, to) in moves {
+            let len = game.history.len();
+            play_move(&mut game, &pos_of(from), &pos_of(to));
+            assert_eq!(game.history.len(), len + 1);
+            assert_eq!(game.hash(), zobrist_of(&game.board, &game.start, &game.history));
+        }
     }
 }
@@ -4,7 +4,7 @@ use crate::{
     color::Color,
     game::{
         board::GameBoard,
-        game::{GameBounds, GameHistory, GamePlayers},
+        game::{GameBounds, GameHistory, GamePlayers, GameStart},
         mov::{
             default::default_moves,
             special::{castling::castling_moves, en_passant::en_passant_moves},
@@ -17,6 +17,7 @@ use crate::{
 pub fn pseudo_legal_moves_of_player(
     board: &GameBoard,
     bounds: &GameBounds,
+    start: &GameStart,
     history: &GameHistory,
     players: &GamePlayers,
     color: &Color,
@@ -26,10 +27,10 @@ pub fn pseudo_legal_moves_of_player(
         if &piece.color == color {
             let mut moves = default_moves(board, bounds, pos);
             if piece.typ == PieceType::Pawn {
-                moves.extend(en_passant_moves(board, history, pos));
+                moves.extend(en_passant_moves(board, start, history, pos));
             }
             if piece.typ == PieceType::King {
-                moves.extend(castling_moves(board, history, players, pos));
+                moves.extend(castling_moves(board, start, history, players, pos));
             }
             if !moves.is_empty() {
                 result.insert(pos.clone(), moves);
@@ -46,7 +47,7 @@ mod tests {
         color::Color,
         game::{
             board::board_of_str,
-            game::empty_players,
+            game::{GameStart, empty_players},
             mode::standard_chess,
             mov::{GameMove, PieceMoveType},
         },
@@ -76,7 +77,14 @@ mod tests {
         let players = empty_players();
         let color = Color::White;
         assert_eq!(
-            pseudo_legal_moves_of_player(&board, &bounds, &history, &players, &color),
+            pseudo_legal_moves_of_player(
+                &board,
+                &bounds,
+                &GameStart::default(),
+                &history,
+                &players,
+                &color
+            ),
             [
                 (
                     pos_of("A2"),
@@ -123,7 +131,14 @@ mod tests {
         let players = empty_players();
         let color = Color::Black;
         assert_eq!(
-            pseudo_legal_moves_of_player(&board, &bounds, &history, &players, &color),
+            pseudo_legal_moves_of_player(
+                &board,
+                &bounds,
+                &GameStart::default(),
+                &history,
+                &players,
+                &color
+            ),
             [
                 (
                     pos_of("A8"),
@@ -0,0 +1,158 @@
+use crate::{
+    game::{
+        game::{Game, GameHistory, GameStart},
+        mov::{GameMove, GameMoveType},
+        zobrist::{GameHash, zobrist_of},
+    },
+    piece::PieceType,
+};
+
+fn is_zeroing(game_move: &GameMove) -> bool {
+    game_move.mov.piece.typ == PieceType::Pawn
+        || !matches!(
+            game_move.typ,
+            GameMoveType::Default
+                | GameMoveType::Menace
+                | GameMoveType::ShortCastling
+                | GameMoveType::LongCastling
+        )
+}
+
+pub fn halfmove_clock(start: &GameStart, history: &GameHistory) -> usize {
+    match history.iter().rposition(is_zeroing) {
+        Some(i) => history.len() - i - 1,
+        None => usize::from(start.halfmove_clock) + history.len(),
+    }
+}
+
+// hashes of the earlier positions that can still repeat, most recent first
+pub fn repetition_hashes(game: &Game) -> Vec<GameHash> {
+    let mut board = game.board.clone();
+    let mut history = game.history.clone();
+    let mut hashes = Vec::new();
+    while let Some(game_move) = history.pop() {
+        if is_zeroing(&game_move)
+            || matches!(game_move.typ, GameMoveType::ShortCastling | GameMoveType::LongCastling)
+        {
+            break;
+        }
+        if let Some(piece) = board.remove(&game_move.mov.to) {
+            board.insert(game_move.mov.from, piece);
+        }
+        hashes.push(zobrist_of(&board, &game.start, &history));
+    }
+    hashes
+}
+
+pub fn repetition_count(game: &Game) -> usize {
+    let current = zobrist_of(&game.board, &game.start, &game.history);
+    1 + repetition_hashes(game).iter().filter(|hash| **hash == current).count()
+}
+
+pub fn is_threefold_repetition(game: &Game) -> bool {
+    repetition_count(game) >= 3
+}
+
+pub fn is_fifty_move_rule(game: &Game) -> bool {
+    halfmove_clock(&game.start, &game.history) >= 100
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        game::{
+            fen::game_of_fen,
+            game::GameStart,
+            mode::standard_chess,
+            mov::GameMove,
+            rule::{init::game_of_mode, move_piece::play_move},
+        },
+        pos::pos_of,
+    };
+
+    use super::{halfmove_clock, is_fifty_move_rule, is_threefold_repetition, repetition_count};
+
+    #[test]
+    fn halfmove_clock_of() {
+        assert_eq!(halfmove_clock(&GameStart::default(), &Vec::new()), 0);
+        assert_eq!(
+            halfmove_clock(
+                &GameStart::default(),
+                &vec![
+                    GameMove::default_of('♙', "E2", "E4"),
+                    GameMove::default_of('♟', "E7", "E5"),
+                    GameMove::default_of('♘', "G1", "F3"),
+                    GameMove::default_of('♞', "B8", "C6"),
+                ]
+            ),
+            2
+        );
+        assert_eq!(
+            halfmove_clock(
+                &GameStart::default(),
+                &vec![
+                    GameMove::default_of('♘', "G1", "F3"),
+                    GameMove::capture_of('♞', "B8", "C6"),
+                    GameMove::short_castling_of('♔', "E1", "H1"),
+                    GameMove::default_of('♞', "C6", "B8"),
+                ]
+            ),
+            2
+        );
+    }
+
+    #[test]
+    fn halfmove_clock_of_start() {
+        let start = GameStart { halfmove_clock: 42, ..GameStart::default() };
+        assert_eq!(halfmove_clock(&start, &Vec::new()), 42);
+        assert_eq!(halfmove_clock(&start, &vec![GameMove::default_of('♘', "G1", "F3")]), 43);
+        assert_eq!(halfmove_clock(&start, &vec![GameMove::default_of('♙', "E2", "E4")]), 0);
+        assert!(is_fifty_move_rule(&game_of_fen("4k3/8/8/8/8/8/8/4K2R w - - 100 80")));
+    }
+
+    #[test]
+    fn repetition_count_of() {
+        let mut game = game_of_mode(standard_chess());
+        assert_eq!(repetition_count(&game), 1);
+        let shuffle = [("G1", "F3"), ("G8", "F6"), ("F3", "G1"), ("F6", "G8")];
+        for (from, to) in shuffle {
+            play_move(&mut game, &pos_of(from), &pos_of(to));
+        }
+        assert_eq!(repetition_count(&game), 2);
+        assert!(!is_threefold_repetition(&game));
+        play_move(&mut game, &pos_of("G1"), &pos_of("F3"));
+        assert_eq!(repetition_count(&game), 2);
+        play_move(&mut game, &pos_of("G8"), &pos_of("F6"));
+        play_move(&mut game, &pos_of("F3"), &pos_of("G1"));
+        play_move(&mut game, &pos_of("F6"), &pos_of("G8"));
+        assert_eq!(repetition_count(&game), 3);
+        assert!(is_threefold_repetition(&game));
+        play_move(&mut game, &pos_of("E2"), &pos_of("E4"));
+        assert_eq!(repetition_count(&game), 1);
+    }
+
+    #[test]
+    fn repetition_count_castling_rights() {
+        let mut game = game_of_fen("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1");
+        for (from, to) in [("E1", "E2"), ("E8", "E7"), ("E2", "E1"), ("E7", "E8")] {
+            play_move(&mut game, &pos_of(from), &pos_of(to));
+        }
+        assert_eq!(repetition_count(&game), 1);
+        for (from, to) in [("E1", "E2"), ("E8", "E7"), ("E2", "E1"), ("E7", "E8")] {
+            play_move(&mut game, &pos_of(from), &pos_of(to));
+        }
+        assert_eq!(repetition_count(&game), 2);
+    }
+
+    #[test]
+    fn fifty_move_rule() {
+        let mut game = game_of_fen("4k3/8/8/8/8/8/8/R3K3 w - - 0 1");
+        let shuffle = [("A1", "A2"), ("E8", "D8"), ("A2", "A1"), ("D8", "E8")];
+        for i in 0..100 {
+            assert!(!is_fifty_move_rule(&game));
+            let (from, to) = shuffle[i % 4];
+            play_move(&mut game, &pos_of(from), &pos_of(to));
+        }
+        assert!(is_fifty_move_rule(&game));
+    }
+}
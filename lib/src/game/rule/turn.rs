@@ -1,24 +1,51 @@
-use crate::{color::Color, game::game::GameHistory};
+use crate::{
+    color::Color,
+    game::game::{GameHistory, GameStart},
+};
 
-pub fn evaluate_turn(history: &GameHistory) -> Color {
-    if history.len() % 2 == 0 { Color::White } else { Color::Black }
+pub fn evaluate_turn(start: &GameStart, history: &GameHistory) -> Color {
+    if history.len() % 2 == 0 {
+        start.turn
+    } else {
+        match start.turn {
+            Color::White => Color::Black,
+            Color::Black => Color::White,
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::{color::Color, game::mov::GameMove};
+    use crate::{
+        color::Color,
+        game::{game::GameStart, mov::GameMove},
+    };
 
     use super::evaluate_turn;
 
     #[test]
     fn test_get_turn() {
-        assert_eq!(evaluate_turn(&Vec::new()), Color::White);
-        assert_eq!(evaluate_turn(&vec![GameMove::default_of('♙', "D2", "D4")]), Color::Black);
+        let start = GameStart::default();
+        assert_eq!(evaluate_turn(&start, &Vec::new()), Color::White);
+        assert_eq!(
+            evaluate_turn(&start, &vec![GameMove::default_of('♙', "D2", "D4")]),
+            Color::Black
+        );
+        assert_eq!(
+            evaluate_turn(
+                &start,
+                &vec![GameMove::default_of('♙', "D2", "D4"), GameMove::default_of('♟', "A7", "A5"),]
+            ),
+            Color::White
+        );
+    }
+
+    #[test]
+    fn test_get_turn_black_start() {
+        let start = GameStart { turn: Color::Black, ..GameStart::default() };
+        assert_eq!(evaluate_turn(&start, &Vec::new()), Color::Black);
         assert_eq!(
-            evaluate_turn(&vec![
-                GameMove::default_of('♙', "D2", "D4"),
-                GameMove::default_of('♟', "A7", "A5"),
-            ]),
+            evaluate_turn(&start, &vec![GameMove::default_of('♟', "A7", "A5")]),
             Color::White
         );
     }
@@ -0,0 +1,272 @@
+use crate::{
+    game::{
+        game::Game,
+        mov::{PieceMoveType, is_promotion, promotion_of_game_move_type},
+        result::{GameResult, WinReason, game_result},
+        rule::{
+            check::is_in_check,
+            init::game_of_start,
+            move_piece::{play_game_move, play_move_with_promotion},
+            turn::evaluate_turn,
+        },
+    },
+    piece::PieceType,
+    pos::{Pos, pos_to_string},
+};
+
+fn piece_letter(typ: &PieceType) -> &'static str {
+    match typ {
+        PieceType::Pawn => "",
+        PieceType::Knight => "N",
+        PieceType::Bishop => "B",
+        PieceType::Rook => "R",
+        PieceType::Queen => "Q",
+        PieceType::King => "K",
+    }
+}
+
+fn square_parts(pos: &Pos) -> (String, String) {
+    let square = pos_to_string(pos).to_ascii_lowercase();
+    let digits = square.find(|c: char| c.is_ascii_digit()).unwrap_or(square.len());
+    (square[..digits].into(), square[digits..].into())
+}
+
+fn disambiguation(game: &Game, from: &Pos, to: &Pos, typ: &PieceType) -> String {
+    let turn = evaluate_turn(&game.start, &game.history);
+    let Some(player) = game.players.get(&turn) else {
+        return String::new();
+    };
+    let others: Vec<&Pos> = player
+        .moves
+        .iter()
+        .filter(|(pos, moves)| {
+            *pos != from
+                && moves.contains_key(to)
+                && game.board.get(pos).is_some_and(|piece| &piece.typ == typ)
+        })
+        .map(|(pos, _)| pos)
+        .collect();
+    if others.is_empty() {
+        return String::new();
+    }
+    let (file, rank) = square_parts(from);
+    if others.iter().all(|pos| pos.col != from.col) {
+        file
+    } else if others.iter().all(|pos| pos.row != from.row) {
+        rank
+    } else {
+        file + &rank
+    }
+}
+
+pub fn san_of(game: &Game, from: &Pos, to: &Pos) -> Option<String> {
+    san_of_with_promotion(game, from, to, None)
+}
+
+pub fn san_of_with_promotion(
+    game: &Game,
+    from: &Pos,
+    to: &Pos,
+    promotion: Option<&PieceMoveType>,
+) -> Option<String> {
+    let turn = evaluate_turn(&game.start, &game.history);
+    let mut typ = game.players.get(&turn)?.moves.get(from)?.get(to)?;
+    if let Some(promotion) =
+        promotion.filter(|promotion| is_promotion(typ) && is_promotion(promotion))
+    {
+        typ = promotion;
+    }
+    let piece = game.board.get(from)?;
+    let mut san = match typ {
+        PieceMoveType::ShortCastling => "O-O".to_string(),
+        PieceMoveType::LongCastling => "O-O-O".to_string(),
+        _ => {
+            let is_capture = game.board.contains_key(to) || typ == &PieceMoveType::EnPassant;
+            let (file, rank) = square_parts(to);
+            let prefix = if piece.typ == PieceType::Pawn {
+                if is_capture { square_parts(from).0 } else { String::new() }
+            } else {
+                piece_letter(&piece.typ).to_string() + &disambiguation(game, from, to, &piece.typ)
+            };
+            let promotion = match typ {
+                PieceMoveType::PromotionToQueen => "=Q",
+                PieceMoveType::PromotionToRook => "=R",
+                PieceMoveType::PromotionToBishop => "=B",
+                PieceMoveType::PromotionToKnight => "=N",
+                _ => "",
+            };
+            prefix + if is_capture { "x" } else { "" } + &file + &rank + promotion
+        }
+    };
+    let mut after = game.clone();
+    play_move_with_promotion(&mut after, from, to, Some(typ));
+    if after.history.len() == game.history.len() {
+        return None;
+    }
+    if let Some(GameResult::Win(_, WinReason::Checkmate)) = game_result(&after) {
+        san.push('#');
+    } else if is_in_check(&after.board, &after.players, &after.start, &after.history) {
+        san.push('+');
+    }
+    Some(san)
+}
+
+pub fn san_history_of(game: &Game) -> Option<Vec<String>> {
+    let mut replay = game_of_start(game)?;
+    let mut result = Vec::new();
+    for game_move in game.history.iter() {
+        let promotion = promotion_of_game_move_type(&game_move.typ);
+        result.push(san_of_with_promotion(
+            &replay,
+            &game_move.mov.from,
+            &game_move.mov.to,
+            promotion.as_ref(),
+        )?);
+        play_game_move(&mut replay, game_move);
+    }
+    if replay.board != game.board {
+        return None;
+    }
+    Some(result)
+}
+
+fn san_trimmed(s: &str) -> &str {
+    s.trim_end_matches(['+', '#', '!', '?'])
+}
+
+pub fn san_promotion_of(s: &str) -> Option<PieceMoveType> {
+    let (_, piece) = san_trimmed(s).rsplit_once('=')?;
+    match piece {
+        "Q" => Some(PieceMoveType::PromotionToQueen),
+        "R" => Some(PieceMoveType::PromotionToRook),
+        "B" => Some(PieceMoveType::PromotionToBishop),
+        "N" => Some(PieceMoveType::PromotionToKnight),
+        _ => None,
+    }
+}
+
+pub fn san_try_of(game: &Game, s: &str) -> Option<(Pos, Pos)> {
+    let trimmed = san_trimmed(s);
+    let promotion = san_promotion_of(s);
+    let target =
+        if trimmed.starts_with("0-0") { trimmed.replace('0', "O") } else { trimmed.to_string() };
+    let turn = evaluate_turn(&game.start, &game.history);
+    let player = game.players.get(&turn)?;
+    player.moves.iter().flat_map(|(from, moves)| moves.keys().map(move |to| (from, to))).find_map(
+        |(from, to)| {
+            let san = san_of_with_promotion(game, from, to, promotion.as_ref())?;
+            if san_trimmed(&san) == target { Some((from.clone(), to.clone())) } else { None }
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        game::{
+            fen::game_of_fen,
+            mode::standard_chess,
+            mov::PieceMoveType,
+            rule::{init::game_of_mode, move_piece::play_move},
+        },
+        pos::pos_of,
+    };
+
+    use super::{san_history_of, san_of, san_of_with_promotion, san_promotion_of, san_try_of};
+
+    #[test]
+    fn san_of_default() {
+        let game = game_of_mode(standard_chess());
+        assert_eq!(san_of(&game, &pos_of("E2"), &pos_of("E4")), Some("e4".into()));
+        assert_eq!(san_of(&game, &pos_of("G1"), &pos_of("F3")), Some("Nf3".into()));
+        assert_eq!(san_of(&game, &pos_of("E2"), &pos_of("E5")), None);
+        assert_eq!(san_of(&game, &pos_of("E7"), &pos_of("E5")), None);
+    }
+
+    #[test]
+    fn san_of_captures() {
+        let mut game = game_of_mode(standard_chess());
+        for (from, to) in [("E2", "E4"), ("D7", "D5")] {
+            play_move(&mut game, &pos_of(from), &pos_of(to));
+        }
+        assert_eq!(san_of(&game, &pos_of("E4"), &pos_of("D5")), Some("exd5".into()));
+        let game = game_of_fen("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1");
+        assert_eq!(san_of(&game, &pos_of("E5"), &pos_of("D6")), Some("exd6".into()));
+        let game = game_of_fen("4k3/8/8/3p4/8/2N5/8/4K3 w - - 0 1");
+        assert_eq!(san_of(&game, &pos_of("C3"), &pos_of("D5")), Some("Nxd5".into()));
+    }
+
+    #[test]
+    fn san_of_disambiguation() {
+        let game = game_of_fen("4k3/8/8/8/8/8/8/R4R1K w - - 0 1");
+        assert_eq!(san_of(&game, &pos_of("A1"), &pos_of("C1")), Some("Rac1".into()));
+        assert_eq!(san_of(&game, &pos_of("F1"), &pos_of("D1")), Some("Rfd1".into()));
+        assert_eq!(san_of(&game, &pos_of("A1"), &pos_of("A5")), Some("Ra5".into()));
+        let game = game_of_fen("4k3/8/8/R7/8/8/8/R3K3 w - - 0 1");
+        assert_eq!(san_of(&game, &pos_of("A1"), &pos_of("A3")), Some("R1a3".into()));
+        let game = game_of_fen("k7/8/8/8/8/2Q1Q3/8/2Q1K3 w - - 0 1");
+        assert_eq!(san_of(&game, &pos_of("C3"), &pos_of("D2")), Some("Qc3d2".into()));
+    }
+
+    #[test]
+    fn san_of_castling_and_check() {
+        let game = game_of_fen("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1");
+        assert_eq!(san_of(&game, &pos_of("E1"), &pos_of("H1")), Some("O-O".into()));
+        assert_eq!(san_of(&game, &pos_of("E1"), &pos_of("A1")), Some("O-O-O".into()));
+        assert_eq!(san_of(&game, &pos_of("A1"), &pos_of("A8")), Some("Rxa8+".into()));
+        let game = game_of_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1");
+        assert_eq!(san_of(&game, &pos_of("A1"), &pos_of("A8")), Some("Ra8#".into()));
+    }
+
+    #[test]
+    fn san_try_of_moves() {
+        let game = game_of_mode(standard_chess());
+        assert_eq!(san_try_of(&game, "e4"), Some((pos_of("E2"), pos_of("E4"))));
+        assert_eq!(san_try_of(&game, "Nf3!"), Some((pos_of("G1"), pos_of("F3"))));
+        assert_eq!(san_try_of(&game, "e5"), None);
+        assert_eq!(san_try_of(&game, "Nf4"), None);
+        assert_eq!(san_try_of(&game, ""), None);
+        let game = game_of_fen("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1");
+        assert_eq!(san_try_of(&game, "O-O"), Some((pos_of("E1"), pos_of("H1"))));
+        assert_eq!(san_try_of(&game, "0-0-0"), Some((pos_of("E1"), pos_of("A1"))));
+        assert_eq!(san_try_of(&game, "Rxa8"), Some((pos_of("A1"), pos_of("A8"))));
+        let game = game_of_fen("4k3/8/8/8/8/8/8/R4R1K w - - 0 1");
+        assert_eq!(san_try_of(&game, "Rc1"), None);
+        assert_eq!(san_try_of(&game, "Rac1"), Some((pos_of("A1"), pos_of("C1"))));
+    }
+
+    #[test]
+    fn san_history_of_game() {
+        let mut game = game_of_mode(standard_chess());
+        assert_eq!(san_history_of(&game), Some(Vec::new()));
+        for (from, to) in [("E2", "E4"), ("D7", "D5"), ("E4", "D5"), ("D8", "D5")] {
+            play_move(&mut game, &pos_of(from), &pos_of(to));
+        }
+        assert_eq!(
+            san_history_of(&game),
+            Some(vec!["e4".into(), "d5".into(), "exd5".into(), "Qxd5".into()])
+        );
+        let mut game = game_of_fen("4k3/8/8/8/8/8/4P3/4K3 w - - 0 1");
+        play_move(&mut game, &pos_of("E2"), &pos_of("E4"));
+        assert_eq!(san_history_of(&game), Some(vec!["e4".into()]));
+    }
+
+    #[test]
+    fn san_promotion() {
+        let game = game_of_fen("1r2k3/2P5/8/8/8/8/8/4K3 w - - 0 1");
+        assert_eq!(san_of(&game, &pos_of("C7"), &pos_of("C8")), Some("c8=Q+".into()));
+        assert_eq!(
+            san_of_with_promotion(
+                &game,
+                &pos_of("C7"),
+                &pos_of("B8"),
+                Some(&PieceMoveType::PromotionToKnight)
+            ),
+            Some("cxb8=N".into())
+        );
+        assert_eq!(san_promotion_of("cxb8=N+"), Some(PieceMoveType::PromotionToKnight));
+        assert_eq!(san_promotion_of("c8"), None);
+        assert_eq!(san_try_of(&game, "c8=R+"), Some((pos_of("C7"), pos_of("C8"))));
+        assert_eq!(san_try_of(&game, "cxb8=N"), Some((pos_of("C7"), pos_of("B8"))));
+    }
+}
@@ -1,24 +1,61 @@
-use std::collections::HashSet;
+use std::collections::HashMap;
 
 use crate::{
     game::{
         board::GameBoard,
-        game::{GameHistory, GamePlayers},
+        game::{GameHistory, GamePlayers, GameStart},
         rule::turn::evaluate_turn,
     },
     pos::Pos,
 };
 
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum AnnotationColor {
+    Green,
+    Red,
+    Yellow,
+    Blue,
+}
+
 #[derive(Debug, PartialEq)]
 pub struct Selection {
-    pub selected_squares: HashSet<Pos>,
+    pub selected_squares: HashMap<Pos, AnnotationColor>,
+    pub arrows: HashMap<(Pos, Pos), AnnotationColor>,
     pub selected_pos: Option<Pos>,
 }
 
+pub fn toggle_square_annotation(selection: &mut Selection, pos: Pos, color: AnnotationColor) {
+    if selection.selected_squares.get(&pos) == Some(&color) {
+        selection.selected_squares.remove(&pos);
+    } else {
+        selection.selected_squares.insert(pos, color);
+    }
+}
+
+pub fn toggle_arrow_annotation(
+    selection: &mut Selection,
+    from: Pos,
+    to: Pos,
+    color: AnnotationColor,
+) {
+    let arrow = (from, to);
+    if selection.arrows.get(&arrow) == Some(&color) {
+        selection.arrows.remove(&arrow);
+    } else {
+        selection.arrows.insert(arrow, color);
+    }
+}
+
+pub fn clear_annotations(selection: &mut Selection) {
+    selection.selected_squares.clear();
+    selection.arrows.clear();
+}
+
 pub fn toggle_selection(
     selection: &mut Selection,
     board: &GameBoard,
     players: &GamePlayers,
+    start: &GameStart,
     history: &GameHistory,
     pos: Pos,
 ) {
@@ -28,7 +65,7 @@ pub fn toggle_selection(
             if let Some(player) = players.get(&selected_piece.color) {
                 if let Some(selected_piece_moves) = player.moves.get(selected_pos) {
                     if selected_piece_moves.iter().any(|(to, _)| to == &pos) {
-                        selection.selected_squares.clear();
+                        clear_annotations(selection);
                         selection.selected_pos = None;
                         return;
                     }
@@ -44,7 +81,7 @@ pub fn toggle_selection(
                 return;
             }
         }
-        let turn = evaluate_turn(history);
+        let turn = evaluate_turn(start, history);
         if turn == piece.color {
             if let Some(player) = players.get(&turn) {
                 if let Some(moves) = player.moves.get(&pos) {
@@ -52,11 +89,11 @@ pub fn toggle_selection(
 
                     if 1 == 1 {
                         // Player selected another piece of himself
-                        selection.selected_squares.clear();
+                        clear_annotations(selection);
                         selection.selected_pos = Some(pos.clone());
                     } else {
                         // Player selected another piece of himself that is locked
-                        selection.selected_squares.clear();
+                        clear_annotations(selection);
                         selection.selected_pos = None;
                     }
                     return;
@@ -65,47 +102,68 @@ pub fn toggle_selection(
             return;
         }
         // Player selected another player piece
-        selection.selected_squares.clear();
+        clear_annotations(selection);
         selection.selected_pos = None;
         return;
     }
     // Player selected empty square already selected
-    if selection.selected_squares.contains(&pos) {
+    if selection.selected_squares.contains_key(&pos) {
         selection.selected_squares.remove(&pos);
         return;
     }
     // empty square
-    selection.selected_squares.insert(pos);
+    selection.selected_squares.insert(pos, AnnotationColor::Green);
     selection.selected_pos = None;
 }
 
 #[cfg(test)]
 mod tests {
-    use std::collections::HashSet;
+    use std::collections::HashMap;
 
     use crate::{
         color::Color,
         game::{
-            board::board_of_str, game::empty_players, mode::standard_chess, mov::PieceMoveType,
+            board::board_of_str,
+            game::{GameStart, empty_players},
+            mode::standard_chess,
+            mov::PieceMoveType,
             player::GamePlayer,
         },
         pos::pos_of,
     };
 
-    use super::{Selection, toggle_selection};
+    use super::{
+        AnnotationColor, Selection, clear_annotations, toggle_arrow_annotation, toggle_selection,
+        toggle_square_annotation,
+    };
 
     #[test]
     fn player_selected_empty_square() {
         let mode = standard_chess();
-        let mut selection = Selection { selected_squares: HashSet::new(), selected_pos: None };
+        let mut selection = Selection {
+            selected_squares: HashMap::new(),
+            arrows: HashMap::new(),
+            selected_pos: None,
+        };
         let board = mode.initial_board;
         let players = empty_players();
         let history = Vec::new();
         let pos = pos_of("D4");
-        toggle_selection(&mut selection, &board, &players, &history, pos.clone());
+        toggle_selection(
+            &mut selection,
+            &board,
+            &players,
+            &GameStart::default(),
+            &history,
+            pos.clone(),
+        );
         assert_eq!(
             selection,
-            Selection { selected_squares: HashSet::from([pos.clone()]), selected_pos: None }
+            Selection {
+                selected_squares: HashMap::from([(pos.clone(), AnnotationColor::Green)]),
+                arrows: HashMap::new(),
+                selected_pos: None
+            }
         );
     }
 
@@ -113,20 +171,40 @@ mod tests {
     fn player_toggle_select_empty_square() {
         let mode = standard_chess();
         let pos = pos_of("D4");
-        let mut selection =
-            Selection { selected_squares: HashSet::from([pos.clone()]), selected_pos: None };
+        let mut selection = Selection {
+            selected_squares: HashMap::from([(pos.clone(), AnnotationColor::Green)]),
+            arrows: HashMap::new(),
+            selected_pos: None,
+        };
         let board = mode.initial_board;
         let players = empty_players();
         let history = Vec::new();
-        toggle_selection(&mut selection, &board, &players, &history, pos.clone());
-        assert_eq!(selection, Selection { selected_squares: HashSet::new(), selected_pos: None });
+        toggle_selection(
+            &mut selection,
+            &board,
+            &players,
+            &GameStart::default(),
+            &history,
+            pos.clone(),
+        );
+        assert_eq!(
+            selection,
+            Selection {
+                selected_squares: HashMap::new(),
+                arrows: HashMap::new(),
+                selected_pos: None
+            }
+        );
     }
 
     #[test]
     fn player_select_own_piece() {
         let mode = standard_chess();
-        let mut selection =
-            Selection { selected_squares: HashSet::from([pos_of("D4")]), selected_pos: None };
+        let mut selection = Selection {
+            selected_squares: HashMap::from([(pos_of("D4"), AnnotationColor::Green)]),
+            arrows: HashMap::new(),
+            selected_pos: None,
+        };
         let board = mode.initial_board;
         let players = [
             (Color::Black, GamePlayer::from(Color::Black)),
@@ -149,10 +227,21 @@ mod tests {
         ]
         .into();
         let history = Vec::new();
-        toggle_selection(&mut selection, &board, &players, &history, pos_of("B2"));
+        toggle_selection(
+            &mut selection,
+            &board,
+            &players,
+            &GameStart::default(),
+            &history,
+            pos_of("B2"),
+        );
         assert_eq!(
             selection,
-            Selection { selected_squares: HashSet::new(), selected_pos: Some(pos_of("B2")) }
+            Selection {
+                selected_squares: HashMap::new(),
+                arrows: HashMap::new(),
+                selected_pos: Some(pos_of("B2"))
+            }
         );
     }
 
@@ -161,37 +250,60 @@ mod tests {
         let mode = standard_chess();
         let pos = pos_of("B2");
         let mut selection = Selection {
-            selected_squares: HashSet::from([pos_of("D4")]),
+            selected_squares: HashMap::from([(pos_of("D4"), AnnotationColor::Green)]),
+            arrows: HashMap::new(),
             selected_pos: Some(pos.clone()),
         };
         let board = mode.initial_board;
         let players = empty_players();
         let history = Vec::new();
-        toggle_selection(&mut selection, &board, &players, &history, pos.clone());
+        toggle_selection(
+            &mut selection,
+            &board,
+            &players,
+            &GameStart::default(),
+            &history,
+            pos.clone(),
+        );
         assert_eq!(
             selection,
-            Selection { selected_squares: HashSet::from([pos_of("D4")]), selected_pos: None }
+            Selection {
+                selected_squares: HashMap::from([(pos_of("D4"), AnnotationColor::Green)]),
+                arrows: HashMap::new(),
+                selected_pos: None
+            }
         );
     }
 
     #[test]
     fn player_select_another_player_piece() {
         let mode = standard_chess();
-        let mut selection =
-            Selection { selected_squares: HashSet::from([pos_of("D4")]), selected_pos: None };
+        let mut selection = Selection {
+            selected_squares: HashMap::from([(pos_of("D4"), AnnotationColor::Green)]),
+            arrows: HashMap::new(),
+            selected_pos: None,
+        };
         let board = mode.initial_board;
         let players = empty_players();
         let history = Vec::new();
         let pos = pos_of("G7");
-        toggle_selection(&mut selection, &board, &players, &history, pos);
-        assert_eq!(selection, Selection { selected_squares: HashSet::new(), selected_pos: None });
+        toggle_selection(&mut selection, &board, &players, &GameStart::default(), &history, pos);
+        assert_eq!(
+            selection,
+            Selection {
+                selected_squares: HashMap::new(),
+                arrows: HashMap::new(),
+                selected_pos: None
+            }
+        );
     }
 
     #[test]
     fn player_select_own_piece_then_another_own_piece() {
         let mode = standard_chess();
         let mut selection = Selection {
-            selected_squares: HashSet::from([pos_of("D4")]),
+            selected_squares: HashMap::from([(pos_of("D4"), AnnotationColor::Green)]),
+            arrows: HashMap::new(),
             selected_pos: Some(pos_of("B2")),
         };
         let board = mode.initial_board;
@@ -217,10 +329,21 @@ mod tests {
         ]
         .into();
         let history = Vec::new();
-        toggle_selection(&mut selection, &board, &players, &history, pos.clone());
+        toggle_selection(
+            &mut selection,
+            &board,
+            &players,
+            &GameStart::default(),
+            &history,
+            pos.clone(),
+        );
         assert_eq!(
             selection,
-            Selection { selected_squares: HashSet::new(), selected_pos: Some(pos.clone()) }
+            Selection {
+                selected_squares: HashMap::new(),
+                arrows: HashMap::new(),
+                selected_pos: Some(pos.clone())
+            }
         );
     }
 
@@ -228,20 +351,38 @@ mod tests {
     fn player_select_own_piece_then_another_player_piece() {
         let mode = standard_chess();
         let mut selection = Selection {
-            selected_squares: HashSet::from([pos_of("D4")]),
+            selected_squares: HashMap::from([(pos_of("D4"), AnnotationColor::Green)]),
+            arrows: HashMap::new(),
             selected_pos: Some(pos_of("B2")),
         };
         let board = mode.initial_board;
         let players = empty_players();
         let history = Vec::new();
-        toggle_selection(&mut selection, &board, &players, &history, pos_of("B7"));
-        assert_eq!(selection, Selection { selected_squares: HashSet::new(), selected_pos: None });
+        toggle_selection(
+            &mut selection,
+            &board,
+            &players,
+            &GameStart::default(),
+            &history,
+            pos_of("B7"),
+        );
+        assert_eq!(
+            selection,
+            Selection {
+                selected_squares: HashMap::new(),
+                arrows: HashMap::new(),
+                selected_pos: None
+            }
+        );
     }
 
     #[test]
     fn player_select_mov() {
-        let mut selection =
-            Selection { selected_squares: HashSet::new(), selected_pos: Some(pos_of("D5")) };
+        let mut selection = Selection {
+            selected_squares: HashMap::new(),
+            arrows: HashMap::new(),
+            selected_pos: Some(pos_of("D5")),
+        };
         let mode = standard_chess();
         let board = board_of_str(
             &mode.bounds,
@@ -279,14 +420,31 @@ mod tests {
         ]
         .into();
         let history = Vec::new();
-        toggle_selection(&mut selection, &board, &players, &history, pos_of("E5"));
-        assert_eq!(selection, Selection { selected_squares: HashSet::new(), selected_pos: None });
+        toggle_selection(
+            &mut selection,
+            &board,
+            &players,
+            &GameStart::default(),
+            &history,
+            pos_of("E5"),
+        );
+        assert_eq!(
+            selection,
+            Selection {
+                selected_squares: HashMap::new(),
+                arrows: HashMap::new(),
+                selected_pos: None
+            }
+        );
     }
 
     #[test]
     fn player_select_en_passant_mov() {
-        let mut selection =
-            Selection { selected_squares: HashSet::new(), selected_pos: Some(pos_of("A5")) };
+        let mut selection = Selection {
+            selected_squares: HashMap::new(),
+            arrows: HashMap::new(),
+            selected_pos: Some(pos_of("A5")),
+        };
         let mode = standard_chess();
         let board = board_of_str(
             &mode.bounds,
@@ -322,14 +480,31 @@ mod tests {
         ]
         .into();
         let history = Vec::new();
-        toggle_selection(&mut selection, &board, &players, &history, pos_of("B6"));
-        assert_eq!(selection, Selection { selected_squares: HashSet::new(), selected_pos: None });
+        toggle_selection(
+            &mut selection,
+            &board,
+            &players,
+            &GameStart::default(),
+            &history,
+            pos_of("B6"),
+        );
+        assert_eq!(
+            selection,
+            Selection {
+                selected_squares: HashMap::new(),
+                arrows: HashMap::new(),
+                selected_pos: None
+            }
+        );
     }
 
     #[test]
     fn player_select_castling_mov() {
-        let mut selection =
-            Selection { selected_squares: HashSet::new(), selected_pos: Some(pos_of("E1")) };
+        let mut selection = Selection {
+            selected_squares: HashMap::new(),
+            arrows: HashMap::new(),
+            selected_pos: Some(pos_of("E1")),
+        };
         let mode = standard_chess();
         let board = board_of_str(
             &mode.bounds,
@@ -370,14 +545,31 @@ mod tests {
         ]
         .into();
         let history = Vec::new();
-        toggle_selection(&mut selection, &board, &players, &history, pos_of("A1"));
-        assert_eq!(selection, Selection { selected_squares: HashSet::new(), selected_pos: None });
+        toggle_selection(
+            &mut selection,
+            &board,
+            &players,
+            &GameStart::default(),
+            &history,
+            pos_of("A1"),
+        );
+        assert_eq!(
+            selection,
+            Selection {
+                selected_squares: HashMap::new(),
+                arrows: HashMap::new(),
+                selected_pos: None
+            }
+        );
     }
 
     #[test]
     fn player_select_menace_mov() {
-        let mut selection =
-            Selection { selected_squares: HashSet::new(), selected_pos: Some(pos_of("E2")) };
+        let mut selection = Selection {
+            selected_squares: HashMap::new(),
+            arrows: HashMap::new(),
+            selected_pos: Some(pos_of("E2")),
+        };
         let mode = standard_chess();
         let board = board_of_str(
             &mode.bounds,
@@ -413,10 +605,68 @@ mod tests {
         ]
         .into();
         let history = Vec::new();
-        toggle_selection(&mut selection, &board, &players, &history, pos_of("F3"));
+        toggle_selection(
+            &mut selection,
+            &board,
+            &players,
+            &GameStart::default(),
+            &history,
+            pos_of("F3"),
+        );
         assert_eq!(
             selection,
-            Selection { selected_squares: HashSet::from([pos_of("F3")]), selected_pos: None }
+            Selection {
+                selected_squares: HashMap::from([(pos_of("F3"), AnnotationColor::Green)]),
+                arrows: HashMap::new(),
+                selected_pos: None
+            }
+        );
+    }
+
+    #[test]
+    fn annotations_toggle() {
+        let mut selection = Selection {
+            selected_squares: HashMap::new(),
+            arrows: HashMap::new(),
+            selected_pos: None,
+        };
+        toggle_square_annotation(&mut selection, pos_of("E4"), AnnotationColor::Red);
+        toggle_square_annotation(&mut selection, pos_of("D4"), AnnotationColor::Green);
+        toggle_square_annotation(&mut selection, pos_of("D4"), AnnotationColor::Blue);
+        toggle_arrow_annotation(&mut selection, pos_of("E2"), pos_of("E4"), AnnotationColor::Green);
+        toggle_arrow_annotation(
+            &mut selection,
+            pos_of("G1"),
+            pos_of("F3"),
+            AnnotationColor::Yellow,
+        );
+        toggle_arrow_annotation(
+            &mut selection,
+            pos_of("G1"),
+            pos_of("F3"),
+            AnnotationColor::Yellow,
+        );
+        assert_eq!(
+            selection,
+            Selection {
+                selected_squares: HashMap::from([
+                    (pos_of("E4"), AnnotationColor::Red),
+                    (pos_of("D4"), AnnotationColor::Blue),
+                ]),
+                arrows: HashMap::from([((pos_of("E2"), pos_of("E4")), AnnotationColor::Green)]),
+                selected_pos: None,
+            }
+        );
+        toggle_square_annotation(&mut selection, pos_of("E4"), AnnotationColor::Red);
+        assert_eq!(selection.selected_squares.get(&pos_of("E4")), None);
+        clear_annotations(&mut selection);
+        assert_eq!(
+            selection,
+            Selection {
+                selected_squares: HashMap::new(),
+                arrows: HashMap::new(),
+                selected_pos: None
+            }
         );
     }
 }
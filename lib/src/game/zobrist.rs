@@ -0,0 +1,231 @@
+use crate::{
+    color::Color,
+    game::{
+        board::GameBoard,
+        game::{GameHistory, GameStart},
+        mov::special::{
+            castling::{long_castling_right, short_castling_right},
+            en_passant::en_passant_target,
+        },
+        rule::turn::evaluate_turn,
+    },
+    piece::{Piece, PieceType},
+    pos::Pos,
+};
+
+pub type GameHash = u64;
+
+const PIECE_SEED: u64 = 0x0000_0000_0000_0000;
+const TURN_SEED: u64 = 0x0100_0000_0000_0000;
+const CASTLING_SEED: u64 = 0x0200_0000_0000_0000;
+const EN_PASSANT_SEED: u64 = 0x0300_0000_0000_0000;
+
+fn splitmix64(seed: u64) -> u64 {
+    let mut z = seed.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+fn piece_index(piece: &Piece) -> u64 {
+    let typ = match piece.typ {
+        PieceType::Rook => 0,
+        PieceType::Knight => 1,
+        PieceType::Bishop => 2,
+        PieceType::Queen => 3,
+        PieceType::King => 4,
+        PieceType::Pawn => 5,
+    };
+    match piece.color {
+        Color::White => typ,
+        Color::Black => typ + 6,
+    }
+}
+
+fn color_index(color: &Color) -> u64 {
+    match color {
+        Color::White => 0,
+        Color::Black => 1,
+    }
+}
+
+pub fn zobrist_piece(piece: &Piece, pos: &Pos) -> GameHash {
+    splitmix64(
+        PIECE_SEED | (piece_index(piece) << 16) | (u64::from(pos.row) << 8) | u64::from(pos.col),
+    )
+}
+
+pub fn zobrist_turn(color: &Color) -> GameHash {
+    match color {
+        Color::White => 0,
+        Color::Black => splitmix64(TURN_SEED),
+    }
+}
+
+pub fn zobrist_short_castling(color: &Color) -> GameHash {
+    splitmix64(CASTLING_SEED | (color_index(color) << 1))
+}
+
+pub fn zobrist_long_castling(color: &Color) -> GameHash {
+    splitmix64(CASTLING_SEED | (color_index(color) << 1) | 1)
+}
+
+pub fn zobrist_en_passant(col: u8) -> GameHash {
+    splitmix64(EN_PASSANT_SEED | u64::from(col))
+}
+
+pub fn zobrist_of_board(board: &GameBoard) -> GameHash {
+    board.iter().fold(0, |hash, (pos, piece)| hash ^ zobrist_piece(piece, pos))
+}
+
+pub fn zobrist_castling_of(
+    board: &GameBoard,
+    start: &GameStart,
+    history: &GameHistory,
+    color: &Color,
+) -> GameHash {
+    let mut hash = 0;
+    if short_castling_right(board, start, history, color) {
+        hash ^= zobrist_short_castling(color);
+    }
+    if long_castling_right(board, start, history, color) {
+        hash ^= zobrist_long_castling(color);
+    }
+    hash
+}
+
+// the en passant file only counts when a pawn of the side to move can capture on it
+pub fn zobrist_en_passant_of(
+    board: &GameBoard,
+    start: &GameStart,
+    history: &GameHistory,
+) -> GameHash {
+    let turn = evaluate_turn(start, history);
+    let (opponent, row) = match turn {
+        Color::White => (Color::Black, 4),
+        Color::Black => (Color::White, 3),
+    };
+    let Some(target) = en_passant_target(start, history, &opponent) else {
+        return 0;
+    };
+    let pawn = Piece { typ: PieceType::Pawn, color: turn };
+    let can_capture = [target.col.checked_sub(1), target.col.checked_add(1)]
+        .into_iter()
+        .flatten()
+        .any(|col| board.get(&Pos::of(row, col)) == Some(&pawn));
+    if can_capture { zobrist_en_passant(target.col) } else { 0 }
+}
+
+pub fn zobrist_of_state(board: &GameBoard, start: &GameStart, history: &GameHistory) -> GameHash {
+    zobrist_turn(&evaluate_turn(start, history))
+        ^ zobrist_castling_of(board, start, history, &Color::White)
+        ^ zobrist_castling_of(board, start, history, &Color::Black)
+        ^ zobrist_en_passant_of(board, start, history)
+}
+
+pub fn zobrist_of(board: &GameBoard, start: &GameStart, history: &GameHistory) -> GameHash {
+    zobrist_of_board(board) ^ zobrist_of_state(board, start, history)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        color::Color,
+        game::{board::board_of_str, game::GameStart, mode::standard_chess, mov::GameMove},
+        piece::Piece,
+        pos::pos_of,
+    };
+
+    use super::{
+        zobrist_en_passant, zobrist_long_castling, zobrist_of, zobrist_of_board, zobrist_of_state,
+        zobrist_piece, zobrist_short_castling, zobrist_turn,
+    };
+
+    #[test]
+    fn zobrist_keys_are_distinct() {
+        assert_ne!(
+            zobrist_piece(&Piece::of('♙'), &pos_of("E2")),
+            zobrist_piece(&Piece::of('♟'), &pos_of("E2"))
+        );
+        assert_ne!(
+            zobrist_piece(&Piece::of('♙'), &pos_of("E2")),
+            zobrist_piece(&Piece::of('♙'), &pos_of("B5"))
+        );
+        assert_ne!(zobrist_short_castling(&Color::White), zobrist_long_castling(&Color::White));
+        assert_ne!(zobrist_short_castling(&Color::White), zobrist_short_castling(&Color::Black));
+        assert_ne!(zobrist_en_passant(0), zobrist_en_passant(1));
+        assert_eq!(zobrist_turn(&Color::White), 0);
+        assert_ne!(zobrist_turn(&Color::Black), 0);
+    }
+
+    #[test]
+    fn zobrist_of_board_empty() {
+        assert_eq!(zobrist_of_board(&[].into()), 0);
+    }
+
+    #[test]
+    fn zobrist_of_turn() {
+        let mode = standard_chess();
+        let board = board_of_str(
+            &mode.bounds,
+            [
+                "    ♚   ",
+                "        ",
+                "        ",
+                "        ",
+                "        ",
+                "        ",
+                "        ",
+                "    ♔   ",
+            ],
+        );
+        let white_to_move = Vec::new();
+        let black_to_move = vec![GameMove::default_of('♔', "D1", "E1")];
+        assert_eq!(
+            zobrist_of(&board, &GameStart::default(), &white_to_move)
+                ^ zobrist_of(&board, &GameStart::default(), &black_to_move),
+            zobrist_turn(&Color::Black)
+        );
+    }
+
+    #[test]
+    fn zobrist_of_castling_rights() {
+        let mode = standard_chess();
+        let history = Vec::new();
+        assert_eq!(
+            zobrist_of_state(&mode.initial_board, &GameStart::default(), &history),
+            zobrist_short_castling(&Color::White)
+                ^ zobrist_long_castling(&Color::White)
+                ^ zobrist_short_castling(&Color::Black)
+                ^ zobrist_long_castling(&Color::Black)
+        );
+    }
+
+    #[test]
+    fn zobrist_of_en_passant() {
+        let mode = standard_chess();
+        let board = board_of_str(
+            &mode.bounds,
+            [
+                "    ♚   ",
+                "        ",
+                "        ",
+                "   ♟♙   ",
+                "        ",
+                "        ",
+                "        ",
+                "    ♔   ",
+            ],
+        );
+        let history = vec![
+            GameMove::default_of('♙', "E2", "E4"),
+            GameMove::default_of('♟', "H7", "H6"),
+            GameMove::default_of('♙', "E4", "E5"),
+            GameMove::default_of('♟', "D7", "D5"),
+        ];
+        assert_eq!(
+            zobrist_of_state(&board, &GameStart::default(), &history),
+            zobrist_en_passant(3)
+        );
+    }
+}
@@ -1,5 +1,12 @@
+pub mod book;
+pub mod clock;
 pub mod color;
+pub mod engine;
 pub mod game;
 pub mod mov;
 pub mod piece;
 pub mod pos;
+#[cfg(feature = "serde")]
+pub mod snapshot;
+pub mod tablebase;
+pub mod time;
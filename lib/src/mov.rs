@@ -4,9 +4,12 @@ use crate::{
 };
 
 #[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Mov {
     pub piece: Piece,
+    #[cfg_attr(feature = "serde", serde(with = "crate::snapshot::pos_serde"))]
     pub from: Pos,
+    #[cfg_attr(feature = "serde", serde(with = "crate::snapshot::pos_serde"))]
     pub to: Pos,
 }
 
@@ -3,6 +3,7 @@ use std::fmt;
 use crate::color::Color;
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum PieceType {
     Rook,
     Knight,
@@ -13,6 +14,7 @@ pub enum PieceType {
 }
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Piece {
     pub typ: PieceType,
     pub color: Color,
@@ -1,6 +1,6 @@
 use manfredo::matrix::point::point_u8::PointU8;
 
-use self::{
+pub use self::{
     col::{col_to_string, col_try_of},
     row::{row_to_string, row_try_of},
 };
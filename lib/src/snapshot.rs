@@ -0,0 +1,282 @@
+use manfredo::matrix::rect::rect_u8::contains;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    game::{
+        game::Game,
+        mov::{GameMoveType, promotion_of_game_move_type, promotion_piece_type},
+        rule::{move_piece::refresh_moves, turn::evaluate_turn},
+        zobrist::zobrist_of,
+    },
+    piece::PieceType,
+};
+
+pub const GAME_SCHEMA_VERSION: u16 = 1;
+
+// Pos is manfredo's PointU8, which cannot derive serde here, so fields of other crates can use
+// `#[serde(with = "libre_chess_lib::snapshot::pos_serde")]` to store it as (row, col)
+pub mod pos_serde {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    use crate::pos::Pos;
+
+    pub fn serialize<S: Serializer>(pos: &Pos, serializer: S) -> Result<S::Ok, S::Error> {
+        (pos.row, pos.col).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Pos, D::Error> {
+        let (row, col) = <(u8, u8)>::deserialize(deserializer)?;
+        Ok(Pos::of(row, col))
+    }
+}
+
+pub mod pos_option_serde {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    use crate::pos::Pos;
+
+    pub fn serialize<S: Serializer>(pos: &Option<Pos>, serializer: S) -> Result<S::Ok, S::Error> {
+        pos.as_ref().map(|pos| (pos.row, pos.col)).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Pos>, D::Error> {
+        let pos = Option::<(u8, u8)>::deserialize(deserializer)?;
+        Ok(pos.map(|(row, col)| Pos::of(row, col)))
+    }
+}
+
+pub(crate) mod bounds_serde {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    use crate::game::game::GameBounds;
+
+    pub fn serialize<S: Serializer>(bounds: &GameBounds, serializer: S) -> Result<S::Ok, S::Error> {
+        [bounds.min.row, bounds.min.col, bounds.max.row, bounds.max.col].serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<GameBounds, D::Error> {
+        let [min_row, min_col, max_row, max_col] = <[u8; 4]>::deserialize(deserializer)?;
+        Ok(GameBounds::of(min_row, min_col, max_row, max_col))
+    }
+}
+
+pub(crate) mod board_serde {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    use crate::{game::board::GameBoard, piece::Piece, pos::Pos};
+
+    pub fn serialize<S: Serializer>(board: &GameBoard, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut squares: Vec<((u8, u8), &Piece)> =
+            board.iter().map(|(pos, piece)| ((pos.row, pos.col), piece)).collect();
+        squares.sort_by_key(|(square, _)| *square);
+        squares.serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<GameBoard, D::Error> {
+        let squares = Vec::<((u8, u8), Piece)>::deserialize(deserializer)?;
+        Ok(squares.into_iter().map(|((row, col), piece)| (Pos::of(row, col), piece)).collect())
+    }
+}
+
+#[derive(Serialize)]
+struct GameDocumentRef<'a> {
+    version: u16,
+    game: &'a Game,
+}
+
+#[derive(Deserialize)]
+struct GameDocumentVersion {
+    version: u16,
+}
+
+#[derive(Deserialize)]
+struct GameDocument {
+    game: Game,
+}
+
+fn is_valid_document(game: &Game) -> bool {
+    let in_bounds = |pos| contains(&game.bounds, pos);
+    if !game.board.keys().all(in_bounds) || !game.start.en_passant.iter().all(in_bounds) {
+        return false;
+    }
+    let mut history = Vec::with_capacity(game.history.len());
+    for game_move in &game.history {
+        if game_move.mov.piece.color != evaluate_turn(&game.start, &history)
+            || !in_bounds(&game_move.mov.from)
+            || !in_bounds(&game_move.mov.to)
+        {
+            return false;
+        }
+        history.push(game_move.clone());
+    }
+    let Some(last) = game.history.last() else {
+        return true;
+    };
+    let piece = &last.mov.piece;
+    match &last.typ {
+        GameMoveType::ShortCastling | GameMoveType::LongCastling => {
+            game.board.iter().any(|(pos, p)| {
+                p.typ == PieceType::King && p.color == piece.color && pos.row == last.mov.from.row
+            })
+        }
+        typ => {
+            let typ = promotion_of_game_move_type(typ)
+                .and_then(|promotion| promotion_piece_type(&promotion))
+                .unwrap_or(piece.typ);
+            game.board.get(&last.mov.to).is_some_and(|p| p.typ == typ && p.color == piece.color)
+        }
+    }
+}
+
+fn game_of_document(mut game: Game) -> Option<Game> {
+    if !is_valid_document(&game) {
+        return None;
+    }
+    game.hash = zobrist_of(&game.board, &game.start, &game.history);
+    refresh_moves(&mut game);
+    Some(game)
+}
+
+pub fn game_to_json(game: &Game) -> Option<String> {
+    serde_json::to_string(&GameDocumentRef { version: GAME_SCHEMA_VERSION, game }).ok()
+}
+
+pub fn game_try_of_json(s: &str) -> Option<Game> {
+    let document = serde_json::from_str::<GameDocumentVersion>(s).ok()?;
+    if document.version != GAME_SCHEMA_VERSION {
+        return None;
+    }
+    let document = serde_json::from_str::<GameDocument>(s).ok()?;
+    game_of_document(document.game)
+}
+
+pub fn game_to_bytes(game: &Game) -> Option<Vec<u8>> {
+    postcard::to_allocvec(&GameDocumentRef { version: GAME_SCHEMA_VERSION, game }).ok()
+}
+
+pub fn game_try_of_bytes(bytes: &[u8]) -> Option<Game> {
+    let (version, rest) = postcard::take_from_bytes::<u16>(bytes).ok()?;
+    if version != GAME_SCHEMA_VERSION {
+        return None;
+    }
+    let (game, rest) = postcard::take_from_bytes::<Game>(rest).ok()?;
+    if !rest.is_empty() {
+        return None;
+    }
+    game_of_document(game)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        clock::{Clock, TimeControl},
+        color::Color,
+        game::{
+            event::{GameAction, play_action},
+            fen::game_of_fen,
+            mode::standard_chess,
+            mov::GameMove,
+            rule::{init::game_of_mode, move_piece::play_move},
+        },
+        piece::Piece,
+        pos::{Pos, pos_of},
+        time::ManualTimeSource,
+    };
+
+    use super::{game_to_bytes, game_to_json, game_try_of_bytes, game_try_of_json};
+
+    fn game_with_moves() -> crate::game::game::Game {
+        let mut game = game_of_mode(standard_chess());
+        for (from, to) in [("E2", "E4"), ("D7", "D5"), ("E4", "D5"), ("G8", "F6")] {
+            play_move(&mut game, &pos_of(from), &pos_of(to));
+        }
+        assert!(play_action(&mut game, &Color::White, GameAction::OfferDraw));
+        game
+    }
+
+    #[test]
+    fn game_json_round_trip() {
+        let game = game_with_moves();
+        let json = game_to_json(&game).unwrap();
+        assert!(json.starts_with("{\"version\":1,"));
+        assert_eq!(game_try_of_json(&json), Some(game));
+        let game = game_of_fen("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1");
+        assert_eq!(game_try_of_json(&game_to_json(&game).unwrap()), Some(game));
+    }
+
+    #[test]
+    fn game_json_is_deterministic() {
+        let game = game_with_moves();
+        assert_eq!(game_to_json(&game), game_to_json(&game.clone()));
+    }
+
+    #[test]
+    fn game_json_invalid() {
+        let json = game_to_json(&game_with_moves()).unwrap();
+        assert_eq!(game_try_of_json(&json.replacen("\"version\":1", "\"version\":2", 1)), None);
+        assert_eq!(game_try_of_json(&json[..json.len() - 1]), None);
+        assert_eq!(game_try_of_json(""), None);
+    }
+
+    #[test]
+    fn game_json_inconsistent() {
+        let mut game = game_with_moves();
+        game.board.insert(Pos::of(8, 0), Piece::of('♛'));
+        assert_eq!(game_try_of_json(&game_to_json(&game).unwrap()), None);
+
+        let mut game = game_with_moves();
+        game.board.remove(&pos_of("F6"));
+        assert_eq!(game_try_of_json(&game_to_json(&game).unwrap()), None);
+
+        let mut game = game_with_moves();
+        game.history.push(GameMove::default_of('♟', "A7", "A6"));
+        assert_eq!(game_try_of_bytes(&game_to_bytes(&game).unwrap()), None);
+    }
+
+    #[test]
+    fn pos_serde_of() {
+        #[derive(serde::Serialize, serde::Deserialize, Debug, PartialEq)]
+        struct Square {
+            #[serde(with = "crate::snapshot::pos_serde")]
+            pos: Pos,
+        }
+        let square = Square { pos: pos_of("E4") };
+        let json = serde_json::to_string(&square).unwrap();
+        assert_eq!(json, "{\"pos\":[3,4]}");
+        assert_eq!(serde_json::from_str::<Square>(&json).unwrap(), square);
+    }
+
+    #[test]
+    fn game_bytes_round_trip() {
+        let game = game_with_moves();
+        let bytes = game_to_bytes(&game).unwrap();
+        assert_eq!(bytes[0], 1);
+        assert!(bytes.len() < game_to_json(&game).unwrap().len());
+        assert_eq!(game_try_of_bytes(&bytes), Some(game));
+    }
+
+    #[test]
+    fn game_bytes_invalid() {
+        let mut bytes = game_to_bytes(&game_with_moves()).unwrap();
+        assert_eq!(game_try_of_bytes(&bytes[..bytes.len() - 1]), None);
+        bytes.push(0);
+        assert_eq!(game_try_of_bytes(&bytes), None);
+        bytes[0] = 2;
+        assert_eq!(game_try_of_bytes(&bytes), None);
+        assert_eq!(game_try_of_bytes(&[]), None);
+    }
+
+    #[test]
+    fn clock_json_round_trip() {
+        let time_source = ManualTimeSource::of(0);
+        let mut clock = Clock::of(TimeControl::of("40/5400+30:1800+30"));
+        clock.start(&Color::White, &time_source);
+        time_source.advance(1_000);
+        clock.press(&time_source);
+        let json = serde_json::to_string(&clock).unwrap();
+        assert_eq!(serde_json::from_str::<Clock>(&json).unwrap(), clock);
+        assert!(serde_json::from_str::<TimeControl>("{\"stages\":[]}").is_err());
+    }
+}
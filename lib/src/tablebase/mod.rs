@@ -0,0 +1,2 @@
+pub mod syzygy;
+mod table;
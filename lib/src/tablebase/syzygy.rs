@@ -0,0 +1,557 @@
+use std::{
+    collections::HashMap,
+    fs,
+    ops::Neg,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+use crate::{
+    color::Color,
+    engine::{
+        SearchMove,
+        search::{game_after, legal_moves_of},
+    },
+    game::{
+        board::GameBoard,
+        game::{Game, GameBounds},
+        mov::special::castling::{long_castling_right, short_castling_right},
+        rule::{check::is_in_check, turn::evaluate_turn},
+    },
+    piece::{Piece, PieceType},
+};
+
+use super::table::{BLACK, KING, MAX_PIECES, Table, TableKind, TableValue};
+
+const PIECE_CHARS: [(PieceType, char); 6] = [
+    (PieceType::King, 'K'),
+    (PieceType::Queen, 'Q'),
+    (PieceType::Rook, 'R'),
+    (PieceType::Bishop, 'B'),
+    (PieceType::Knight, 'N'),
+    (PieceType::Pawn, 'P'),
+];
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+pub enum Wdl {
+    Loss,
+    BlessedLoss,
+    Draw,
+    CursedWin,
+    Win,
+}
+
+impl Wdl {
+    fn try_of(value: i32) -> Option<Self> {
+        match value {
+            -2 => Some(Wdl::Loss),
+            -1 => Some(Wdl::BlessedLoss),
+            0 => Some(Wdl::Draw),
+            1 => Some(Wdl::CursedWin),
+            2 => Some(Wdl::Win),
+            _ => None,
+        }
+    }
+
+    pub fn value(&self) -> i32 {
+        match self {
+            Wdl::Loss => -2,
+            Wdl::BlessedLoss => -1,
+            Wdl::Draw => 0,
+            Wdl::CursedWin => 1,
+            Wdl::Win => 2,
+        }
+    }
+}
+
+impl Neg for Wdl {
+    type Output = Wdl;
+
+    fn neg(self) -> Wdl {
+        match self {
+            Wdl::Loss => Wdl::Win,
+            Wdl::BlessedLoss => Wdl::CursedWin,
+            Wdl::Draw => Wdl::Draw,
+            Wdl::CursedWin => Wdl::BlessedLoss,
+            Wdl::Win => Wdl::Loss,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct TablebaseProbe {
+    pub wdl: Wdl,
+    pub dtz: Option<i32>,
+}
+
+fn piece_code(piece: &Piece) -> u8 {
+    let typ = match piece.typ {
+        PieceType::Pawn => 1,
+        PieceType::Knight => 2,
+        PieceType::Bishop => 3,
+        PieceType::Rook => 4,
+        PieceType::Queen => 5,
+        PieceType::King => 6,
+    };
+    match piece.color {
+        Color::White => typ,
+        Color::Black => typ | BLACK,
+    }
+}
+
+fn material_of(board: &GameBoard, color: &Color) -> String {
+    PIECE_CHARS
+        .iter()
+        .flat_map(|(typ, c)| {
+            let count = board.values().filter(|piece| piece.typ == *typ && piece.color == *color);
+            count.map(|_| *c)
+        })
+        .collect()
+}
+
+fn side_try_of(s: &str) -> Option<Vec<u8>> {
+    let pieces = s
+        .chars()
+        .map(|c| {
+            let (typ, _) = PIECE_CHARS.iter().find(|(_, piece_char)| *piece_char == c)?;
+            Some(piece_code(&Piece { typ: *typ, color: Color::White }))
+        })
+        .collect::<Option<Vec<u8>>>()?;
+    if pieces.iter().filter(|piece| **piece == KING).count() != 1 {
+        return None;
+    }
+    Some(pieces)
+}
+
+pub fn material_try_of(name: &str) -> Option<(Vec<u8>, Vec<u8>)> {
+    let (white, black) = name.split_once('v')?;
+    Some((side_try_of(white)?, side_try_of(black)?))
+}
+
+fn child_of(game: &Game, mv: &SearchMove) -> Option<Game> {
+    let child = game_after(game, mv);
+    (evaluate_turn(&child.start, &child.history) != evaluate_turn(&game.start, &game.history))
+        .then_some(child)
+}
+
+fn is_zeroing(game: &Game, mv: &SearchMove) -> bool {
+    mv.is_capture(&game.board)
+        || game.board.get(&mv.from).is_some_and(|piece| piece.typ == PieceType::Pawn)
+}
+
+fn is_mate(game: &Game) -> bool {
+    legal_moves_of(game).is_empty()
+        && is_in_check(&game.board, &game.players, &game.start, &game.history)
+}
+
+fn dtz_before_zeroing(wdl: Wdl) -> i32 {
+    match wdl {
+        Wdl::Win => 1,
+        Wdl::CursedWin => 101,
+        Wdl::Draw => 0,
+        Wdl::BlessedLoss => -101,
+        Wdl::Loss => -1,
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Tablebase {
+    files: HashMap<String, PathBuf>,
+    max_pieces: usize,
+    tables: Mutex<HashMap<String, Option<Arc<Table>>>>,
+}
+
+impl Tablebase {
+    pub fn add_directory(&mut self, dir: &Path) -> Option<usize> {
+        let mut added = 0;
+        for entry in fs::read_dir(dir).ok()?.flatten() {
+            let path = entry.path();
+            let Some(name) = path.file_name().and_then(|name| name.to_str()) else {
+                continue;
+            };
+            let Some((stem, extension)) = name.rsplit_once('.') else {
+                continue;
+            };
+            if extension != TableKind::Wdl.extension() && extension != TableKind::Dtz.extension() {
+                continue;
+            }
+            let Some((white, black)) = material_try_of(stem) else {
+                continue;
+            };
+            let pieces = white.len() + black.len();
+            if pieces > MAX_PIECES {
+                continue;
+            }
+            self.max_pieces = self.max_pieces.max(pieces);
+            self.files.insert(name.into(), path);
+            added += 1;
+        }
+        Some(added)
+    }
+
+    pub fn len(&self) -> usize {
+        self.files.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    pub fn max_pieces(&self) -> usize {
+        self.max_pieces
+    }
+
+    pub fn is_probeable(&self, game: &Game) -> bool {
+        game.board.len() <= self.max_pieces
+            && game.bounds == GameBounds::of(0, 0, 7, 7)
+            && [Color::White, Color::Black].iter().all(|color| {
+                game.board
+                    .values()
+                    .filter(|piece| piece.typ == PieceType::King && piece.color == *color)
+                    .count()
+                    == 1
+            })
+            && game
+                .board
+                .iter()
+                .all(|(pos, piece)| piece.typ != PieceType::Pawn || (1..7).contains(&pos.row))
+            && [Color::White, Color::Black].iter().all(|color| {
+                !short_castling_right(&game.board, &game.start, &game.history, color)
+                    && !long_castling_right(&game.board, &game.start, &game.history, color)
+            })
+    }
+
+    fn table(&self, kind: TableKind, name: &str) -> Option<Arc<Table>> {
+        let file = format!("{name}.{}", kind.extension());
+        let path = self.files.get(&file)?;
+        let mut tables = self.tables.lock().unwrap();
+        tables
+            .entry(file)
+            .or_insert_with(|| {
+                let (white, black) = material_try_of(name)?;
+                let bytes = fs::read(path).ok()?;
+                Table::try_of(kind, &white, &black, bytes).map(Arc::new)
+            })
+            .clone()
+    }
+
+    fn probe_table(&self, game: &Game, kind: TableKind, wdl: Wdl) -> Option<TableValue> {
+        if game.board.len() == 2 {
+            return Some(TableValue::Value(0));
+        }
+        let white = material_of(&game.board, &Color::White);
+        let black = material_of(&game.board, &Color::Black);
+        let (table, black_stronger) = match self.table(kind, &format!("{white}v{black}")) {
+            Some(table) => (table, false),
+            None => (self.table(kind, &format!("{black}v{white}"))?, true),
+        };
+        let pieces: Vec<(usize, u8)> = game
+            .board
+            .iter()
+            .map(|(pos, piece)| {
+                (usize::from(pos.row) * 8 + usize::from(pos.col), piece_code(piece))
+            })
+            .collect();
+        let white_to_move = evaluate_turn(&game.start, &game.history) == Color::White;
+        table.probe(&pieces, white_to_move, black_stronger, wdl.value())
+    }
+
+    // the tables do not store en passant nor positions where a capture is the best move, so
+    // captures are searched before trusting the table
+    fn search(&self, game: &Game, check_zeroing: bool) -> Option<(Wdl, bool)> {
+        let moves = legal_moves_of(game);
+        let mut best = Wdl::Loss;
+        let mut move_count = 0;
+        for mv in &moves {
+            let is_pawn_move =
+                game.board.get(&mv.from).is_some_and(|piece| piece.typ == PieceType::Pawn);
+            if !(mv.is_capture(&game.board) || (check_zeroing && is_pawn_move)) {
+                continue;
+            }
+            move_count += 1;
+            let value = -self.search(&child_of(game, mv)?, false)?.0;
+            if value > best {
+                best = value;
+                if value == Wdl::Win {
+                    return Some((value, true));
+                }
+            }
+        }
+        let no_more_moves = move_count > 0 && move_count == moves.len();
+        let value = if no_more_moves {
+            best
+        } else {
+            match self.probe_table(game, TableKind::Wdl, Wdl::Draw)? {
+                TableValue::Value(value) => Wdl::try_of(value)?,
+                TableValue::ChangeStm => return None,
+            }
+        };
+        if best >= value {
+            return Some((best, best > Wdl::Draw || no_more_moves));
+        }
+        Some((value, false))
+    }
+
+    fn dtz(&self, game: &Game) -> Option<i32> {
+        let (wdl, zeroing_best_move) = self.search(game, true)?;
+        if wdl == Wdl::Draw {
+            return Some(0);
+        }
+        if zeroing_best_move {
+            return Some(dtz_before_zeroing(wdl));
+        }
+        match self.probe_table(game, TableKind::Dtz, wdl)? {
+            TableValue::Value(dtz) => {
+                let cursed = if matches!(wdl, Wdl::CursedWin | Wdl::BlessedLoss) { 100 } else { 0 };
+                Some((dtz + cursed) * wdl.value().signum())
+            }
+            TableValue::ChangeStm => {
+                let mut min_dtz = i32::MAX;
+                for mv in legal_moves_of(game) {
+                    let zeroing = is_zeroing(game, &mv);
+                    let child = child_of(game, &mv)?;
+                    let mut dtz = if zeroing {
+                        -dtz_before_zeroing(self.search(&child, false)?.0)
+                    } else {
+                        -self.dtz(&child)?
+                    };
+                    if dtz == 1 && is_mate(&child) {
+                        min_dtz = 1;
+                    }
+                    if !zeroing {
+                        dtz += dtz.signum();
+                    }
+                    if dtz < min_dtz && dtz.signum() == wdl.value().signum() {
+                        min_dtz = dtz;
+                    }
+                }
+                Some(if min_dtz == i32::MAX { -1 } else { min_dtz })
+            }
+        }
+    }
+
+    pub fn probe_wdl(&self, game: &Game) -> Option<Wdl> {
+        if !self.is_probeable(game) {
+            return None;
+        }
+        self.search(game, false).map(|(wdl, _)| wdl)
+    }
+
+    pub fn probe_dtz(&self, game: &Game) -> Option<i32> {
+        if !self.is_probeable(game) {
+            return None;
+        }
+        self.dtz(game)
+    }
+
+    pub fn best_move(&self, game: &Game) -> Option<(SearchMove, Wdl)> {
+        let wdl = self.probe_wdl(game)?;
+        let mut best: Option<((u8, i32), SearchMove)> = None;
+        for mv in legal_moves_of(game) {
+            let child = child_of(game, &mv)?;
+            let mut dtz = if is_zeroing(game, &mv) {
+                dtz_before_zeroing(-self.search(&child, false)?.0)
+            } else {
+                let dtz = -self.dtz(&child)?;
+                dtz + dtz.signum()
+            };
+            if dtz == 2 && is_mate(&child) {
+                dtz = 1;
+            }
+            let rank = match dtz.signum() {
+                1 => (2, -dtz),
+                0 => (1, 0),
+                _ => (0, -dtz),
+            };
+            if best.as_ref().is_none_or(|(best_rank, _)| rank > *best_rank) {
+                best = Some((rank, mv));
+            }
+        }
+        best.map(|(_, mv)| (mv, wdl))
+    }
+}
+
+impl Game {
+    pub fn probe(&self, tablebase: &Tablebase) -> Option<TablebaseProbe> {
+        let wdl = tablebase.probe_wdl(self)?;
+        Some(TablebaseProbe { wdl, dtz: tablebase.probe_dtz(self) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        fs,
+        path::{Path, PathBuf},
+    };
+
+    use std::sync::atomic::AtomicBool;
+
+    use crate::{
+        engine::search::{Engine, MATE, SearchLimits, TB_WIN},
+        game::{fen::game_of_fen, rule::move_piece::play_move},
+        pos::pos_of,
+        tablebase::table::{KING, TableKind, single_value_table_bytes},
+        time::SystemTimeSource,
+    };
+
+    use super::{Tablebase, TablebaseProbe, Wdl, material_try_of};
+
+    const ROOK: u8 = 4;
+
+    fn tablebase_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("libre_chess_syzygy_{name}"));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let wdl = single_value_table_bytes(TableKind::Wdl, &[KING, ROOK], &[KING], &[4, 0]);
+        let dtz = single_value_table_bytes(TableKind::Dtz, &[KING, ROOK], &[KING], &[5]);
+        fs::write(dir.join("KRvK.rtbw"), wdl).unwrap();
+        fs::write(dir.join("KRvK.rtbz"), dtz).unwrap();
+        fs::write(dir.join("KRvK.txt"), "").unwrap();
+        fs::write(dir.join("KXvK.rtbw"), "").unwrap();
+        dir
+    }
+
+    fn tablebase_of(name: &str) -> Tablebase {
+        let mut tablebase = Tablebase::default();
+        assert_eq!(tablebase.add_directory(&tablebase_dir(name)), Some(2));
+        tablebase
+    }
+
+    fn fixture_tablebase() -> Tablebase {
+        let mut tablebase = Tablebase::default();
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures/syzygy");
+        assert_eq!(tablebase.add_directory(&dir), Some(4));
+        tablebase
+    }
+
+    #[test]
+    fn material_try_of_names() {
+        assert_eq!(material_try_of("KRvK"), Some((vec![6, 4], vec![6])));
+        assert_eq!(material_try_of("KQPvKR"), Some((vec![6, 5, 1], vec![6, 4])));
+        assert_eq!(material_try_of("KRK"), None);
+        assert_eq!(material_try_of("RvK"), None);
+        assert_eq!(material_try_of("KKvK"), None);
+        assert_eq!(material_try_of("KXvK"), None);
+    }
+
+    #[test]
+    fn wdl_neg() {
+        assert_eq!(-Wdl::Win, Wdl::Loss);
+        assert_eq!(-Wdl::CursedWin, Wdl::BlessedLoss);
+        assert_eq!(-Wdl::Draw, Wdl::Draw);
+        assert!(Wdl::Win > Wdl::CursedWin);
+    }
+
+    #[test]
+    fn tablebase_add_directory() {
+        let tablebase = tablebase_of("add_directory");
+        assert_eq!(tablebase.len(), 2);
+        assert_eq!(tablebase.max_pieces(), 3);
+        assert_eq!(Tablebase::default().add_directory(&PathBuf::from("/nonexistent")), None);
+    }
+
+    #[test]
+    fn game_probe() {
+        let tablebase = tablebase_of("probe");
+        assert_eq!(
+            game_of_fen("7R/8/8/4k3/8/8/8/K7 w - - 0 1").probe(&tablebase),
+            Some(TablebaseProbe { wdl: Wdl::Win, dtz: Some(11) })
+        );
+        assert_eq!(
+            game_of_fen("7R/8/8/4k3/8/8/8/K7 b - - 0 1").probe(&tablebase),
+            Some(TablebaseProbe { wdl: Wdl::Loss, dtz: Some(-12) })
+        );
+        assert_eq!(
+            game_of_fen("k7/8/8/8/4K3/8/8/7r b - - 0 1").probe(&tablebase),
+            Some(TablebaseProbe { wdl: Wdl::Win, dtz: Some(11) })
+        );
+        assert_eq!(
+            game_of_fen("7R/6k1/8/8/8/8/8/K7 b - - 0 1").probe(&tablebase),
+            Some(TablebaseProbe { wdl: Wdl::Draw, dtz: Some(0) })
+        );
+        assert_eq!(
+            game_of_fen("8/8/8/4k3/8/8/8/K7 w - - 0 1").probe(&tablebase),
+            Some(TablebaseProbe { wdl: Wdl::Draw, dtz: Some(0) })
+        );
+        assert_eq!(game_of_fen("7Q/8/8/4k3/8/8/8/K7 w - - 0 1").probe(&tablebase), None);
+        assert_eq!(game_of_fen("7R/8/8/4k3/8/8/8/K6R w - - 0 1").probe(&tablebase), None);
+        assert_eq!(game_of_fen("7R/8/8/4k3/8/8/8/K7 w - - 0 1").probe(&Tablebase::default()), None);
+    }
+
+    #[test]
+    fn game_probe_fixtures() {
+        let tablebase = fixture_tablebase();
+        for (fen, wdl, dtz) in [
+            ("7K/6Q1/8/8/2k5/8/8/8 w - - 0 1", Wdl::Win, 19),
+            ("7K/6Q1/8/3k4/8/8/8/8 b - - 0 1", Wdl::Loss, -20),
+            ("7K/6R1/5k2/8/8/8/8/8 w - - 0 1", Wdl::Win, 31),
+            ("7K/5kR1/8/8/8/8/8/8 b - - 0 1", Wdl::Loss, -32),
+            ("8/8/8/8/3k4/8/1R6/K7 w - - 0 1", Wdl::Win, 31),
+            ("8/8/8/8/3k4/8/1R6/K7 b - - 0 1", Wdl::Loss, -32),
+            ("8/8/8/8/8/2K5/2R5/k7 b - - 0 1", Wdl::Loss, -4),
+            ("8/8/8/8/8/2K5/1Q6/k7 b - - 0 1", Wdl::Loss, -1),
+            ("8/8/8/8/8/2K5/1R6/k7 b - - 0 1", Wdl::Draw, 0),
+            ("8/8/8/8/8/2K5/2Q5/k7 b - - 0 1", Wdl::Draw, 0),
+            ("8/8/8/3Rk3/8/8/8/K7 b - - 0 1", Wdl::Draw, 0),
+            ("k7/8/8/8/4K3/8/8/7q b - - 0 1", Wdl::Win, 7),
+        ] {
+            assert_eq!(
+                game_of_fen(fen).probe(&tablebase),
+                Some(TablebaseProbe { wdl, dtz: Some(dtz) }),
+                "{fen}"
+            );
+        }
+    }
+
+    #[test]
+    fn tablebase_dtz_of_mate_search() {
+        let tablebase = fixture_tablebase();
+        for fen in ["8/7k/5K2/8/8/8/8/Q7 w - - 0 1", "8/7k/5K2/8/8/8/8/R7 w - - 0 1"] {
+            let game = game_of_fen(fen);
+            let result = Engine::of(1 << 12).search(
+                &game,
+                &SearchLimits { mate: Some(2), ..SearchLimits::default() },
+                &SystemTimeSource::new(),
+                &AtomicBool::new(false),
+                &mut |_| {},
+            );
+            assert_eq!(result.score, MATE - 3);
+            assert_eq!(tablebase.probe_dtz(&game), Some(3));
+        }
+    }
+
+    #[test]
+    fn tablebase_best_move_fixtures() {
+        let tablebase = fixture_tablebase();
+        let mut game = game_of_fen("8/8/8/8/3k4/8/1R6/K7 w - - 0 1");
+        let (mv, wdl) = tablebase.best_move(&game).unwrap();
+        assert_eq!(wdl, Wdl::Win);
+        play_move(&mut game, &mv.from, &mv.to);
+        assert_eq!(tablebase.probe_dtz(&game), Some(-30));
+    }
+
+    #[test]
+    fn tablebase_best_move() {
+        let tablebase = tablebase_of("best_move");
+        let (mv, wdl) = tablebase.best_move(&game_of_fen("7R/6k1/8/8/8/8/8/K7 b - - 0 1")).unwrap();
+        assert_eq!((mv.from, mv.to, wdl), (pos_of("G7"), pos_of("H8"), Wdl::Draw));
+        let (_, wdl) = tablebase.best_move(&game_of_fen("7R/8/8/4k3/8/8/8/K7 w - - 0 1")).unwrap();
+        assert_eq!(wdl, Wdl::Win);
+    }
+
+    #[test]
+    fn engine_search_tablebase() {
+        let mut engine = Engine::of(1024);
+        engine.tablebase = tablebase_of("engine_search");
+        let result = engine.search(
+            &game_of_fen("7R/6k1/8/8/8/8/8/K7 w - - 0 1"),
+            &SearchLimits { depth: Some(2), ..SearchLimits::default() },
+            &SystemTimeSource::new(),
+            &AtomicBool::new(false),
+            &mut |_| {},
+        );
+        assert_eq!(result.score, TB_WIN);
+        assert_eq!(result.best_move.unwrap().mov.from, pos_of("H8"));
+    }
+}
@@ -0,0 +1,881 @@
+use std::sync::LazyLock;
+
+pub const MAX_PIECES: usize = 7;
+
+const WDL_MAGIC: [u8; 4] = [0x71, 0xE8, 0x23, 0x5D];
+const DTZ_MAGIC: [u8; 4] = [0xD7, 0x66, 0x0C, 0xA5];
+
+const FLAG_STM: u8 = 1;
+const FLAG_MAPPED: u8 = 2;
+const FLAG_WIN_PLIES: u8 = 4;
+const FLAG_LOSS_PLIES: u8 = 8;
+const FLAG_WIDE: u8 = 16;
+const FLAG_SINGLE_VALUE: u8 = 128;
+
+const HEADER_SPLIT: u8 = 1;
+const HEADER_HAS_PAWNS: u8 = 2;
+
+const SPARSE_ENTRY_SIZE: usize = 6;
+const NO_ORDER: u8 = 0xF;
+const NO_SYMBOL: usize = 0xFFF;
+
+// index of the DTZ value maps by WDL + 2: Loss, BlessedLoss, Draw, CursedWin, Win
+const WDL_MAP: [usize; 5] = [1, 3, 0, 2, 0];
+
+pub const PAWN: u8 = 1;
+pub const KING: u8 = 6;
+pub const BLACK: u8 = 8;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum TableKind {
+    Wdl,
+    Dtz,
+}
+
+impl TableKind {
+    fn magic(&self) -> [u8; 4] {
+        match self {
+            TableKind::Wdl => WDL_MAGIC,
+            TableKind::Dtz => DTZ_MAGIC,
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            TableKind::Wdl => "rtbw",
+            TableKind::Dtz => "rtbz",
+        }
+    }
+}
+
+struct Consts {
+    map_pawns: [usize; 64],
+    map_b1h1h7: [u64; 64],
+    map_a1d1d4: [usize; 64],
+    map_kk: [[u64; 64]; 10],
+    binomial: [[u64; 64]; MAX_PIECES],
+    lead_pawn_idx: [[u64; 64]; 6],
+    lead_pawns_size: [[u64; 4]; 6],
+    kk_len: u64,
+}
+
+fn rank_of(sq: usize) -> usize {
+    sq >> 3
+}
+
+fn file_of(sq: usize) -> usize {
+    sq & 7
+}
+
+fn off_a1h8(sq: usize) -> i32 {
+    rank_of(sq) as i32 - file_of(sq) as i32
+}
+
+fn in_a1d1d4(sq: usize) -> bool {
+    file_of(sq) <= 3 && off_a1h8(sq) <= 0
+}
+
+static CONSTS: LazyLock<Consts> = LazyLock::new(|| {
+    let mut map_b1h1h7 = [0; 64];
+    for (code, sq) in (0..64).filter(|sq| off_a1h8(*sq) < 0).enumerate() {
+        map_b1h1h7[sq] = code as u64;
+    }
+
+    let mut map_a1d1d4 = [0; 64];
+    let mut code = 0;
+    let mut diagonal = Vec::new();
+    for sq in (0..64).filter(|sq| in_a1d1d4(*sq)) {
+        if off_a1h8(sq) < 0 {
+            map_a1d1d4[sq] = code;
+            code += 1;
+        } else {
+            diagonal.push(sq);
+        }
+    }
+    for sq in diagonal {
+        map_a1d1d4[sq] = code;
+        code += 1;
+    }
+
+    let mut map_kk = [[0; 64]; 10];
+    let mut both_on_diagonal = Vec::new();
+    let mut code = 0;
+    for (idx, kk) in map_kk.iter_mut().enumerate() {
+        for s1 in (0..64).filter(|sq| in_a1d1d4(*sq) && map_a1d1d4[*sq] == idx) {
+            for (s2, kk_code) in kk.iter_mut().enumerate() {
+                if rank_of(s1).abs_diff(rank_of(s2)) <= 1 && file_of(s1).abs_diff(file_of(s2)) <= 1
+                {
+                    continue;
+                }
+                if off_a1h8(s1) == 0 && off_a1h8(s2) > 0 {
+                    continue;
+                }
+                if off_a1h8(s1) == 0 && off_a1h8(s2) == 0 {
+                    both_on_diagonal.push((idx, s2));
+                } else {
+                    *kk_code = code;
+                    code += 1;
+                }
+            }
+        }
+    }
+    for (idx, s2) in both_on_diagonal {
+        map_kk[idx][s2] = code;
+        code += 1;
+    }
+    let kk_len = code;
+
+    let mut binomial = [[0; 64]; MAX_PIECES];
+    binomial[0][0] = 1;
+    for n in 1..64 {
+        for k in 0..MAX_PIECES.min(n + 1) {
+            binomial[k][n] = if k > 0 { binomial[k - 1][n - 1] } else { 0 }
+                + if k < n { binomial[k][n - 1] } else { 0 };
+        }
+    }
+
+    let mut map_pawns = [0; 64];
+    let mut lead_pawn_idx = [[0; 64]; 6];
+    let mut lead_pawns_size = [[0; 4]; 6];
+    let mut available: usize = 47;
+    for lead_pawns in 1..6 {
+        for (file, size) in lead_pawns_size[lead_pawns].iter_mut().enumerate() {
+            let mut idx = 0;
+            for rank in 1..7 {
+                let sq = rank * 8 + file;
+                if lead_pawns == 1 {
+                    map_pawns[sq] = available;
+                    map_pawns[sq ^ 7] = available - 1;
+                    available = available.saturating_sub(2);
+                }
+                lead_pawn_idx[lead_pawns][sq] = idx;
+                idx += binomial[lead_pawns - 1][map_pawns[sq]];
+            }
+            *size = idx;
+        }
+    }
+
+    Consts {
+        map_pawns,
+        map_b1h1h7,
+        map_a1d1d4,
+        map_kk,
+        binomial,
+        lead_pawn_idx,
+        lead_pawns_size,
+        kk_len,
+    }
+});
+
+fn binomial(k: usize, n: usize) -> u64 {
+    CONSTS.binomial.get(k).and_then(|row| row.get(n)).copied().unwrap_or(0)
+}
+
+fn u16_le(bytes: &[u8], at: usize) -> Option<u16> {
+    Some(u16::from_le_bytes(bytes.get(at..at + 2)?.try_into().ok()?))
+}
+
+fn u32_le(bytes: &[u8], at: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(bytes.get(at..at + 4)?.try_into().ok()?))
+}
+
+fn u32_be(bytes: &[u8], at: usize) -> Option<u32> {
+    Some(u32::from_be_bytes(bytes.get(at..at + 4)?.try_into().ok()?))
+}
+
+fn u64_be(bytes: &[u8], at: usize) -> Option<u64> {
+    Some(u64::from_be_bytes(bytes.get(at..at + 8)?.try_into().ok()?))
+}
+
+#[derive(Debug, Clone, Default)]
+struct PairsData {
+    flags: u8,
+    pieces: [u8; MAX_PIECES],
+    group_idx: [u64; MAX_PIECES + 1],
+    group_len: [usize; MAX_PIECES + 1],
+    map_idx: [usize; 4],
+    block_size: usize,
+    span: u64,
+    num_blocks: usize,
+    block_length_size: usize,
+    sparse_index_size: usize,
+    min_sym_len: u8,
+    base64: Vec<u64>,
+    symlen: Vec<u16>,
+    lowest_sym: usize,
+    btree: usize,
+    sparse_index: usize,
+    block_length: usize,
+    data: usize,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum TableValue {
+    Value(i32),
+    ChangeStm,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum Encoding {
+    Index { side: usize, file: usize, idx: u64 },
+    ChangeStm,
+}
+
+#[derive(Debug)]
+pub struct Table {
+    kind: TableKind,
+    bytes: Vec<u8>,
+    symmetric: bool,
+    has_pawns: bool,
+    has_unique_pieces: bool,
+    piece_count: usize,
+    pawn_count: [usize; 2],
+    items: Vec<Vec<PairsData>>,
+    map: usize,
+}
+
+impl Table {
+    // white and black are the piece types of each side of the table name, from Pawn 1 to King 6
+    pub fn try_of(kind: TableKind, white: &[u8], black: &[u8], bytes: Vec<u8>) -> Option<Self> {
+        if bytes.len() % 64 != 16 || bytes.get(0..4)? != kind.magic() {
+            return None;
+        }
+        let piece_count = white.len() + black.len();
+        if !(3..=MAX_PIECES).contains(&piece_count) {
+            return None;
+        }
+        let count = |pieces: &[u8], typ: u8| pieces.iter().filter(|piece| **piece == typ).count();
+        let white_pawns = count(white, PAWN);
+        let black_pawns = count(black, PAWN);
+        let has_pawns = white_pawns + black_pawns > 0;
+        let has_unique_pieces =
+            (PAWN..KING).any(|typ| count(white, typ) == 1 || count(black, typ) == 1);
+        let lead_white = black_pawns == 0 || (white_pawns > 0 && black_pawns >= white_pawns);
+        let pawn_count =
+            if lead_white { [white_pawns, black_pawns] } else { [black_pawns, white_pawns] };
+        let mut sorted_white = white.to_vec();
+        let mut sorted_black = black.to_vec();
+        sorted_white.sort();
+        sorted_black.sort();
+        let symmetric = sorted_white == sorted_black;
+
+        let header = *bytes.get(4)?;
+        if (header & HEADER_HAS_PAWNS != 0) != has_pawns {
+            return None;
+        }
+        if kind == TableKind::Wdl && (header & HEADER_SPLIT != 0) == symmetric {
+            return None;
+        }
+        let sides = if kind == TableKind::Wdl && !symmetric { 2 } else { 1 };
+        let files = if has_pawns { 4 } else { 1 };
+        let both_pawns = has_pawns && pawn_count[1] > 0;
+
+        let mut table = Table {
+            kind,
+            bytes,
+            symmetric,
+            has_pawns,
+            has_unique_pieces,
+            piece_count,
+            pawn_count,
+            items: vec![vec![PairsData::default(); files]; sides],
+            map: 0,
+        };
+
+        let mut at = 5;
+        for file in 0..files {
+            let first = *table.bytes.get(at)?;
+            let second = if both_pawns { *table.bytes.get(at + 1)? } else { 0xFF };
+            let orders = [[first & 0xF, second & 0xF], [first >> 4, second >> 4]];
+            at += 1 + usize::from(both_pawns);
+            for k in 0..piece_count {
+                let piece = *table.bytes.get(at)?;
+                for (side, item) in table.items.iter_mut().enumerate() {
+                    item[file].pieces[k] = if side == 1 { piece >> 4 } else { piece & 0xF };
+                }
+                at += 1;
+            }
+            for (side, order) in orders.into_iter().enumerate().take(sides) {
+                table.set_groups(side, file, order)?;
+            }
+        }
+        at += at & 1;
+
+        for file in 0..files {
+            for side in 0..sides {
+                at = table.set_sizes(side, file, at)?;
+            }
+        }
+        if kind == TableKind::Dtz {
+            at = table.set_dtz_map(at)?;
+        }
+        for file in 0..files {
+            for item in table.items.iter_mut() {
+                item[file].sparse_index = at;
+                at += item[file].sparse_index_size * SPARSE_ENTRY_SIZE;
+            }
+        }
+        for file in 0..files {
+            for item in table.items.iter_mut() {
+                item[file].block_length = at;
+                at += item[file].block_length_size * 2;
+            }
+        }
+        for file in 0..files {
+            for item in table.items.iter_mut() {
+                at = (at + 0x3F) & !0x3F;
+                item[file].data = at;
+                at += item[file].num_blocks * item[file].block_size;
+            }
+        }
+        if at > table.bytes.len() {
+            return None;
+        }
+        Some(table)
+    }
+
+    fn set_groups(&mut self, side: usize, file: usize, order: [u8; 2]) -> Option<()> {
+        let has_pawns = self.has_pawns;
+        let has_unique_pieces = self.has_unique_pieces;
+        let piece_count = self.piece_count;
+        let both_pawns = has_pawns && self.pawn_count[1] > 0;
+        let d = &mut self.items[side][file];
+
+        let mut first_len: i32 = if has_pawns {
+            0
+        } else if has_unique_pieces {
+            3
+        } else {
+            2
+        };
+        let mut n = 0;
+        d.group_len[0] = 1;
+        for i in 1..piece_count {
+            first_len -= 1;
+            if first_len > 0 || d.pieces[i] == d.pieces[i - 1] {
+                d.group_len[n] += 1;
+            } else {
+                n += 1;
+                d.group_len[n] = 1;
+            }
+        }
+        n += 1;
+        d.group_len[n] = 0;
+
+        let mut next = if both_pawns { 2 } else { 1 };
+        let mut free_squares =
+            64usize.checked_sub(d.group_len[0] + if both_pawns { d.group_len[1] } else { 0 })?;
+        let mut idx: u64 = 1;
+        let mut k = 0;
+        while next < n || k == order[0] || k == order[1] {
+            if k >= NO_ORDER {
+                return None;
+            }
+            if k == order[0] {
+                d.group_idx[0] = idx;
+                idx *= if has_pawns {
+                    *CONSTS.lead_pawns_size.get(d.group_len[0])?.get(file)?
+                } else if has_unique_pieces {
+                    31332
+                } else {
+                    CONSTS.kk_len
+                };
+            } else if k == order[1] {
+                d.group_idx[1] = idx;
+                idx *= binomial(d.group_len[1], 48usize.checked_sub(d.group_len[0])?);
+            } else {
+                d.group_idx[next] = idx;
+                idx *= binomial(d.group_len[next], free_squares);
+                free_squares = free_squares.checked_sub(d.group_len[next])?;
+                next += 1;
+            }
+            k += 1;
+        }
+        d.group_idx[n] = idx;
+        Some(())
+    }
+
+    fn set_sizes(&mut self, side: usize, file: usize, mut at: usize) -> Option<usize> {
+        let bytes = &self.bytes;
+        let d = &mut self.items[side][file];
+        d.flags = *bytes.get(at)?;
+        at += 1;
+        if d.flags & FLAG_SINGLE_VALUE != 0 {
+            d.min_sym_len = *bytes.get(at)?;
+            return Some(at + 1);
+        }
+        let tb_size = d.group_idx[d.group_len.iter().position(|len| *len == 0)?];
+        d.block_size = 1usize.checked_shl(u32::from(*bytes.get(at)?))?;
+        d.span = 1u64.checked_shl(u32::from(*bytes.get(at + 1)?))?;
+        d.sparse_index_size = tb_size.div_ceil(d.span) as usize;
+        let padding = usize::from(*bytes.get(at + 2)?);
+        d.num_blocks = u32_le(bytes, at + 3)? as usize;
+        d.block_length_size = d.num_blocks + padding;
+        let max_sym_len = *bytes.get(at + 7)?;
+        d.min_sym_len = *bytes.get(at + 8)?;
+        at += 9;
+        d.lowest_sym = at;
+        let len = usize::from(max_sym_len.checked_sub(d.min_sym_len)?) + 1;
+        d.base64 = vec![0; len];
+        for i in (0..len - 1).rev() {
+            let lowest = u64::from(u16_le(bytes, d.lowest_sym + 2 * i)?);
+            let next_lowest = u64::from(u16_le(bytes, d.lowest_sym + 2 * (i + 1))?);
+            d.base64[i] = d.base64[i + 1].wrapping_add(lowest).wrapping_sub(next_lowest) / 2;
+        }
+        for (i, base) in d.base64.iter_mut().enumerate() {
+            let shift = 64u32.checked_sub(i as u32 + u32::from(d.min_sym_len))?;
+            *base = base.checked_shl(shift).unwrap_or(0);
+        }
+        at += len * 2;
+        let sym_count = usize::from(u16_le(bytes, at)?);
+        at += 2;
+        d.btree = at;
+        d.symlen = vec![0; sym_count];
+        let mut visited = vec![false; sym_count];
+        for sym in 0..sym_count {
+            if !visited[sym] {
+                d.symlen[sym] = set_symlen(bytes, d.btree, sym, &mut d.symlen, &mut visited)?;
+            }
+        }
+        Some(at + sym_count * 3 + (sym_count & 1))
+    }
+
+    fn set_dtz_map(&mut self, mut at: usize) -> Option<usize> {
+        let bytes = &self.bytes;
+        self.map = at;
+        for d in self.items[0].iter_mut() {
+            if d.flags & FLAG_MAPPED == 0 {
+                continue;
+            }
+            if d.flags & FLAG_WIDE != 0 {
+                at += at & 1;
+                for i in 0..4 {
+                    d.map_idx[i] = (at - self.map) / 2 + 1;
+                    at += 2 * usize::from(u16_le(bytes, at)?) + 2;
+                }
+            } else {
+                for i in 0..4 {
+                    d.map_idx[i] = at - self.map + 1;
+                    at += usize::from(*bytes.get(at)?) + 1;
+                }
+            }
+        }
+        Some(at + (at & 1))
+    }
+
+    fn check_dtz_stm(&self, stm: usize, file: usize) -> bool {
+        match self.kind {
+            TableKind::Wdl => true,
+            TableKind::Dtz => {
+                usize::from(self.items[0][file].flags & FLAG_STM) == stm
+                    || (self.symmetric && !self.has_pawns)
+            }
+        }
+    }
+
+    fn map_score(&self, file: usize, value: i32, wdl: i32) -> Option<i32> {
+        if self.kind == TableKind::Wdl {
+            return Some(value - 2);
+        }
+        let d = &self.items[0][file];
+        let mut value = value;
+        if d.flags & FLAG_MAPPED != 0 {
+            let i = d.map_idx[*WDL_MAP.get(usize::try_from(wdl + 2).ok()?)?] + value as usize;
+            value = if d.flags & FLAG_WIDE != 0 {
+                i32::from(u16_le(&self.bytes, self.map + 2 * i)?)
+            } else {
+                i32::from(*self.bytes.get(self.map + i)?)
+            };
+        }
+        if (wdl == 2 && d.flags & FLAG_WIN_PLIES == 0)
+            || (wdl == -2 && d.flags & FLAG_LOSS_PLIES == 0)
+            || wdl == 1
+            || wdl == -1
+        {
+            value *= 2;
+        }
+        Some(value + 1)
+    }
+
+    fn decompress_pairs(&self, d: &PairsData, idx: u64) -> Option<i32> {
+        if d.flags & FLAG_SINGLE_VALUE != 0 {
+            return Some(i32::from(d.min_sym_len));
+        }
+        let bytes = &self.bytes;
+        let block_length = |block: usize| -> Option<i64> {
+            if block >= d.block_length_size {
+                return None;
+            }
+            Some(i64::from(u16_le(bytes, d.block_length + 2 * block)?))
+        };
+
+        let k = (idx / d.span) as usize;
+        let entry = d.sparse_index + k * SPARSE_ENTRY_SIZE;
+        let mut block = u32_le(bytes, entry)? as usize;
+        let mut offset = i64::from(u16_le(bytes, entry + 4)?);
+        offset += (idx % d.span) as i64 - (d.span / 2) as i64;
+        while offset < 0 {
+            block = block.checked_sub(1)?;
+            offset += block_length(block)? + 1;
+        }
+        while offset > block_length(block)? {
+            offset -= block_length(block)? + 1;
+            block += 1;
+        }
+
+        let min_sym_len = usize::from(d.min_sym_len);
+        let mut ptr = d.data + block * d.block_size;
+        let mut buf = u64_be(bytes, ptr)?;
+        ptr += 8;
+        let mut buf_size = 64;
+        let mut sym;
+        loop {
+            let mut len = 0;
+            while buf < *d.base64.get(len)? {
+                len += 1;
+            }
+            let shift = 64u32.checked_sub((len + min_sym_len) as u32)?;
+            sym = (buf - d.base64[len]).checked_shr(shift).unwrap_or(0) as usize;
+            sym += usize::from(u16_le(bytes, d.lowest_sym + 2 * len)?);
+            let sym_len = i64::from(*d.symlen.get(sym)?);
+            if offset < sym_len + 1 {
+                break;
+            }
+            offset -= sym_len + 1;
+            len += min_sym_len;
+            buf = buf.checked_shl(len as u32).unwrap_or(0);
+            buf_size -= len as i32;
+            if buf_size <= 32 {
+                buf_size += 32;
+                buf |= u64::from(u32_be(bytes, ptr).unwrap_or(0)) << (64 - buf_size);
+                ptr += 4;
+            }
+        }
+
+        while *d.symlen.get(sym)? != 0 {
+            let (left, right) = symbol_pair(bytes, d.btree, sym)?;
+            let left_len = i64::from(*d.symlen.get(left)?);
+            if offset < left_len + 1 {
+                sym = left;
+            } else {
+                offset -= left_len + 1;
+                sym = right;
+            }
+        }
+        Some(symbol_pair(bytes, d.btree, sym)?.0 as i32)
+    }
+
+    // pieces are (square, piece) with squares from A1 0 to H8 63 and pieces from white Pawn 1 to
+    // black King 14, black_stronger tells the position has the table colors swapped
+    fn encode(
+        &self,
+        pieces: &[(usize, u8)],
+        white_to_move: bool,
+        black_stronger: bool,
+    ) -> Option<Encoding> {
+        let consts = &*CONSTS;
+        let flip = (!white_to_move && self.symmetric) || black_stronger;
+        let flip_color = if flip { BLACK } else { 0 };
+        let flip_squares = if flip { 56 } else { 0 };
+        let stm = usize::from(flip ^ !white_to_move);
+
+        let mut squares: Vec<usize> = Vec::with_capacity(pieces.len());
+        let mut table_pieces: Vec<u8> = Vec::with_capacity(pieces.len());
+        let mut file = 0;
+        let mut lead_pawns = 0;
+        let lead_pawn = self.items[0][0].pieces[0] ^ flip_color;
+        if self.has_pawns {
+            for (sq, piece) in pieces.iter().filter(|(_, piece)| *piece == lead_pawn) {
+                squares.push(sq ^ flip_squares);
+                table_pieces.push(piece ^ flip_color);
+            }
+            lead_pawns = squares.len();
+            let lead = (0..lead_pawns).max_by_key(|i| consts.map_pawns[squares[*i]])?;
+            squares.swap(0, lead);
+            file = file_of(squares[0]).min(7 - file_of(squares[0]));
+        }
+        if !self.check_dtz_stm(stm, file) {
+            return Some(Encoding::ChangeStm);
+        }
+        for (sq, piece) in pieces {
+            if !self.has_pawns || *piece != lead_pawn {
+                squares.push(sq ^ flip_squares);
+                table_pieces.push(piece ^ flip_color);
+            }
+        }
+        if squares.len() != self.piece_count {
+            return None;
+        }
+        let size = squares.len();
+        let side = stm % self.items.len();
+        let d = &self.items[side][file];
+
+        for i in lead_pawns..size.saturating_sub(1) {
+            if let Some(j) = (i + 1..size).find(|j| d.pieces[i] == table_pieces[*j]) {
+                table_pieces.swap(i, j);
+                squares.swap(i, j);
+            }
+        }
+        if file_of(squares[0]) > 3 {
+            squares.iter_mut().for_each(|sq| *sq ^= 7);
+        }
+
+        let mut idx;
+        if self.has_pawns {
+            idx = consts.lead_pawn_idx[lead_pawns][squares[0]];
+            squares[1..lead_pawns].sort_by_key(|sq| consts.map_pawns[*sq]);
+            for (i, sq) in squares.iter().enumerate().take(lead_pawns).skip(1) {
+                idx += binomial(i, consts.map_pawns[*sq]);
+            }
+        } else {
+            if rank_of(squares[0]) > 3 {
+                squares.iter_mut().for_each(|sq| *sq ^= 56);
+            }
+            for i in 0..d.group_len[0] {
+                let off = off_a1h8(squares[i]);
+                if off == 0 {
+                    continue;
+                }
+                if off > 0 {
+                    squares[i..].iter_mut().for_each(|sq| *sq = ((*sq >> 3) | (*sq << 3)) & 63);
+                }
+                break;
+            }
+            idx = if self.has_unique_pieces {
+                let adjust1 = u64::from(squares[1] > squares[0]);
+                let adjust2 =
+                    u64::from(squares[2] > squares[0]) + u64::from(squares[2] > squares[1]);
+                let (s1, s2) = (squares[1] as u64, squares[2] as u64);
+                let (r0, r1, r2) = (
+                    rank_of(squares[0]) as u64,
+                    rank_of(squares[1]) as u64,
+                    rank_of(squares[2]) as u64,
+                );
+                if off_a1h8(squares[0]) != 0 {
+                    (consts.map_a1d1d4[squares[0]] as u64 * 63 + (s1 - adjust1)) * 62 + s2 - adjust2
+                } else if off_a1h8(squares[1]) != 0 {
+                    (6 * 63 + r0 * 28 + consts.map_b1h1h7[squares[1]]) * 62 + s2 - adjust2
+                } else if off_a1h8(squares[2]) != 0 {
+                    6 * 63 * 62
+                        + 4 * 28 * 62
+                        + r0 * 7 * 28
+                        + (r1 - adjust1) * 28
+                        + consts.map_b1h1h7[squares[2]]
+                } else {
+                    6 * 63 * 62
+                        + 4 * 28 * 62
+                        + 4 * 7 * 28
+                        + r0 * 7 * 6
+                        + (r1 - adjust1) * 6
+                        + (r2 - adjust2)
+                }
+            } else {
+                consts.map_kk[consts.map_a1d1d4[squares[0]]][squares[1]]
+            };
+        }
+
+        idx *= d.group_idx[0];
+        let mut remaining_pawns = self.has_pawns && self.pawn_count[1] > 0;
+        let mut start = d.group_len[0];
+        let mut next = 1;
+        while d.group_len[next] != 0 {
+            let len = d.group_len[next];
+            squares[start..start + len].sort();
+            let mut n = 0;
+            for i in 0..len {
+                let sq = squares[start + i];
+                let adjust = squares[..start].iter().filter(|prev| sq > **prev).count();
+                let available = sq.checked_sub(adjust + if remaining_pawns { 8 } else { 0 })?;
+                n += binomial(i + 1, available);
+            }
+            remaining_pawns = false;
+            idx += n * d.group_idx[next];
+            start += len;
+            next += 1;
+        }
+        Some(Encoding::Index { side, file, idx })
+    }
+
+    pub fn probe(
+        &self,
+        pieces: &[(usize, u8)],
+        white_to_move: bool,
+        black_stronger: bool,
+        wdl: i32,
+    ) -> Option<TableValue> {
+        match self.encode(pieces, white_to_move, black_stronger)? {
+            Encoding::Index { side, file, idx } => {
+                let value = self.decompress_pairs(&self.items[side][file], idx)?;
+                Some(TableValue::Value(self.map_score(file, value, wdl)?))
+            }
+            Encoding::ChangeStm => Some(TableValue::ChangeStm),
+        }
+    }
+}
+
+fn symbol_pair(bytes: &[u8], btree: usize, sym: usize) -> Option<(usize, usize)> {
+    let lr = bytes.get(btree + 3 * sym..btree + 3 * sym + 3)?;
+    let left = (usize::from(lr[1] & 0xF) << 8) | usize::from(lr[0]);
+    let right = (usize::from(lr[2]) << 4) | usize::from(lr[1] >> 4);
+    Some((left, right))
+}
+
+fn set_symlen(
+    bytes: &[u8],
+    btree: usize,
+    sym: usize,
+    symlen: &mut [u16],
+    visited: &mut [bool],
+) -> Option<u16> {
+    visited[sym] = true;
+    let (left, right) = symbol_pair(bytes, btree, sym)?;
+    if right == NO_SYMBOL {
+        return Some(0);
+    }
+    for child in [left, right] {
+        if !*visited.get(child)? {
+            symlen[child] = set_symlen(bytes, btree, child, symlen, visited)?;
+        }
+    }
+    Some(symlen[left] + symlen[right] + 1)
+}
+
+// builds a table where every position of each side stores a single value, with the kings
+// leading the pieces
+#[cfg(test)]
+pub fn single_value_table_bytes(
+    kind: TableKind,
+    white: &[u8],
+    black: &[u8],
+    values: &[u8],
+) -> Vec<u8> {
+    let mut sorted_white = white.to_vec();
+    let mut sorted_black = black.to_vec();
+    sorted_white.sort();
+    sorted_black.sort();
+    let split = kind == TableKind::Wdl && sorted_white != sorted_black;
+    let mut bytes = kind.magic().to_vec();
+    bytes.push(if split { HEADER_SPLIT } else { 0 });
+    bytes.push(0);
+    let others = white.iter().filter(|piece| **piece != KING).copied();
+    let others =
+        others.chain(black.iter().filter(|piece| **piece != KING).map(|piece| piece | BLACK));
+    for piece in [KING, KING | BLACK].into_iter().chain(others) {
+        bytes.push(piece | (piece << 4));
+    }
+    if bytes.len() % 2 == 1 {
+        bytes.push(0);
+    }
+    for value in values.iter().take(if split { 2 } else { 1 }) {
+        bytes.extend([FLAG_SINGLE_VALUE, *value]);
+    }
+    let len = bytes.len().div_ceil(64) * 64 + 16;
+    bytes.resize(len, 0);
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::Path};
+
+    use super::{
+        BLACK, CONSTS, Encoding, KING, Table, TableKind, TableValue, binomial,
+        single_value_table_bytes,
+    };
+
+    const ROOK: u8 = 4;
+
+    #[test]
+    fn consts() {
+        assert_eq!(CONSTS.kk_len, 462);
+        assert_eq!(CONSTS.map_a1d1d4[1], 0);
+        assert_eq!(CONSTS.map_a1d1d4[0], 6);
+        assert_eq!(CONSTS.map_a1d1d4[27], 9);
+        assert_eq!(CONSTS.map_pawns[8], 47);
+        assert_eq!(CONSTS.map_pawns[15], 46);
+        assert_eq!(CONSTS.lead_pawns_size[1], [6, 6, 6, 6]);
+        assert_eq!(CONSTS.lead_pawns_size[2][0], 252);
+        assert_eq!(binomial(2, 5), 10);
+        assert_eq!(binomial(0, 63), 1);
+        assert_eq!(binomial(9, 63), 0);
+    }
+
+    #[test]
+    fn table_try_of() {
+        let bytes = single_value_table_bytes(TableKind::Wdl, &[KING, ROOK], &[KING], &[4, 0]);
+        assert_eq!(bytes.len() % 64, 16);
+        assert!(Table::try_of(TableKind::Wdl, &[KING, ROOK], &[KING], bytes.clone()).is_some());
+        assert!(Table::try_of(TableKind::Dtz, &[KING, ROOK], &[KING], bytes.clone()).is_none());
+        assert!(
+            Table::try_of(TableKind::Wdl, &[KING, ROOK], &[KING, ROOK], bytes.clone()).is_none()
+        );
+        assert!(
+            Table::try_of(TableKind::Wdl, &[KING, ROOK], &[KING], bytes[..16].to_vec()).is_none()
+        );
+        assert!(Table::try_of(TableKind::Wdl, &[KING, ROOK], &[KING], vec![0; 80]).is_none());
+    }
+
+    #[test]
+    fn table_probe_single_value() {
+        let bytes = single_value_table_bytes(TableKind::Wdl, &[KING, ROOK], &[KING], &[4, 0]);
+        let table = Table::try_of(TableKind::Wdl, &[KING, ROOK], &[KING], bytes).unwrap();
+        let pieces = [(0, KING), (63, ROOK), (36, KING | BLACK)];
+        assert_eq!(table.probe(&pieces, true, false, 0), Some(TableValue::Value(2)));
+        assert_eq!(table.probe(&pieces, false, false, 0), Some(TableValue::Value(-2)));
+        assert_eq!(table.probe(&pieces[..2], true, false, 0), None);
+    }
+
+    #[test]
+    fn table_encode_symmetries() {
+        let bytes = single_value_table_bytes(TableKind::Wdl, &[KING, ROOK], &[KING], &[4, 0]);
+        let table = Table::try_of(TableKind::Wdl, &[KING, ROOK], &[KING], bytes).unwrap();
+        let transforms: [fn(usize) -> usize; 4] =
+            [|sq| sq, |sq| sq ^ 7, |sq| sq ^ 56, |sq| ((sq >> 3) | (sq << 3)) & 63];
+        let size = table.items[0][0].group_idx[1];
+        for (wk, wr, bk) in
+            [(0, 63, 36), (12, 3, 50), (27, 9, 45), (9, 54, 7), (18, 27, 63), (27, 9, 44)]
+        {
+            let indexes: Vec<_> = transforms
+                .iter()
+                .map(|transform| {
+                    let pieces = [
+                        (transform(wk), KING),
+                        (transform(wr), ROOK),
+                        (transform(bk), KING | BLACK),
+                    ];
+                    table.encode(&pieces, true, false).unwrap()
+                })
+                .collect();
+            assert!(indexes.iter().all(|index| *index == indexes[0]));
+            assert!(matches!(indexes[0], Encoding::Index { idx, .. } if idx < size));
+        }
+        let swapped = [(4, KING | BLACK), (60, ROOK | BLACK), (28, KING)];
+        assert_eq!(
+            table.encode(&swapped, false, true),
+            table.encode(&[(60, KING), (4, ROOK), (36, KING | BLACK)], true, false)
+        );
+    }
+
+    #[test]
+    fn table_probe_fixture() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures/syzygy/KRvK.rtbz");
+        let bytes = fs::read(path).unwrap();
+        let table = Table::try_of(TableKind::Dtz, &[KING, ROOK], &[KING], bytes).unwrap();
+        // the kings and the rook are all on the a1-h8 diagonal
+        let pieces = [(0, KING), (9, ROOK), (27, KING | BLACK)];
+        assert_eq!(table.probe(&pieces, false, false, -2), Some(TableValue::Value(32)));
+        assert_eq!(table.probe(&pieces, true, false, 2), Some(TableValue::ChangeStm));
+        let pieces = [(7, KING), (15, ROOK), (23, KING | BLACK)];
+        assert_eq!(table.probe(&pieces, false, false, -2), Some(TableValue::Value(30)));
+    }
+
+    #[test]
+    fn table_dtz_changes_side_to_move() {
+        let bytes = single_value_table_bytes(TableKind::Dtz, &[KING, ROOK], &[KING], &[5]);
+        let table = Table::try_of(TableKind::Dtz, &[KING, ROOK], &[KING], bytes).unwrap();
+        let pieces = [(0, KING), (63, ROOK), (36, KING | BLACK)];
+        assert_eq!(table.probe(&pieces, true, false, 2), Some(TableValue::Value(11)));
+        assert_eq!(table.probe(&pieces, true, false, 1), Some(TableValue::Value(11)));
+        assert_eq!(table.probe(&pieces, false, false, -2), Some(TableValue::ChangeStm));
+    }
+}
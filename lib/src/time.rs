@@ -0,0 +1,69 @@
+use std::{cell::Cell, time::Instant};
+
+pub trait TimeSource {
+    fn now_ms(&self) -> u64;
+}
+
+#[derive(Debug, Clone)]
+pub struct SystemTimeSource {
+    start: Instant,
+}
+
+impl SystemTimeSource {
+    pub fn new() -> Self {
+        SystemTimeSource { start: Instant::now() }
+    }
+}
+
+impl Default for SystemTimeSource {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TimeSource for SystemTimeSource {
+    fn now_ms(&self) -> u64 {
+        self.start.elapsed().as_millis() as u64
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ManualTimeSource {
+    now: Cell<u64>,
+}
+
+impl ManualTimeSource {
+    pub fn of(now_ms: u64) -> Self {
+        ManualTimeSource { now: Cell::new(now_ms) }
+    }
+
+    pub fn set(&self, now_ms: u64) {
+        self.now.set(now_ms);
+    }
+
+    pub fn advance(&self, ms: u64) {
+        self.now.set(self.now.get() + ms);
+    }
+}
+
+impl TimeSource for ManualTimeSource {
+    fn now_ms(&self) -> u64 {
+        self.now.get()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ManualTimeSource, TimeSource};
+
+    #[test]
+    fn manual_time_source() {
+        let time_source = ManualTimeSource::of(100);
+        assert_eq!(time_source.now_ms(), 100);
+        time_source.advance(50);
+        assert_eq!(time_source.now_ms(), 150);
+        time_source.set(10);
+        assert_eq!(time_source.now_ms(), 10);
+        assert_eq!(ManualTimeSource::default().now_ms(), 0);
+    }
+}
@@ -0,0 +1,487 @@
+use std::{
+    io::BufRead,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicBool, Ordering},
+    },
+    thread::{self, JoinHandle},
+};
+
+use libre_chess_lib::{
+    color::Color,
+    engine::search::{Engine, SearchLimits, SearchResult},
+    game::{
+        fen::game_try_of_fen,
+        game::Game,
+        lan::{lan_promotion_of, lan_to_string, lan_try_of},
+        mode::{get_game_mode_presets, try_get_game_mode},
+        mov::{PieceMoveType, promotion_of_game_move_type},
+        rule::{
+            check::is_in_check, init::game_of_mode, move_piece::play_move_with_promotion,
+            turn::evaluate_turn,
+        },
+    },
+    pos::Pos,
+    time::{SystemTimeSource, TimeSource},
+};
+
+use crate::uci::{
+    DEFAULT_HASH_MB, ENGINE_NAME, Output, send, tablebase_of_paths, time_for_move, tt_size_of_mb,
+};
+
+const DEFAULT_MOVE_TIME_MS: u64 = 5_000;
+
+pub fn cecp_variant_of(mode_id: &str) -> &str {
+    match mode_id {
+        "standard" => "normal",
+        _ => mode_id,
+    }
+}
+
+pub fn mode_id_of(variant: &str) -> &str {
+    match variant {
+        "normal" => "standard",
+        _ => variant,
+    }
+}
+
+pub fn features() -> String {
+    let variants: Vec<&str> =
+        get_game_mode_presets().iter().map(|preset| cecp_variant_of(preset.id)).collect();
+    format!(
+        "feature myname=\"{ENGINE_NAME}\" ping=1 setboard=1 usermove=1 san=0 colors=0 sigint=0 sigterm=0 reuse=1 analyze=0 egt=\"syzygy\" variants=\"{}\" done=1",
+        variants.join(",")
+    )
+}
+
+pub fn result_of(game: &Game) -> Option<String> {
+    let turn = evaluate_turn(&game.start, &game.history);
+    let has_moves = game
+        .players
+        .get(&turn)
+        .is_some_and(|player| player.moves.values().any(|piece_moves| !piece_moves.is_empty()));
+    if has_moves {
+        return None;
+    }
+    if is_in_check(&game.board, &game.players, &game.start, &game.history) {
+        Some(match turn {
+            Color::White => "0-1 {Black mates}".into(),
+            Color::Black => "1-0 {White mates}".into(),
+        })
+    } else {
+        Some("1/2-1/2 {Stalemate}".into())
+    }
+}
+
+pub fn thinking_of(result: &SearchResult, time: u64) -> String {
+    let pv: Vec<String> = result.pv.iter().map(lan_to_string).collect();
+    format!("{} {} {} {} {}", result.depth, result.score, time / 10, result.nodes, pv.join(" "))
+}
+
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct CecpClock {
+    pub moves_per_session: u64,
+    pub inc: u64,
+    pub time: Option<u64>,
+    pub fixed_time: Option<u64>,
+    pub depth: Option<u8>,
+}
+
+impl CecpClock {
+    pub fn limits(&self, played: u64) -> SearchLimits {
+        let moves_to_go = (self.moves_per_session > 0)
+            .then(|| self.moves_per_session - played % self.moves_per_session);
+        let time = self
+            .fixed_time
+            .or_else(|| self.time.map(|time| time_for_move(time, self.inc, moves_to_go)))
+            .or(if self.depth.is_none() { Some(DEFAULT_MOVE_TIME_MS) } else { None });
+        SearchLimits { depth: self.depth, nodes: None, time, mate: None }
+    }
+}
+
+struct CecpState {
+    mode_id: String,
+    base: Game,
+    moves: Vec<(Pos, Pos, Option<PieceMoveType>)>,
+    game: Game,
+    force: bool,
+    engine_color: Color,
+    post: bool,
+    clock: CecpClock,
+}
+
+impl CecpState {
+    fn of(mode_id: &str, base: Game) -> Self {
+        CecpState {
+            mode_id: mode_id.into(),
+            game: base.clone(),
+            base,
+            moves: Vec::new(),
+            force: false,
+            engine_color: Color::Black,
+            post: false,
+            clock: CecpClock::default(),
+        }
+    }
+
+    fn reset(&mut self, mode_id: &str, base: Game) {
+        let post = self.post;
+        let clock = self.clock.clone();
+        *self = CecpState::of(mode_id, base);
+        self.post = post;
+        self.clock = clock;
+    }
+
+    fn play(&mut self, from: Pos, to: Pos, promotion: Option<PieceMoveType>) {
+        play_move_with_promotion(&mut self.game, &from, &to, promotion.as_ref());
+        self.moves.push((from, to, promotion));
+    }
+
+    fn undo(&mut self) {
+        self.moves.pop();
+        self.game = self.base.clone();
+        for (from, to, promotion) in self.moves.iter() {
+            play_move_with_promotion(&mut self.game, from, to, promotion.as_ref());
+        }
+    }
+}
+
+pub struct Cecp {
+    state: Arc<Mutex<CecpState>>,
+    engine: Arc<Mutex<Engine>>,
+    stop: Arc<AtomicBool>,
+    cancel: Arc<AtomicBool>,
+    search: Option<JoinHandle<()>>,
+    out: Output,
+}
+
+impl Cecp {
+    pub fn of(out: Output) -> Self {
+        let mode_id = "standard";
+        let base = game_of_mode(try_get_game_mode(mode_id).unwrap());
+        Cecp {
+            state: Arc::new(Mutex::new(CecpState::of(mode_id, base))),
+            engine: Arc::new(Mutex::new(Engine::of(tt_size_of_mb(DEFAULT_HASH_MB)))),
+            stop: Arc::new(AtomicBool::new(false)),
+            cancel: Arc::new(AtomicBool::new(false)),
+            search: None,
+            out,
+        }
+    }
+
+    fn wait(&mut self) {
+        if let Some(search) = self.search.take() {
+            let _ = search.join();
+        }
+    }
+
+    // the search ends without playing its move, for commands that change or leave the game
+    fn abort(&mut self) {
+        self.cancel.store(true, Ordering::Relaxed);
+        self.stop.store(true, Ordering::Relaxed);
+        self.wait();
+    }
+
+    fn think(&mut self) {
+        self.wait();
+        self.stop.store(false, Ordering::Relaxed);
+        self.cancel.store(false, Ordering::Relaxed);
+        let state = Arc::clone(&self.state);
+        let engine = Arc::clone(&self.engine);
+        let stop = Arc::clone(&self.stop);
+        let cancel = Arc::clone(&self.cancel);
+        let out = Arc::clone(&self.out);
+        self.search = Some(thread::spawn(move || {
+            let (game, limits, post) = {
+                let state = state.lock().unwrap();
+                let played = state.moves.len() as u64 / 2;
+                (state.game.clone(), state.clock.limits(played), state.post)
+            };
+            if result_of(&game).is_some() {
+                return;
+            }
+            let time_source = SystemTimeSource::new();
+            let result =
+                engine.lock().unwrap().search(&game, &limits, &time_source, &stop, &mut |result| {
+                    if post {
+                        send(&out, &thinking_of(result, time_source.now_ms()));
+                    }
+                });
+            let Some(best_move) = result.best_move else {
+                return;
+            };
+            let mut state = state.lock().unwrap();
+            if cancel.load(Ordering::Relaxed) {
+                return;
+            }
+            let promotion = promotion_of_game_move_type(&best_move.typ);
+            state.play(best_move.mov.from.clone(), best_move.mov.to.clone(), promotion);
+            send(&out, &format!("move {}", lan_to_string(&best_move)));
+            if let Some(result) = result_of(&state.game) {
+                send(&out, &result);
+            }
+        }));
+    }
+
+    fn engine_to_move(&self) -> bool {
+        let state = self.state.lock().unwrap();
+        !state.force && evaluate_turn(&state.game.start, &state.game.history) == state.engine_color
+    }
+
+    fn user_move(&mut self, lan: &str) {
+        {
+            let mut state = self.state.lock().unwrap();
+            match lan_try_of(&state.game, lan) {
+                Some((from, to)) => state.play(from, to, lan_promotion_of(lan)),
+                None => {
+                    send(&self.out, &format!("Illegal move: {lan}"));
+                    return;
+                }
+            }
+            if let Some(result) = result_of(&state.game) {
+                send(&self.out, &result);
+                return;
+            }
+        }
+        if self.engine_to_move() {
+            self.think();
+        }
+    }
+
+    pub fn handle(&mut self, line: &str) -> bool {
+        let args: Vec<&str> = line.split_whitespace().collect();
+        let Some((command, args)) = args.split_first() else {
+            return true;
+        };
+        if *command == "?" {
+            self.stop.store(true, Ordering::Relaxed);
+            return true;
+        }
+        if *command == "ping" {
+            self.wait();
+            send(&self.out, &format!("pong {}", args.join(" ")));
+            return true;
+        }
+        match *command {
+            "new" | "variant" | "setboard" | "force" | "undo" | "remove" | "result" | "quit" => {
+                self.abort()
+            }
+            _ => self.wait(),
+        }
+        match *command {
+            "protover" => send(&self.out, &features()),
+            "new" => {
+                self.engine.lock().unwrap().clear();
+                let mut state = self.state.lock().unwrap();
+                state.reset("standard", game_of_mode(try_get_game_mode("standard").unwrap()));
+                state.clock.depth = None;
+            }
+            "variant" => {
+                let variant = args.first().copied().unwrap_or_default();
+                match try_get_game_mode(mode_id_of(variant)) {
+                    Some(mode) => {
+                        self.state.lock().unwrap().reset(mode_id_of(variant), game_of_mode(mode))
+                    }
+                    None => send(&self.out, &format!("Error (unsupported variant): {variant}")),
+                }
+            }
+            "setboard" => match game_try_of_fen(&args.join(" ")) {
+                Some(game) => {
+                    let mut state = self.state.lock().unwrap();
+                    let mode_id = state.mode_id.clone();
+                    let force = state.force;
+                    state.reset(&mode_id, game);
+                    state.force = force;
+                }
+                None => send(&self.out, "tellusererror Illegal position"),
+            },
+            "force" => self.state.lock().unwrap().force = true,
+            "go" => {
+                {
+                    let mut state = self.state.lock().unwrap();
+                    state.force = false;
+                    state.engine_color = evaluate_turn(&state.game.start, &state.game.history);
+                }
+                self.think();
+            }
+            "playother" => {
+                let mut state = self.state.lock().unwrap();
+                state.force = false;
+                state.engine_color = match evaluate_turn(&state.game.start, &state.game.history) {
+                    Color::White => Color::Black,
+                    Color::Black => Color::White,
+                };
+            }
+            "usermove" => self.user_move(args.first().copied().unwrap_or_default()),
+            "undo" => self.state.lock().unwrap().undo(),
+            "remove" => {
+                let mut state = self.state.lock().unwrap();
+                state.undo();
+                state.undo();
+            }
+            "level" => {
+                let mut state = self.state.lock().unwrap();
+                state.clock.moves_per_session =
+                    args.first().and_then(|mps| mps.parse().ok()).unwrap_or(0);
+                state.clock.inc = args
+                    .get(2)
+                    .and_then(|inc| inc.parse::<f64>().ok())
+                    .map_or(0, |inc| (inc * 1000.0) as u64);
+                state.clock.fixed_time = None;
+            }
+            "st" => {
+                self.state.lock().unwrap().clock.fixed_time =
+                    args.first().and_then(|st| st.parse::<u64>().ok()).map(|st| st * 1000);
+            }
+            "sd" => {
+                self.state.lock().unwrap().clock.depth =
+                    args.first().and_then(|sd| sd.parse::<u8>().ok());
+            }
+            "time" => {
+                self.state.lock().unwrap().clock.time =
+                    args.first().and_then(|time| time.parse::<u64>().ok()).map(|time| time * 10);
+            }
+            "egtpath" => {
+                if args.first() == Some(&"syzygy") {
+                    let (tablebase, _) = tablebase_of_paths(&args[1..].join(" "));
+                    self.engine.lock().unwrap().tablebase = tablebase;
+                }
+            }
+            "post" => self.state.lock().unwrap().post = true,
+            "nopost" => self.state.lock().unwrap().post = false,
+            "quit" => return false,
+            "xboard" | "accepted" | "rejected" | "otim" | "result" | "hard" | "easy" | "random"
+            | "computer" | "name" | "rating" | "white" | "black" => {}
+            _ => {
+                let is_move = lan_try_of(&self.state.lock().unwrap().game, command).is_some();
+                if is_move {
+                    self.user_move(command);
+                } else {
+                    send(&self.out, &format!("Error (unknown command): {command}"));
+                }
+            }
+        }
+        true
+    }
+
+    pub fn finish(&mut self) {
+        self.wait();
+    }
+}
+
+pub fn run<R: BufRead>(input: R, out: Output) {
+    let mut cecp = Cecp::of(out);
+    for line in input.lines() {
+        let Ok(line) = line else {
+            break;
+        };
+        if !cecp.handle(&line) {
+            return;
+        }
+    }
+    cecp.finish();
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::Cursor,
+        sync::{Arc, Mutex},
+    };
+
+    use libre_chess_lib::{
+        engine::search::SearchLimits,
+        game::{fen::game_of_fen, mode::standard_chess, rule::init::game_of_mode},
+    };
+
+    use super::{CecpClock, cecp_variant_of, features, mode_id_of, result_of, run};
+
+    fn run_script(script: &str) -> Vec<String> {
+        let out = Arc::new(Mutex::new(Vec::new()));
+        run(Cursor::new(script.to_string()), out.clone());
+        let bytes = out.lock().unwrap().clone();
+        String::from_utf8(bytes).unwrap().lines().map(String::from).collect()
+    }
+
+    #[test]
+    fn cecp_variants() {
+        assert_eq!(cecp_variant_of("standard"), "normal");
+        assert_eq!(mode_id_of("normal"), "standard");
+        assert!(features().contains(" variants=\"normal\" "));
+        assert!(features().ends_with("done=1"));
+    }
+
+    #[test]
+    fn cecp_handshake() {
+        let lines = run_script("xboard\nprotover 2\nping 7\n");
+        assert_eq!(lines, vec![features(), "pong 7".into()]);
+    }
+
+    #[test]
+    fn cecp_unsupported_variant() {
+        let lines = run_script("xboard\nvariant crazyhouse\nvariant normal\nping 1\n");
+        assert_eq!(lines, vec!["Error (unsupported variant): crazyhouse", "pong 1"]);
+    }
+
+    #[test]
+    fn cecp_engine_replies() {
+        let lines = run_script("xboard\nnew\nsd 2\nusermove e2e4\nping 1\n");
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with("move "));
+        assert_eq!(lines[1], "pong 1");
+    }
+
+    #[test]
+    fn cecp_force_and_illegal() {
+        let lines = run_script("xboard\nnew\nforce\nusermove e2e4\nusermove e2e4\nping 2\n");
+        assert_eq!(lines, vec!["Illegal move: e2e4", "pong 2"]);
+    }
+
+    #[test]
+    fn cecp_setboard_go_mate() {
+        let lines =
+            run_script("xboard\nforce\nsetboard 7k/8/6K1/8/8/8/8/1Q6 w - - 0 1\nsd 3\ngo\n");
+        assert_eq!(lines, vec!["move b1b8", "1-0 {White mates}"]);
+    }
+
+    #[test]
+    fn cecp_cancel_search() {
+        for command in ["force", "new", "result 1-0 {White resigns}", "quit"] {
+            let script = format!("xboard\nprotover 2\nnew\nst 30\ngo\n{command}\nping 1\n");
+            let lines = run_script(&script);
+            assert!(!lines.iter().any(|line| line.starts_with("move ")));
+            assert_eq!(lines[0], features());
+        }
+    }
+
+    #[test]
+    fn cecp_undo() {
+        let lines = run_script("xboard\nforce\nusermove e2e4\nundo\nusermove e2e4\nping 3\n");
+        assert_eq!(lines, vec!["pong 3"]);
+    }
+
+    #[test]
+    fn cecp_result_of() {
+        assert_eq!(result_of(&game_of_mode(standard_chess())), None);
+        assert_eq!(
+            result_of(&game_of_fen("1Q5k/8/6K1/8/8/8/8/8 b - - 0 1")),
+            Some("1-0 {White mates}".into())
+        );
+        assert_eq!(
+            result_of(&game_of_fen("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1")),
+            Some("1/2-1/2 {Stalemate}".into())
+        );
+    }
+
+    #[test]
+    fn cecp_clock_limits() {
+        let clock = CecpClock { moves_per_session: 40, time: Some(60_000), ..Default::default() };
+        assert_eq!(clock.limits(0), SearchLimits { time: Some(1_500), ..Default::default() });
+        assert_eq!(clock.limits(39), SearchLimits { time: Some(59_950), ..Default::default() });
+        let clock = CecpClock { fixed_time: Some(5_000), depth: Some(4), ..Default::default() };
+        assert_eq!(
+            clock.limits(0),
+            SearchLimits { time: Some(5_000), depth: Some(4), nodes: None, mate: None }
+        );
+    }
+}
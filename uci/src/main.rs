@@ -0,0 +1,21 @@
+use std::{
+    io::{self, BufRead, Cursor, Read},
+    sync::{Arc, Mutex},
+};
+
+mod cecp;
+mod uci;
+
+fn main() {
+    let mut stdin = io::stdin().lock();
+    let mut first = String::new();
+    while first.trim().is_empty() {
+        first.clear();
+        if stdin.read_line(&mut first).unwrap_or(0) == 0 {
+            return;
+        }
+    }
+    let out = Arc::new(Mutex::new(io::stdout()));
+    let input = Cursor::new(first.clone()).chain(stdin);
+    if first.trim() == "xboard" { cecp::run(input, out) } else { uci::run(input, out) }
+}
@@ -0,0 +1,500 @@
+use std::{
+    env, fs,
+    io::{BufRead, Write},
+    mem::{self, size_of},
+    path::PathBuf,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicBool, Ordering},
+    },
+    thread::{self, JoinHandle},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use libre_chess_lib::{
+    book::polyglot::{PolyglotBook, book_moves, pick_book_move},
+    color::Color,
+    engine::{
+        search::{Engine, MATE, SearchLimits, SearchResult, is_mate_score},
+        tt::TtEntry,
+    },
+    game::{
+        fen::game_try_of_fen,
+        game::Game,
+        lan::{lan_promotion_of, lan_to_string, lan_try_of},
+        mode::standard_chess,
+        rule::{init::game_of_mode, move_piece::play_move_with_promotion, turn::evaluate_turn},
+    },
+    tablebase::syzygy::Tablebase,
+    time::{SystemTimeSource, TimeSource},
+};
+
+pub const ENGINE_NAME: &str = "libre_chess";
+pub const ENGINE_AUTHOR: &str = "the libre_chess authors";
+
+pub const DEFAULT_HASH_MB: usize = 16;
+const MAX_HASH_MB: usize = 1024;
+const DEFAULT_MOVES_TO_GO: u64 = 30;
+const MOVE_OVERHEAD_MS: u64 = 50;
+
+pub type Output = Arc<Mutex<dyn Write + Send>>;
+
+pub fn tablebase_of_paths(paths: &str) -> (Tablebase, Vec<PathBuf>) {
+    let mut tablebase = Tablebase::default();
+    let mut unread = Vec::new();
+    if paths != "<empty>" {
+        for dir in env::split_paths(paths) {
+            if tablebase.add_directory(&dir).is_none() {
+                unread.push(dir);
+            }
+        }
+    }
+    (tablebase, unread)
+}
+
+pub fn send(out: &Output, line: &str) {
+    let mut out = out.lock().unwrap();
+    let _ = writeln!(out, "{line}");
+    let _ = out.flush();
+}
+
+pub fn tt_size_of_mb(mb: usize) -> usize {
+    mb * 1024 * 1024 / size_of::<Option<TtEntry>>()
+}
+
+pub fn time_for_move(remaining: u64, inc: u64, moves_to_go: Option<u64>) -> u64 {
+    let budget = remaining / moves_to_go.unwrap_or(DEFAULT_MOVES_TO_GO).max(1) + inc * 3 / 4;
+    budget.min(remaining.saturating_sub(MOVE_OVERHEAD_MS)).max(1)
+}
+
+fn random_seed() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |duration| duration.as_nanos() as u64)
+}
+
+pub fn position_of(args: &[&str]) -> Option<Game> {
+    let moves_index = args.iter().position(|arg| *arg == "moves").unwrap_or(args.len());
+    let mut game = match *args.first()? {
+        "startpos" => game_of_mode(standard_chess()),
+        "fen" => game_try_of_fen(&args[1..moves_index].join(" "))?,
+        _ => return None,
+    };
+    for lan in args.iter().skip(moves_index + 1) {
+        let (from, to) = lan_try_of(&game, lan)?;
+        play_move_with_promotion(&mut game, &from, &to, lan_promotion_of(lan).as_ref());
+    }
+    Some(game)
+}
+
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct GoArgs {
+    pub limits: SearchLimits,
+    pub infinite: bool,
+}
+
+pub fn go_args_of(turn: &Color, args: &[&str]) -> GoArgs {
+    let mut result = GoArgs::default();
+    let mut time = None;
+    let mut inc = 0;
+    let mut moves_to_go = None;
+    let mut it = args.iter();
+    while let Some(arg) = it.next() {
+        let mut value = || it.next().and_then(|value| value.parse::<u64>().ok());
+        match (*arg, turn) {
+            ("wtime", Color::White) | ("btime", Color::Black) => time = value(),
+            ("winc", Color::White) | ("binc", Color::Black) => inc = value().unwrap_or(0),
+            ("wtime" | "btime" | "winc" | "binc", _) => {
+                value();
+            }
+            ("movestogo", _) => moves_to_go = value(),
+            ("movetime", _) => result.limits.time = value(),
+            ("nodes", _) => result.limits.nodes = value(),
+            ("depth", _) => result.limits.depth = value().map(|depth| depth.min(255) as u8),
+            ("mate", _) => result.limits.mate = value().map(|moves| moves.min(255) as u8),
+            ("infinite", _) => result.infinite = true,
+            _ => {}
+        }
+    }
+    if result.limits.time.is_none() && !result.infinite {
+        result.limits.time = time.map(|time| time_for_move(time, inc, moves_to_go));
+    }
+    result
+}
+
+pub fn score_to_string(score: i32) -> String {
+    if is_mate_score(score) {
+        let moves = (MATE - score.abs() + 1) / 2;
+        format!("mate {}", if score > 0 { moves } else { -moves })
+    } else {
+        format!("cp {score}")
+    }
+}
+
+pub fn info_of(result: &SearchResult, time: u64) -> String {
+    let pv: Vec<String> = result.pv.iter().map(lan_to_string).collect();
+    format!(
+        "info depth {} score {} nodes {} time {} pv {}",
+        result.depth,
+        score_to_string(result.score),
+        result.nodes,
+        time,
+        pv.join(" ")
+    )
+}
+
+pub struct Uci {
+    game: Game,
+    engine: Arc<Mutex<Engine>>,
+    stop: Arc<AtomicBool>,
+    search: Option<JoinHandle<()>>,
+    infinite: bool,
+    book: PolyglotBook,
+    own_book: bool,
+    out: Output,
+}
+
+impl Uci {
+    pub fn of(out: Output) -> Self {
+        Uci {
+            game: game_of_mode(standard_chess()),
+            engine: Arc::new(Mutex::new(Engine::of(tt_size_of_mb(DEFAULT_HASH_MB)))),
+            stop: Arc::new(AtomicBool::new(false)),
+            search: None,
+            infinite: false,
+            book: PolyglotBook::default(),
+            own_book: false,
+            out,
+        }
+    }
+
+    fn wait(&mut self) {
+        if let Some(search) = self.search.take() {
+            let _ = search.join();
+        }
+    }
+
+    fn stop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        self.wait();
+    }
+
+    fn go(&mut self, args: &[&str]) {
+        self.stop();
+        self.stop.store(false, Ordering::Relaxed);
+        let go_args = go_args_of(&evaluate_turn(&self.game.start, &self.game.history), args);
+        self.infinite = go_args.infinite;
+        if self.own_book && !go_args.infinite {
+            let moves = book_moves(&self.book, &self.game);
+            if let Some(book_move) = pick_book_move(&moves, random_seed()) {
+                send(&self.out, &format!("bestmove {}", lan_to_string(&book_move.game_move)));
+                return;
+            }
+        }
+        let game = self.game.clone();
+        let engine = Arc::clone(&self.engine);
+        let stop = Arc::clone(&self.stop);
+        let out = Arc::clone(&self.out);
+        self.search = Some(thread::spawn(move || {
+            let time_source = SystemTimeSource::new();
+            let mut engine = engine.lock().unwrap();
+            let result =
+                engine.search(&game, &go_args.limits, &time_source, &stop, &mut |result| {
+                    send(&out, &info_of(result, time_source.now_ms()))
+                });
+            while go_args.infinite && !stop.load(Ordering::Relaxed) {
+                thread::sleep(Duration::from_millis(5));
+            }
+            let best_move = result.best_move.as_ref().map_or("0000".into(), lan_to_string);
+            send(&out, &format!("bestmove {best_move}"));
+        }));
+    }
+
+    fn set_option(&mut self, args: &[&str]) {
+        let value_index = args.iter().position(|arg| *arg == "value").unwrap_or(args.len());
+        let name = args.get(1..value_index).unwrap_or_default().join(" ");
+        let value = args.get(value_index + 1..).unwrap_or_default().join(" ");
+        self.stop();
+        match name.to_ascii_lowercase().as_str() {
+            "hash" => {
+                if let Ok(mb) = value.parse::<usize>() {
+                    let mut engine = self.engine.lock().unwrap();
+                    let params = engine.params.clone();
+                    let tablebase = mem::take(&mut engine.tablebase);
+                    *engine = Engine::of(tt_size_of_mb(mb.clamp(1, MAX_HASH_MB)));
+                    engine.params = params;
+                    engine.tablebase = tablebase;
+                }
+            }
+            "clear hash" => self.engine.lock().unwrap().clear(),
+            "ownbook" => self.own_book = value == "true",
+            "bookfile" => {
+                match fs::read(&value).ok().and_then(|bytes| PolyglotBook::try_of(&bytes)) {
+                    Some(book) => self.book = book,
+                    None => send(&self.out, &format!("info string could not read book {value}")),
+                }
+            }
+            "syzygypath" => {
+                let (tablebase, unread) = tablebase_of_paths(&value);
+                for dir in unread {
+                    let dir = dir.display();
+                    send(&self.out, &format!("info string could not read tablebases {dir}"));
+                }
+                let len = tablebase.len();
+                send(&self.out, &format!("info string found {len} tablebase files"));
+                self.engine.lock().unwrap().tablebase = tablebase;
+            }
+            _ => send(&self.out, &format!("info string unknown option {name}")),
+        }
+    }
+
+    pub fn handle(&mut self, line: &str) -> bool {
+        let args: Vec<&str> = line.split_whitespace().collect();
+        let Some((command, args)) = args.split_first() else {
+            return true;
+        };
+        match *command {
+            "uci" => {
+                send(&self.out, &format!("id name {ENGINE_NAME}"));
+                send(&self.out, &format!("id author {ENGINE_AUTHOR}"));
+                send(
+                    &self.out,
+                    &format!(
+                        "option name Hash type spin default {DEFAULT_HASH_MB} min 1 max {MAX_HASH_MB}"
+                    ),
+                );
+                send(&self.out, "option name Clear Hash type button");
+                send(&self.out, "option name OwnBook type check default false");
+                send(&self.out, "option name BookFile type string default <empty>");
+                send(&self.out, "option name SyzygyPath type string default <empty>");
+                send(&self.out, "uciok");
+            }
+            "isready" => send(&self.out, "readyok"),
+            "ucinewgame" => {
+                self.stop();
+                self.engine.lock().unwrap().clear();
+                self.game = game_of_mode(standard_chess());
+            }
+            "position" => {
+                self.stop();
+                match position_of(args) {
+                    Some(game) => self.game = game,
+                    None => {
+                        send(&self.out, &format!("info string invalid position {}", args.join(" ")))
+                    }
+                }
+            }
+            "go" => self.go(args),
+            "stop" => self.stop(),
+            "setoption" => self.set_option(args),
+            "quit" => {
+                self.stop();
+                return false;
+            }
+            _ => {}
+        }
+        true
+    }
+
+    pub fn finish(&mut self) {
+        if self.infinite {
+            self.stop();
+        } else {
+            self.wait();
+        }
+    }
+}
+
+pub fn run<R: BufRead>(input: R, out: Output) {
+    let mut uci = Uci::of(out);
+    for line in input.lines() {
+        let Ok(line) = line else {
+            break;
+        };
+        if !uci.handle(&line) {
+            return;
+        }
+    }
+    uci.finish();
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        env, fs,
+        io::Cursor,
+        sync::{Arc, Mutex},
+    };
+
+    use libre_chess_lib::{
+        book::polyglot::PolyglotEntry,
+        color::Color,
+        engine::search::{MATE, SearchLimits},
+        game::{
+            fen::game_of_fen,
+            lan::lan_try_of,
+            mode::standard_chess,
+            rule::{init::game_of_mode, turn::evaluate_turn},
+        },
+        piece::Piece,
+        pos::pos_of,
+    };
+
+    use super::{GoArgs, go_args_of, position_of, run, score_to_string, time_for_move};
+
+    fn run_script(script: &str) -> Vec<String> {
+        let out = Arc::new(Mutex::new(Vec::new()));
+        run(Cursor::new(script.to_string()), out.clone());
+        let bytes = out.lock().unwrap().clone();
+        String::from_utf8(bytes).unwrap().lines().map(String::from).collect()
+    }
+
+    #[test]
+    fn uci_handshake() {
+        let lines = run_script("uci\nisready\nquit\n");
+        assert_eq!(lines.first().map(String::as_str), Some("id name libre_chess"));
+        assert!(lines.contains(&"uciok".to_string()));
+        assert_eq!(lines.last().map(String::as_str), Some("readyok"));
+    }
+
+    #[test]
+    fn uci_position() {
+        let game = position_of(&["startpos", "moves", "e2e4", "e7e5", "g1f3"]).unwrap();
+        let fen = "rnbqkbnr/pppp1ppp/8/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R b KQkq - 1 2";
+        let fen_args: Vec<&str> = ["fen"].into_iter().chain(fen.split(' ')).collect();
+        let fen_game = position_of(&fen_args).unwrap();
+        assert_eq!(game.board, fen_game.board);
+        assert_eq!(evaluate_turn(&game.start, &game.history), Color::Black);
+        assert_eq!(evaluate_turn(&fen_game.start, &fen_game.history), Color::Black);
+        assert_eq!(position_of(&["startpos"]), Some(game_of_mode(standard_chess())));
+        assert_eq!(position_of(&["startpos", "moves", "e2e5"]), None);
+        assert_eq!(position_of(&["nothing"]), None);
+        let promoted =
+            position_of(&["fen", "4k3/2P5/8/8/8/8/8/4K3", "w", "-", "-", "moves", "c7c8n"]);
+        assert_eq!(promoted.unwrap().board.get(&pos_of("C8")), Some(&Piece::of('♘')));
+    }
+
+    #[test]
+    fn uci_go_args() {
+        assert_eq!(
+            go_args_of(&Color::White, &["depth", "3"]),
+            GoArgs {
+                limits: SearchLimits { depth: Some(3), ..Default::default() },
+                infinite: false
+            }
+        );
+        assert_eq!(
+            go_args_of(&Color::Black, &["wtime", "1000", "btime", "30000", "winc", "5"]),
+            GoArgs {
+                limits: SearchLimits { time: Some(1000), ..Default::default() },
+                infinite: false
+            }
+        );
+        assert_eq!(
+            go_args_of(&Color::White, &["movetime", "200", "nodes", "5000"]),
+            GoArgs {
+                limits: SearchLimits {
+                    time: Some(200),
+                    nodes: Some(5000),
+                    depth: None,
+                    mate: None
+                },
+                infinite: false
+            }
+        );
+        assert_eq!(go_args_of(&Color::White, &["mate", "3"]).limits.mate, Some(3));
+        assert!(go_args_of(&Color::White, &["infinite"]).infinite);
+        assert_eq!(time_for_move(60_000, 1_000, None), 2_750);
+        assert_eq!(time_for_move(60_000, 0, Some(1)), 59_950);
+        assert_eq!(time_for_move(10, 0, None), 1);
+    }
+
+    #[test]
+    fn uci_score_to_string() {
+        assert_eq!(score_to_string(35), "cp 35");
+        assert_eq!(score_to_string(MATE - 1), "mate 1");
+        assert_eq!(score_to_string(-(MATE - 2)), "mate -1");
+        assert_eq!(score_to_string(MATE - 3), "mate 2");
+    }
+
+    #[test]
+    fn uci_go_depth() {
+        let lines = run_script("position fen 7k/8/6K1/8/8/8/8/1Q6 w - - 0 1\ngo depth 3\n");
+        assert!(lines.iter().any(|line| line.starts_with("info depth 1 ")));
+        assert_eq!(lines.last().map(String::as_str), Some("bestmove b1b8"));
+    }
+
+    #[test]
+    fn uci_go_infinite_stop() {
+        let lines = run_script("position startpos moves e2e4\ngo infinite\nstop\nisready\n");
+        let bestmove = lines.iter().position(|line| line.starts_with("bestmove "));
+        let readyok = lines.iter().position(|line| line == "readyok");
+        assert!(bestmove.is_some());
+        assert!(bestmove < readyok);
+        let game = game_of_fen("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1");
+        let lan = lines[bestmove.unwrap()].trim_start_matches("bestmove ");
+        assert!(lan_try_of(&game, lan).is_some());
+    }
+
+    #[test]
+    fn uci_own_book() {
+        let entry = PolyglotEntry { key: 0x463B_9618_1691_FC9C, mov: 0x02DB, weight: 1, learn: 0 };
+        let path = env::temp_dir().join("libre_chess_uci_own_book.bin");
+        fs::write(&path, entry.to_bytes()).unwrap();
+        let script = format!(
+            "setoption name BookFile value {}\nsetoption name OwnBook value true\nposition startpos\ngo depth 1\nsetoption name BookFile value {}.nope\n",
+            path.display(),
+            path.display()
+        );
+        let lines = run_script(&script);
+        assert_eq!(
+            lines,
+            vec![
+                "bestmove d2d4".to_string(),
+                format!("info string could not read book {}.nope", path.display())
+            ]
+        );
+    }
+
+    #[test]
+    fn uci_set_option() {
+        let lines =
+            run_script("setoption name Hash value 1\nsetoption name Nope value 2\nisready\n");
+        assert_eq!(lines, vec!["info string unknown option Nope", "readyok"]);
+    }
+
+    #[test]
+    fn uci_set_option_during_infinite_search() {
+        let lines = run_script("go infinite\nsetoption name Hash value 1\nisready\n");
+        let bestmove = lines.iter().position(|line| line.starts_with("bestmove "));
+        let readyok = lines.iter().position(|line| line == "readyok");
+        assert!(bestmove.is_some());
+        assert!(bestmove < readyok);
+    }
+
+    #[test]
+    fn uci_go_mate() {
+        let lines = run_script("position fen 7k/8/8/8/8/8/1R6/R3K3 w - - 0 1\ngo mate 2\n");
+        assert!(lines.iter().any(|line| line.starts_with("info depth 3 score mate 2 ")));
+        assert!(!lines.iter().any(|line| line.starts_with("info depth 4 ")));
+    }
+
+    #[test]
+    fn uci_syzygy_path() {
+        let dir = env::temp_dir().join("libre_chess_uci_syzygy_path");
+        fs::create_dir_all(&dir).unwrap();
+        let script = format!(
+            "setoption name SyzygyPath value {}\nsetoption name SyzygyPath value {}.nope\nsetoption name SyzygyPath value <empty>\n",
+            dir.display(),
+            dir.display()
+        );
+        let lines = run_script(&script);
+        assert_eq!(
+            lines,
+            vec![
+                "info string found 0 tablebase files".to_string(),
+                format!("info string could not read tablebases {}.nope", dir.display()),
+                "info string found 0 tablebase files".to_string(),
+                "info string found 0 tablebase files".to_string(),
+            ]
+        );
+    }
+}
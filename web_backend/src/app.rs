@@ -1,33 +1,100 @@
 use core::f64;
-use std::{cell::RefCell, collections::HashMap};
+use std::{cell::RefCell, collections::HashMap, sync::atomic::AtomicBool};
 use wasm_bindgen::{JsCast, JsValue, prelude::Closure};
 use web_sys::{
     Blob, BlobPropertyBag, CanvasRenderingContext2d, HtmlImageElement, Url, js_sys, window,
 };
 
 use libre_chess_lib::{
+    book::{
+        eco::{EcoOpening, eco_classify},
+        polyglot::{BookMove, PolyglotBook, book_moves},
+    },
+    clock::{Clock, try_get_time_control},
     color::Color,
+    engine::search::{Engine, SearchLimits, SearchResult, is_mate_score},
     game::{
+        capture::{captures_by_type, material_balance},
+        event::{DrawClaim, GameAction, can_claim_draw, pending_draw_offer, play_action},
         game::Game,
+        lan::{lan_promotion_of, lan_try_of},
+        mov::{
+            GameMove, PROMOTIONS, PieceMoveType, is_promotion, promotion_of_game_move_type,
+            promotion_piece_type,
+        },
+        pgn::{pgn_of_with_annotations, pgn_try_of},
+        result::{GameResult, game_result, game_result_with_clock},
         rule::{
-            check::is_in_check, legal_moves::legal_moves_of_player, move_piece::move_piece,
+            check::is_in_check,
+            init::{game_of_mode, game_of_ply},
+            move_piece::play_move_with_promotion,
             turn::evaluate_turn,
         },
-        selection::toggle_selection,
+        san::{san_history_of, san_promotion_of, san_try_of},
+        selection::{
+            AnnotationColor, Selection, clear_annotations, toggle_arrow_annotation,
+            toggle_selection, toggle_square_annotation,
+        },
     },
-    piece::PieceType,
+    piece::{Piece, PieceType},
     pos::Pos,
+    snapshot::{game_to_json, game_try_of_json},
+    time::TimeSource,
 };
 
 use crate::{
-    app_info::AppInfo, board_color::try_get_board_color, board_set::try_get_board_set,
-    model::Model, prop::Prop, render::get_values_to_render,
+    app_info::AppInfo,
+    board_color::{
+        BoardColor, BoardColorPreset, board_color_images, board_color_is_valid,
+        get_board_color_presets, try_get_board_color,
+    },
+    board_set::{
+        BoardSet, BoardSetPreset, board_set_image, board_set_image_is_url, board_set_images,
+        board_set_is_valid, get_board_set_presets, try_get_board_set,
+    },
+    describe::{describe_last_move, describe_position, describe_square},
+    model::{Drag, Model, Premove},
+    prop::Prop,
+    render::{
+        Coordinates, animation_progress, arrow_head, board_rect, border_size, cell_size,
+        coordinate_labels, coordinates_to_string, coordinates_try_of_str, cursor_origin,
+        cursor_step, get_values_to_render, interpolate_rect, last_move_squares, move_animation_of,
+        promotion_squares, square_center, square_of_point, square_rect,
+    },
+    storage::{
+        ANIMATION_KEY, AUTO_QUEEN_KEY, BOARD_COLOR_KEY, BOARD_SET_KEY, COORDINATES_KEY,
+        CUSTOM_BOARD_COLORS_KEY, CUSTOM_BOARD_SETS_KEY, GAME_KEY, storage_get, storage_get_json,
+        storage_set, storage_set_json,
+    },
+    time_source::DateTimeSource,
 };
 
+const ENGINE_TT_SIZE: usize = 1 << 16;
+
+const ENGINE_MOVE_TIME_MS: u64 = 1_000;
+
+const ENGINE_MOVE_DELAY_MS: i32 = 300;
+
 thread_local! {
     static MODEL: RefCell<Model> = RefCell::new(Model::default());
 }
 
+thread_local! {
+    static ENGINE: RefCell<Engine> = RefCell::new(Engine::of(ENGINE_TT_SIZE));
+}
+
+// the engine searches one depth at a time so the page stays responsive between iterations
+struct EngineSearch {
+    id: u32,
+    game: Game,
+    start: u64,
+    result: Option<SearchResult>,
+}
+
+thread_local! {
+    static ENGINE_SEARCH: RefCell<Option<EngineSearch>> = const { RefCell::new(None) };
+}
+
 thread_local! {
     static LISTENERS: RefCell<Vec<Box<dyn FnMut(Prop) + 'static>>> = RefCell::new(Vec::new());
 }
@@ -40,6 +107,9 @@ where
 }
 
 fn on_change(param: Prop) {
+    if param == Prop::Move {
+        app_announce_last_move();
+    }
     LISTENERS.with_borrow_mut(|l| {
         for cb in l.iter_mut() {
             cb(param.clone());
@@ -53,57 +123,96 @@ pub fn app_get_settings() -> AppInfo {
         AppInfo {
             board_set: m.settings.board_set_id.clone(),
             board_color: m.settings.board_color_id.clone(),
+            orientation: m.settings.render_settings.orientation,
+            play_as: m.play_as,
+            auto_queen: m.settings.auto_queen,
+            coordinates: m.settings.render_settings.coordinates,
+            animation_ms: m.settings.animation_ms,
         }
     })
 }
 
+fn app_restore() {
+    MODEL.with(|i| {
+        let mut m = i.borrow_mut();
+        if let Some(custom_board_sets) =
+            storage_get_json::<Vec<(BoardSetPreset, BoardSet)>>(CUSTOM_BOARD_SETS_KEY)
+        {
+            m.custom_board_sets = custom_board_sets
+                .into_iter()
+                .filter(|(_, board_set)| board_set_is_valid(board_set))
+                .collect();
+        }
+        if let Some(custom_board_colors) =
+            storage_get_json::<Vec<(BoardColorPreset, BoardColor)>>(CUSTOM_BOARD_COLORS_KEY)
+        {
+            m.custom_board_colors = custom_board_colors
+                .into_iter()
+                .filter(|(_, board_color)| board_color_is_valid(board_color))
+                .collect();
+        }
+        if let Some(id) = storage_get(BOARD_SET_KEY)
+            && let Some(preset) = app_try_get_board_set(&m, &id)
+        {
+            m.settings.board_set = preset;
+            m.settings.board_set_id = id;
+        }
+        if let Some(id) = storage_get(BOARD_COLOR_KEY)
+            && let Some(preset) = app_try_get_board_color(&m, &id)
+        {
+            m.settings.board_color = preset;
+            m.settings.board_color_id = id;
+        }
+        m.settings.auto_queen = storage_get(AUTO_QUEEN_KEY).as_deref() == Some("true");
+        if let Some(animation_ms) = storage_get(ANIMATION_KEY).and_then(|s| s.parse().ok()) {
+            m.settings.animation_ms = animation_ms;
+        }
+        if let Some(coordinates) =
+            storage_get(COORDINATES_KEY).and_then(|s| coordinates_try_of_str(&s))
+        {
+            m.settings.render_settings.coordinates = coordinates;
+        }
+        if let Some(game) = storage_get(GAME_KEY).and_then(|json| game_try_of_json(&json)) {
+            m.game = game;
+        }
+    });
+}
+
+fn app_save() {
+    MODEL.with(|i| {
+        let m = i.borrow();
+        storage_set(BOARD_SET_KEY, &m.settings.board_set_id);
+        storage_set(BOARD_COLOR_KEY, &m.settings.board_color_id);
+        storage_set(AUTO_QUEEN_KEY, if m.settings.auto_queen { "true" } else { "false" });
+        storage_set(ANIMATION_KEY, &m.settings.animation_ms.to_string());
+        storage_set(
+            COORDINATES_KEY,
+            coordinates_to_string(&m.settings.render_settings.coordinates),
+        );
+        if let Some(json) = game_to_json(&m.game) {
+            storage_set(GAME_KEY, &json);
+        }
+    });
+}
+
 pub fn app_init(context: CanvasRenderingContext2d) {
+    app_restore();
     MODEL.with(|i| {
         let mut model = i.borrow_mut();
         model.context = Some(context);
-        let strs = [
-            ("bb", model.settings.board_set.bb),
-            ("bk", model.settings.board_set.bk),
-            ("bn", model.settings.board_set.bn),
-            ("bp", model.settings.board_set.bp),
-            ("bq", model.settings.board_set.bq),
-            ("br", model.settings.board_set.br),
-            ("wb", model.settings.board_set.wb),
-            ("wk", model.settings.board_set.wk),
-            ("wn", model.settings.board_set.wn),
-            ("wp", model.settings.board_set.wp),
-            ("wq", model.settings.board_set.wq),
-            ("wr", model.settings.board_set.wr),
-        ];
-        for (name, str_img) in strs {
-            let maybe_blob = Blob::new_with_str_sequence_and_options(
-                &js_sys::Array::of1(&JsValue::from_str(str_img)),
-                BlobPropertyBag::new().type_("image/svg+xml"),
-            );
-            if let Ok(blob) = maybe_blob {
-                let maybe_url = Url::create_object_url_with_blob(&blob);
-                if let Ok(url) = maybe_url {
-                    let maybe_image_element = HtmlImageElement::new();
-                    if let Ok(image_element) = maybe_image_element {
-                        image_element.set_src(&url);
-                        let closure = Closure::wrap(Box::new({
-                            move || {
-                                app_render();
-                            }
-                        }) as Box<dyn FnMut()>);
-                        image_element.set_onload(Some(closure.as_ref().unchecked_ref()));
-                        closure.forget();
-                        model.board_set.insert(name.into(), image_element);
-                    }
-                }
-            }
-        }
+        app_load_board_set_images(&mut model);
+        app_load_board_color_images(&mut model);
     });
     app_add_on_change_listener({
         move |prop| {
+            if prop == Prop::Move {
+                app_start_animation();
+            }
             app_render();
         }
     });
+    app_add_on_change_listener(|_| app_save());
+    on_change(Prop::BoardSet);
 }
 
 pub fn app_set_dim(dim: u16) {
@@ -114,121 +223,335 @@ pub fn app_set_dim(dim: u16) {
     on_change(Prop::Dim);
 }
 
+fn image_element_of(image: &str) -> Option<HtmlImageElement> {
+    let url = if board_set_image_is_url(image) {
+        Some(image.to_string())
+    } else {
+        Blob::new_with_str_sequence_and_options(
+            &js_sys::Array::of1(&JsValue::from_str(image)),
+            BlobPropertyBag::new().type_("image/svg+xml"),
+        )
+        .ok()
+        .and_then(|blob| Url::create_object_url_with_blob(&blob).ok())
+    }?;
+    let image_element = HtmlImageElement::new().ok()?;
+    image_element.set_src(&url);
+    let closure = Closure::wrap(Box::new({
+        move || {
+            app_render();
+        }
+    }) as Box<dyn FnMut()>);
+    image_element.set_onload(Some(closure.as_ref().unchecked_ref()));
+    closure.forget();
+    Some(image_element)
+}
+
+fn app_load_board_set_images(m: &mut Model) {
+    for (name, image) in board_set_images(&m.settings.board_set) {
+        if let Some(image_element) = image_element_of(image) {
+            m.board_set.insert(name.into(), image_element);
+        }
+    }
+}
+
+fn app_load_board_color_images(m: &mut Model) {
+    m.board_textures.clear();
+    for (name, image) in board_color_images(&m.settings.board_color) {
+        if let Some(image_element) = image_element_of(image) {
+            m.board_textures.insert(name.into(), image_element);
+        }
+    }
+}
+
+fn app_try_get_board_set(m: &Model, id: &str) -> Option<BoardSet> {
+    m.custom_board_sets
+        .iter()
+        .find(|(preset, _)| preset.id == id)
+        .map(|(_, board_set)| board_set.clone())
+        .or_else(|| try_get_board_set(id))
+}
+
+fn app_try_get_board_color(m: &Model, id: &str) -> Option<BoardColor> {
+    m.custom_board_colors
+        .iter()
+        .find(|(preset, _)| preset.id == id)
+        .map(|(_, board_color)| board_color.clone())
+        .or_else(|| try_get_board_color(id))
+}
+
+pub fn app_get_board_set_presets() -> Vec<BoardSetPreset> {
+    MODEL.with(|i| {
+        get_board_set_presets()
+            .into_iter()
+            .chain(i.borrow().custom_board_sets.iter().map(|(preset, _)| preset.clone()))
+            .collect()
+    })
+}
+
+pub fn app_get_board_color_presets() -> Vec<BoardColorPreset> {
+    MODEL.with(|i| {
+        get_board_color_presets()
+            .into_iter()
+            .chain(i.borrow().custom_board_colors.iter().map(|(preset, _)| preset.clone()))
+            .collect()
+    })
+}
+
 pub fn app_set_board_color(board_color: &str) {
-    if let Some(preset) = try_get_board_color(board_color) {
-        MODEL.with(|i| {
-            let mut m = i.borrow_mut();
+    let found = MODEL.with(|i| {
+        let mut m = i.borrow_mut();
+        if let Some(preset) = app_try_get_board_color(&m, board_color) {
             m.settings.board_color = preset;
             m.settings.board_color_id = board_color.into();
-        });
+            app_load_board_color_images(&mut m);
+            true
+        } else {
+            false
+        }
+    });
+    if found {
         on_change(Prop::BoardColor);
     }
 }
 
 pub fn app_set_board_set(board_set: &str) {
-    if let Some(preset) = try_get_board_set(board_set) {
-        MODEL.with(|i| {
-            let mut m = i.borrow_mut();
+    let found = MODEL.with(|i| {
+        let mut m = i.borrow_mut();
+        if let Some(preset) = app_try_get_board_set(&m, board_set) {
             m.settings.board_set = preset;
             m.settings.board_set_id = board_set.into();
-            let strs = [
-                ("bb", m.settings.board_set.bb),
-                ("bk", m.settings.board_set.bk),
-                ("bn", m.settings.board_set.bn),
-                ("bp", m.settings.board_set.bp),
-                ("bq", m.settings.board_set.bq),
-                ("br", m.settings.board_set.br),
-                ("wb", m.settings.board_set.wb),
-                ("wk", m.settings.board_set.wk),
-                ("wn", m.settings.board_set.wn),
-                ("wp", m.settings.board_set.wp),
-                ("wq", m.settings.board_set.wq),
-                ("wr", m.settings.board_set.wr),
-            ];
-            for (name, str_img) in strs {
-                let maybe_blob = Blob::new_with_str_sequence_and_options(
-                    &js_sys::Array::of1(&JsValue::from_str(str_img)),
-                    BlobPropertyBag::new().type_("image/svg+xml"),
-                );
-                if let Ok(blob) = maybe_blob {
-                    let maybe_url = Url::create_object_url_with_blob(&blob);
-                    if let Ok(url) = maybe_url {
-                        let maybe_image_element = HtmlImageElement::new();
-                        if let Ok(image_element) = maybe_image_element {
-                            image_element.set_src(&url);
-                            let closure = Closure::wrap(Box::new({
-                                move || {
-                                    app_render();
-                                }
-                            })
-                                as Box<dyn FnMut()>);
-                            image_element.set_onload(Some(closure.as_ref().unchecked_ref()));
-                            closure.forget();
-                            m.board_set.insert(name.into(), image_element);
-                        }
-                    }
-                }
-            }
-        });
+            app_load_board_set_images(&mut m);
+            true
+        } else {
+            false
+        }
+    });
+    if found {
         on_change(Prop::BoardSet);
     }
 }
 
+pub fn app_register_board_set(id: &str, name: &str, board_set: BoardSet) -> bool {
+    if id.is_empty()
+        || name.is_empty()
+        || try_get_board_set(id).is_some()
+        || !board_set_is_valid(&board_set)
+    {
+        return false;
+    }
+    let selected = MODEL.with(|i| {
+        let mut m = i.borrow_mut();
+        m.custom_board_sets.retain(|(preset, _)| preset.id != id);
+        m.custom_board_sets.push((BoardSetPreset { id: id.into(), name: name.into() }, board_set));
+        storage_set_json(CUSTOM_BOARD_SETS_KEY, &m.custom_board_sets);
+        m.settings.board_set_id == id
+    });
+    if selected {
+        app_set_board_set(id);
+    }
+    true
+}
+
+pub fn app_register_board_color(id: &str, name: &str, board_color: BoardColor) -> bool {
+    if id.is_empty()
+        || name.is_empty()
+        || try_get_board_color(id).is_some()
+        || !board_color_is_valid(&board_color)
+    {
+        return false;
+    }
+    let selected = MODEL.with(|i| {
+        let mut m = i.borrow_mut();
+        m.custom_board_colors.retain(|(preset, _)| preset.id != id);
+        m.custom_board_colors
+            .push((BoardColorPreset { id: id.into(), name: name.into() }, board_color));
+        storage_set_json(CUSTOM_BOARD_COLORS_KEY, &m.custom_board_colors);
+        m.settings.board_color_id == id
+    });
+    if selected {
+        app_set_board_color(id);
+    }
+    true
+}
+
+pub fn app_load_book(bytes: &[u8]) -> bool {
+    if let Some(book) = PolyglotBook::try_of(bytes) {
+        MODEL.with(|i| i.borrow_mut().book = book);
+        on_change(Prop::Book);
+        true
+    } else {
+        false
+    }
+}
+
+pub fn app_get_book_moves() -> Vec<BookMove> {
+    MODEL.with(|i| {
+        let m = i.borrow();
+        book_moves(&m.book, &m.game)
+    })
+}
+
+pub fn app_get_opening() -> Option<&'static EcoOpening> {
+    MODEL.with(|i| eco_classify(&i.borrow().game))
+}
+
+fn piece_image_name(piece: &Piece) -> &'static str {
+    match piece.color {
+        Color::White => match piece.typ {
+            PieceType::Rook => "wr",
+            PieceType::Knight => "wn",
+            PieceType::Bishop => "wb",
+            PieceType::Queen => "wq",
+            PieceType::King => "wk",
+            PieceType::Pawn => "wp",
+        },
+        Color::Black => match piece.typ {
+            PieceType::Rook => "br",
+            PieceType::Knight => "bn",
+            PieceType::Bishop => "bb",
+            PieceType::Queen => "bq",
+            PieceType::King => "bk",
+            PieceType::Pawn => "bp",
+        },
+    }
+}
+
+fn annotation_color_to_css(color: &AnnotationColor) -> &'static str {
+    match color {
+        AnnotationColor::Green => "#15781baa",
+        AnnotationColor::Red => "#882020aa",
+        AnnotationColor::Yellow => "#e68f00aa",
+        AnnotationColor::Blue => "#003088aa",
+    }
+}
+
 // when iniciating the app, must cache the piece image, so it does not flicker
 pub fn app_render() {
     MODEL.with(|i| {
         let m = i.borrow();
-        let board = &m.game.board;
-        let bounds = &m.game.bounds;
-        let players = &m.game.players;
+        let game = m.view.as_ref().map_or(&m.game, |(_, game)| game);
+        let board = &game.board;
+        let bounds = &game.bounds;
+        let players = &game.players;
         let settings = &m.settings;
         let selection = &m.selection;
         let context = &m.context;
         let board_set = &m.board_set;
-        let turn = evaluate_turn(&m.game.history);
-        let in_check = is_in_check(&m.game.board, &m.game.players, &m.game.history);
+        let turn = evaluate_turn(&game.start, &game.history);
+        let in_check = is_in_check(&game.board, &game.players, &game.start, &game.history);
         if let Some(context) = context {
-            let dimmm = settings.render_settings.dim as f64;
-            let cell_size = dimmm / 8.0;
-            let mut acc = 0;
+            let dim = settings.render_settings.dim as f64;
+            let cell_size = cell_size(bounds, &settings.render_settings);
+            context.clear_rect(0.0, 0.0, dim, dim);
+            if settings.render_settings.coordinates == Coordinates::Outside {
+                context.set_fill_style_str(&settings.board_color.border);
+                context.fill_rect(0.0, 0.0, dim, dim);
+            }
+            let loaded_texture = |name: &str| {
+                m.board_textures.get(name).filter(|image_element| {
+                    image_element.complete() && image_element.natural_width() > 0
+                })
+            };
+            let pattern_of = |name: &str| {
+                loaded_texture(name).and_then(|image_element| {
+                    context
+                        .create_pattern_with_html_image_element(image_element, "repeat")
+                        .ok()
+                        .flatten()
+                })
+            };
+            let dark_pattern = pattern_of("dark");
+            let light_pattern = pattern_of("light");
             for row in bounds.iter_row() {
-                acc += 1;
                 for col in bounds.iter_col() {
-                    if acc % 2 == 0 {
-                        context.set_fill_style(&settings.board_color.dark.into());
+                    let (color, pattern) = if (row + col) % 2 == 0 {
+                        (&settings.board_color.dark, &dark_pattern)
                     } else {
-                        context.set_fill_style(&settings.board_color.light.into());
+                        (&settings.board_color.light, &light_pattern)
+                    };
+                    let rect = square_rect(&Pos { row, col }, bounds, &settings.render_settings);
+                    context.set_fill_style_str(color);
+                    context.fill_rect(rect.min.x, rect.min.y, cell_size, cell_size);
+                    if let Some(pattern) = pattern {
+                        context.set_fill_style_canvas_pattern(pattern);
+                        context.fill_rect(rect.min.x, rect.min.y, cell_size, cell_size);
                     }
-                    acc += 1;
-                    context.fill_rect(
-                        col as f64 * cell_size,
-                        row as f64 * cell_size,
-                        cell_size,
-                        cell_size,
-                    );
                 }
             }
-            let values_to_render = get_values_to_render(board, &bounds, &settings.render_settings);
-            let window = window().unwrap();
-            for v in values_to_render {
-                let piece_str_name = match v.piece.color {
-                    Color::White => match v.piece.typ {
-                        PieceType::Rook => "wr",
-                        PieceType::Knight => "wn",
-                        PieceType::Bishop => "wb",
-                        PieceType::Queen => "wq",
-                        PieceType::King => "wk",
-                        PieceType::Pawn => "wp",
-                    },
-                    Color::Black => match v.piece.typ {
-                        PieceType::Rook => "br",
-                        PieceType::Knight => "bn",
-                        PieceType::Bishop => "bb",
-                        PieceType::Queen => "bq",
-                        PieceType::King => "bk",
-                        PieceType::Pawn => "bp",
-                    },
+            if let Some(background) = loaded_texture("background") {
+                let rect = board_rect(bounds, &settings.render_settings);
+                let _ = context.draw_image_with_html_image_element_and_dw_and_dh(
+                    background,
+                    rect.min.x,
+                    rect.min.y,
+                    rect.max.x - rect.min.x,
+                    rect.max.y - rect.min.y,
+                );
+            }
+            if let Some((from, to)) = last_move_squares(&game.history) {
+                context.set_fill_style_str(&settings.board_color.last_move);
+                for pos in [from, to] {
+                    let rect = square_rect(&pos, bounds, &settings.render_settings);
+                    context.fill_rect(rect.min.x, rect.min.y, cell_size, cell_size);
+                }
+            }
+            if let Some(premove) = &m.premove {
+                context.set_fill_style_str(&settings.board_color.premove);
+                for pos in [Some(&premove.from), premove.to.as_ref()].into_iter().flatten() {
+                    let rect = square_rect(pos, bounds, &settings.render_settings);
+                    context.fill_rect(rect.min.x, rect.min.y, cell_size, cell_size);
+                }
+            }
+            if let Some(pos) = &selection.selected_pos {
+                context.set_fill_style_str(&settings.board_color.selected);
+                let rect = square_rect(pos, bounds, &settings.render_settings);
+                context.fill_rect(rect.min.x, rect.min.y, cell_size, cell_size);
+            }
+            for (pos, color) in selection.selected_squares.iter() {
+                context.set_fill_style_str(annotation_color_to_css(color));
+                let rect = square_rect(pos, bounds, &settings.render_settings);
+                context.fill_rect(rect.min.x, rect.min.y, cell_size, cell_size);
+            }
+            let font_size = match settings.render_settings.coordinates {
+                Coordinates::Outside => border_size(bounds, &settings.render_settings) * 0.6,
+                _ => cell_size / 5.0,
+            };
+            context.set_font(&format!("{font_size}px sans-serif"));
+            for label in coordinate_labels(bounds, &settings.render_settings) {
+                let color = match settings.render_settings.coordinates {
+                    Coordinates::Outside => &settings.board_color.coordinate,
+                    _ if (label.pos.row + label.pos.col) % 2 == 0 => &settings.board_color.light,
+                    _ => &settings.board_color.dark,
                 };
+                context.set_fill_style_str(color);
+                context.set_text_align(label.align);
+                context.set_text_baseline(label.baseline);
+                let _ = context.fill_text(&label.text, label.x, label.y);
+            }
+            let values_to_render = get_values_to_render(board, bounds, &settings.render_settings);
+            let window = window().unwrap();
+            let drag_rect = m
+                .drag
+                .as_ref()
+                .map(|drag| square_rect(&drag.from, bounds, &settings.render_settings));
+            let animation = m.animation.as_ref().filter(|_| m.view.is_none()).map(|(a, start)| {
+                (
+                    a,
+                    animation_progress(
+                        js_sys::Date::now() - start,
+                        f64::from(settings.animation_ms),
+                    ),
+                )
+            });
+            let animated_rects: Vec<_> = animation
+                .iter()
+                .flat_map(|(a, _)| a.paths.iter())
+                .map(|(_, to)| square_rect(to, bounds, &settings.render_settings))
+                .collect();
+            for v in values_to_render.iter().filter(|v| {
+                Some(&v.rect) != drag_rect.as_ref() && !animated_rects.contains(&v.rect)
+            }) {
+                let piece_str_name = piece_image_name(&v.piece);
                 if in_check && v.piece.typ == PieceType::King && v.piece.color == turn {
                     let grid_horizontal = context.create_linear_gradient(
                         v.rect.min.x,
@@ -236,10 +559,10 @@ pub fn app_render() {
                         v.rect.max.x,
                         v.rect.min.y,
                     );
-                    grid_horizontal.add_color_stop(0.0, "#D20103");
+                    grid_horizontal.add_color_stop(0.0, &settings.board_color.check);
                     grid_horizontal.add_color_stop(0.1, "transparent");
                     grid_horizontal.add_color_stop(0.9, "transparent");
-                    grid_horizontal.add_color_stop(1.0, "#D20103");
+                    grid_horizontal.add_color_stop(1.0, &settings.board_color.check);
 
                     let grid_vertical = context.create_linear_gradient(
                         v.rect.min.x,
@@ -247,10 +570,10 @@ pub fn app_render() {
                         v.rect.min.x,
                         v.rect.max.y,
                     );
-                    grid_vertical.add_color_stop(0.0, "#D20103");
+                    grid_vertical.add_color_stop(0.0, &settings.board_color.check);
                     grid_vertical.add_color_stop(0.1, "transparent");
                     grid_vertical.add_color_stop(0.9, "transparent");
-                    grid_vertical.add_color_stop(1.0, "#D20103");
+                    grid_vertical.add_color_stop(1.0, &settings.board_color.check);
 
                     context.set_fill_style(&grid_horizontal.into());
                     context.fill_rect(
@@ -267,7 +590,7 @@ pub fn app_render() {
                         v.rect.max.y - v.rect.min.y,
                     );
                 }
-                if let Some(html_el) = board_set.get(&piece_str_name.to_string()) {
+                if let Some(html_el) = board_set.get(piece_str_name) {
                     context
                         .draw_image_with_html_image_element_and_dw_and_dh(
                             html_el,
@@ -279,70 +602,906 @@ pub fn app_render() {
                         .unwrap()
                 }
             }
-            if !selection.selected_squares.is_empty() {
-                context.set_fill_style(&"#f0ec0088".into());
-                selection.selected_squares.iter().for_each(|pos| {
-                    context.fill_rect(
-                        pos.col as f64 * cell_size,
-                        (settings.render_settings.dim as f64)
-                            - (pos.row as f64) * cell_size
-                            - cell_size,
-                        cell_size,
-                        cell_size,
+            if let Some((a, t)) = animation {
+                if let Some((piece, pos)) = &a.captured
+                    && let Some(html_el) = board_set.get(piece_image_name(piece))
+                {
+                    let rect = square_rect(pos, bounds, &settings.render_settings);
+                    context.set_global_alpha(1.0 - t);
+                    let _ = context.draw_image_with_html_image_element_and_dw_and_dh(
+                        html_el, rect.min.x, rect.min.y, cell_size, cell_size,
                     );
-                })
+                    context.set_global_alpha(1.0);
+                }
+                for (from, to) in a.paths.iter() {
+                    if let Some(piece) = board.get(to)
+                        && let Some(html_el) = board_set.get(piece_image_name(piece))
+                    {
+                        let rect = interpolate_rect(
+                            &square_rect(from, bounds, &settings.render_settings),
+                            &square_rect(to, bounds, &settings.render_settings),
+                            t,
+                        );
+                        let _ = context.draw_image_with_html_image_element_and_dw_and_dh(
+                            html_el, rect.min.x, rect.min.y, cell_size, cell_size,
+                        );
+                    }
+                }
             }
             if let Some(from) = &selection.selected_pos {
                 if let Some(selected_piece) = board.get(from) {
                     if let Some(player) = players.get(&selected_piece.color) {
                         if let Some(piece_moves) = player.moves.get(from) {
-                            for to in piece_moves.keys() {
-                                context.set_fill_style(&"#09056b88".into());
+                            for (to, typ) in piece_moves.iter() {
+                                let rect = square_rect(to, bounds, &settings.render_settings);
+                                let is_capture = typ == &PieceMoveType::EnPassant
+                                    || board
+                                        .get(to)
+                                        .is_some_and(|piece| piece.color != selected_piece.color);
                                 context.begin_path();
-                                let _ = context.arc(
-                                    to.col as f64 * cell_size + cell_size / 2.0,
-                                    ((settings.render_settings.dim as f64)
-                                        - (to.row as f64) * cell_size
-                                        - cell_size)
-                                        + cell_size / 2.0,
-                                    cell_size / (2.0 * f64::consts::PI),
-                                    0.0,
-                                    2.0 * f64::consts::PI,
-                                );
-                                context.fill();
+                                if is_capture {
+                                    let line_width = cell_size / 12.0;
+                                    context
+                                        .set_stroke_style_str(&settings.board_color.capture_ring);
+                                    context.set_line_width(line_width);
+                                    let _ = context.arc(
+                                        rect.min.x + cell_size / 2.0,
+                                        rect.min.y + cell_size / 2.0,
+                                        (cell_size - line_width) / 2.0,
+                                        0.0,
+                                        2.0 * f64::consts::PI,
+                                    );
+                                    context.stroke();
+                                } else {
+                                    context.set_fill_style_str(&settings.board_color.move_dot);
+                                    let _ = context.arc(
+                                        rect.min.x + cell_size / 2.0,
+                                        rect.min.y + cell_size / 2.0,
+                                        cell_size / (2.0 * f64::consts::PI),
+                                        0.0,
+                                        2.0 * f64::consts::PI,
+                                    );
+                                    context.fill();
+                                }
                             }
                         }
                     }
                 }
             }
+            for ((from, to), color) in selection.arrows.iter() {
+                let start = square_center(from, bounds, &settings.render_settings);
+                let end = square_center(to, bounds, &settings.render_settings);
+                let head = arrow_head(start, end, cell_size / 3.0);
+                let css = annotation_color_to_css(color);
+                context.set_stroke_style_str(css);
+                context.set_line_width(cell_size / 6.0);
+                context.begin_path();
+                context.move_to(start.0, start.1);
+                context.line_to((head[1].0 + head[2].0) / 2.0, (head[1].1 + head[2].1) / 2.0);
+                context.stroke();
+                context.set_fill_style_str(css);
+                context.begin_path();
+                for (i, (x, y)) in head.into_iter().enumerate() {
+                    if i == 0 { context.move_to(x, y) } else { context.line_to(x, y) }
+                }
+                context.close_path();
+                context.fill();
+            }
+            if let Some((from, to)) = &m.promotion {
+                let color = board.get(from).map_or(turn, |piece| piece.color);
+                context.set_fill_style_str("#00000088");
+                context.fill_rect(0.0, 0.0, dim, dim);
+                let squares = promotion_squares(to, bounds, PROMOTIONS.len() as u8);
+                for (pos, promotion) in squares.iter().zip(PROMOTIONS.iter()) {
+                    let rect = square_rect(pos, bounds, &settings.render_settings);
+                    context.set_fill_style_str(&settings.board_color.light);
+                    context.fill_rect(rect.min.x, rect.min.y, cell_size, cell_size);
+                    if let Some(typ) = promotion_piece_type(promotion)
+                        && let Some(html_el) =
+                            board_set.get(piece_image_name(&Piece { typ, color }))
+                    {
+                        let _ = context.draw_image_with_html_image_element_and_dw_and_dh(
+                            html_el, rect.min.x, rect.min.y, cell_size, cell_size,
+                        );
+                    }
+                }
+            }
+            if let Some(drag) = &m.drag
+                && let Some(piece) = board.get(&drag.from)
+                && let Some(html_el) = board_set.get(piece_image_name(piece))
+            {
+                let _ = context.draw_image_with_html_image_element_and_dw_and_dh(
+                    html_el,
+                    drag.x - cell_size / 2.0,
+                    drag.y - cell_size / 2.0,
+                    cell_size,
+                    cell_size,
+                );
+            }
+            if let Some(cursor) = &m.cursor {
+                let rect = square_rect(cursor, bounds, &settings.render_settings);
+                let line_width = cell_size / 16.0;
+                context.set_stroke_style_str(&settings.board_color.selected);
+                context.set_line_width(line_width);
+                context.stroke_rect(
+                    rect.min.x + line_width / 2.0,
+                    rect.min.y + line_width / 2.0,
+                    cell_size - line_width,
+                    cell_size - line_width,
+                );
+            }
         }
     });
 }
 
+fn app_can_move() -> bool {
+    app_get_result().is_none() && app_get_view_ply().is_none() && !app_is_engine_turn()
+}
+
+fn app_can_premove() -> bool {
+    app_get_result().is_none() && app_get_view_ply().is_none() && app_is_engine_turn()
+}
+
+fn app_premove_click(pos: Pos) {
+    MODEL.with(|i| {
+        let mut m = i.borrow_mut();
+        let Some(color) = m.play_as else {
+            return;
+        };
+        if let Some((_, to)) = m.promotion.take() {
+            let squares = promotion_squares(&to, &m.game.bounds, PROMOTIONS.len() as u8);
+            let promotion = squares.iter().position(|square| square == &pos);
+            match (promotion, &mut m.premove) {
+                (Some(i), Some(premove)) => premove.promotion = PROMOTIONS.get(i).cloned(),
+                _ => m.premove = None,
+            }
+            return;
+        }
+        let is_own = m.game.board.get(&pos).is_some_and(|piece| piece.color == color);
+        m.premove = match m.premove.take() {
+            Some(Premove { from, to: None, .. }) if from == pos => None,
+            Some(Premove { from, to: None, .. }) if !is_own => {
+                let last_row = match color {
+                    Color::White => m.game.bounds.max.row,
+                    Color::Black => m.game.bounds.min.row,
+                };
+                let is_promotion = pos.row == last_row
+                    && m.game.board.get(&from).is_some_and(|piece| piece.typ == PieceType::Pawn);
+                let promotion = match (is_promotion, m.settings.auto_queen) {
+                    (true, true) => Some(PieceMoveType::PromotionToQueen),
+                    (true, false) => {
+                        m.promotion = Some((from.clone(), pos.clone()));
+                        None
+                    }
+                    (false, _) => None,
+                };
+                Some(Premove { from, to: Some(pos), promotion })
+            }
+            _ => is_own.then_some(Premove { from: pos, to: None, promotion: None }),
+        };
+    });
+    on_change(Prop::Selection);
+}
+
 pub fn app_click(row: u16, col: u16) {
-    let dim = MODEL.with(|m| m.borrow().settings.render_settings.dim as f64);
+    let Some(pos) = MODEL.with(|i| {
+        let m = i.borrow();
+        square_of_point(row, col, &m.game.bounds, &m.settings.render_settings)
+    }) else {
+        return;
+    };
+    app_select_square(pos);
+}
+
+fn app_select_square(pos: Pos) {
     let bounds = MODEL.with(|m| m.borrow().game.bounds.clone());
+    if app_can_premove() {
+        app_premove_click(pos);
+        return;
+    }
+    if !app_can_move() {
+        return;
+    }
+    if let Some((from, to)) = MODEL.with(|i| i.borrow_mut().promotion.take()) {
+        let squares = promotion_squares(&to, &bounds, PROMOTIONS.len() as u8);
+        match squares.iter().position(|square| square == &pos) {
+            Some(i) => app_play_move(&from, &to, PROMOTIONS.get(i)),
+            None => on_change(Prop::Selection),
+        }
+        return;
+    }
+    let selected_move = MODEL.with(|i| {
+        let m = i.borrow();
+        let turn = evaluate_turn(&m.game.start, &m.game.history);
+        let from = m.selection.selected_pos.clone()?;
+        let typ = m.game.players.get(&turn)?.moves.get(&from)?.get(&pos)?;
+        Some((from, is_promotion(typ) && !m.settings.auto_queen))
+    });
+    match selected_move {
+        Some((from, true)) => {
+            MODEL.with(|i| i.borrow_mut().promotion = Some((from, pos)));
+            on_change(Prop::Selection);
+        }
+        Some((from, false)) => app_play_move(&from, &pos, None),
+        None => {
+            MODEL.with(|i| {
+                let mut m = i.borrow_mut();
+                let Model { game, selection, .. } = &mut *m;
+                let Game { board, players, start, history, .. } = game;
+                toggle_selection(selection, board, players, start, history, pos);
+            });
+            on_change(Prop::Selection);
+        }
+    }
+}
 
+pub fn app_pointer_down(row: u16, col: u16) {
+    if !app_can_move() {
+        return;
+    }
     MODEL.with(|i| {
         let mut m = i.borrow_mut();
-        let Model { game: Game { board, players, history, .. }, selection, .. } = &mut *m;
-        let cell_size = dim / 8.0;
-        let cell_row = (8 - ((((row as f64) / cell_size).floor() as u8) as i16)) as u8 - 1;
-        let cell_col = ((col as f64) / cell_size).floor() as u8;
-        let pos = Pos { row: cell_row, col: cell_col };
-        move_piece(board, history, players, &bounds, selection, &pos);
-        toggle_selection(selection, board, players, history, pos);
-        let mut tempmoves = HashMap::new();
-        let players_temp = players.clone();
-        for color in players_temp.keys() {
-            tempmoves.insert(
-                color,
-                legal_moves_of_player(board, &bounds, history, &players_temp, color),
-            );
-        }
-        for (color, moves) in tempmoves {
-            players.get_mut(color).unwrap().moves = moves;
+        let Some(from) = square_of_point(row, col, &m.game.bounds, &m.settings.render_settings)
+        else {
+            return;
+        };
+        let turn = evaluate_turn(&m.game.start, &m.game.history);
+        let can_drag =
+            m.game.players.get(&turn).is_some_and(|player| {
+                player.moves.get(&from).is_some_and(|moves| !moves.is_empty())
+            });
+        if can_drag {
+            m.drag = Some(Drag { from, x: f64::from(col), y: f64::from(row) });
         }
     });
-    on_change(Prop::BoardSet);
+}
+
+pub fn app_pointer_move(row: u16, col: u16) {
+    let dragging = MODEL.with(|i| {
+        let mut m = i.borrow_mut();
+        let Some(drag) = &mut m.drag else {
+            return false;
+        };
+        drag.x = f64::from(col);
+        drag.y = f64::from(row);
+        true
+    });
+    if dragging {
+        app_render();
+    }
+}
+
+pub fn app_pointer_cancel() {
+    let cancelled = MODEL.with(|i| {
+        let mut m = i.borrow_mut();
+        let drag = m.drag.take();
+        let annotation_from = m.annotation_from.take();
+        drag.is_some() || annotation_from.is_some()
+    });
+    if cancelled {
+        app_render();
+    }
+}
+
+pub fn app_pointer_up(row: u16, col: u16) {
+    let drop = MODEL.with(|i| {
+        let mut m = i.borrow_mut();
+        let drag = m.drag.take()?;
+        let to = square_of_point(row, col, &m.game.bounds, &m.settings.render_settings);
+        if to.as_ref() == Some(&drag.from) {
+            return None;
+        }
+        let turn = evaluate_turn(&m.game.start, &m.game.history);
+        let is_legal = to.as_ref().is_some_and(|to| {
+            m.game.players.get(&turn).is_some_and(|player| {
+                player.moves.get(&drag.from).is_some_and(|moves| moves.contains_key(to))
+            })
+        });
+        m.selection.selected_pos = Some(drag.from);
+        clear_annotations(&mut m.selection);
+        Some(is_legal)
+    });
+    match drop {
+        Some(false) => on_change(Prop::Selection),
+        Some(true) => {
+            app_click(row, col);
+            app_stop_animation();
+        }
+        None => app_click(row, col),
+    }
+}
+
+pub fn app_move_cursor(dx: i8, dy: i8) {
+    MODEL.with(|i| {
+        let mut m = i.borrow_mut();
+        let orientation = m.settings.render_settings.orientation;
+        let cursor = match &m.cursor {
+            Some(cursor) => cursor_step(cursor, dx, dy, &m.game.bounds, &orientation),
+            None => cursor_origin(&m.game.bounds, &orientation),
+        };
+        m.announcement = describe_square(&m.game.board, &cursor);
+        m.cursor = Some(cursor);
+    });
+    on_change(Prop::Cursor);
+}
+
+pub fn app_cursor_select() {
+    let Some((pos, history_len)) = MODEL.with(|i| {
+        let m = i.borrow();
+        m.cursor.clone().map(|pos| (pos, m.game.history.len()))
+    }) else {
+        return;
+    };
+    app_select_square(pos);
+    let announced = MODEL.with(|i| {
+        let mut m = i.borrow_mut();
+        if m.game.history.len() != history_len {
+            return false;
+        }
+        m.announcement = match (&m.promotion, &m.selection.selected_pos) {
+            (Some(_), _) => "Choose a promotion piece".into(),
+            (None, Some(selected)) => {
+                format!("Selected {}", describe_square(&m.game.board, selected))
+            }
+            (None, None) => "Selection cleared".into(),
+        };
+        true
+    });
+    if announced {
+        on_change(Prop::Cursor);
+    }
+}
+
+pub fn app_cancel_selection() {
+    MODEL.with(|i| {
+        let mut m = i.borrow_mut();
+        m.selection.selected_pos = None;
+        m.promotion = None;
+        m.premove = None;
+        m.announcement = "Selection cleared".into();
+    });
+    on_change(Prop::Selection);
+}
+
+pub fn app_enter_move(text: &str) -> bool {
+    let text = text.trim();
+    if !app_can_move() {
+        return false;
+    }
+    let mov = MODEL.with(|i| {
+        let game = &i.borrow().game;
+        san_try_of(game, text).map(|(from, to)| (from, to, san_promotion_of(text))).or_else(|| {
+            let lan = text.to_ascii_lowercase();
+            lan_try_of(game, &lan).map(|(from, to)| (from, to, lan_promotion_of(&lan)))
+        })
+    });
+    match mov {
+        Some((from, to, promotion)) => {
+            app_play_move(&from, &to, promotion.as_ref());
+            true
+        }
+        None => {
+            MODEL.with(|i| i.borrow_mut().announcement = format!("{text} is not a legal move"));
+            on_change(Prop::Cursor);
+            false
+        }
+    }
+}
+
+fn app_announce_last_move() {
+    MODEL.with(|i| {
+        let mut m = i.borrow_mut();
+        if let Some(description) = describe_last_move(&m.game) {
+            m.announcement = description;
+        }
+    });
+}
+
+pub fn app_get_announcement() -> String {
+    MODEL.with(|i| i.borrow().announcement.clone())
+}
+
+pub fn app_describe_position() -> String {
+    MODEL.with(|i| {
+        let m = i.borrow();
+        describe_position(m.view.as_ref().map_or(&m.game, |(_, game)| game))
+    })
+}
+
+fn app_start_animation() {
+    let started = MODEL.with(|i| {
+        let mut m = i.borrow_mut();
+        let animation = m
+            .game
+            .history
+            .last()
+            .and_then(move_animation_of)
+            .filter(|_| m.settings.animation_ms > 0 && m.view.is_none());
+        let started = animation.is_some() && m.animation.is_none();
+        m.animation = animation.map(|animation| (animation, js_sys::Date::now()));
+        started
+    });
+    if started {
+        app_request_animation_frame();
+    }
+}
+
+fn app_stop_animation() {
+    MODEL.with(|i| i.borrow_mut().animation = None);
+    app_render();
+}
+
+fn app_request_animation_frame() {
+    let closure = Closure::once_into_js(app_animation_frame);
+    let _ = window().unwrap().request_animation_frame(closure.unchecked_ref());
+}
+
+fn app_animation_frame() {
+    let running = MODEL.with(|i| {
+        let mut m = i.borrow_mut();
+        let duration = f64::from(m.settings.animation_ms);
+        let running =
+            m.animation.as_ref().is_some_and(|(_, start)| js_sys::Date::now() - start < duration);
+        if !running {
+            m.animation = None;
+        }
+        running
+    });
+    app_render();
+    if running {
+        app_request_animation_frame();
+    }
+}
+
+pub fn app_annotation_start(row: u16, col: u16) {
+    MODEL.with(|i| {
+        let mut m = i.borrow_mut();
+        m.annotation_from = square_of_point(row, col, &m.game.bounds, &m.settings.render_settings);
+    });
+}
+
+pub fn app_annotation_end(row: u16, col: u16, color: AnnotationColor) {
+    let annotated = MODEL.with(|i| {
+        let mut m = i.borrow_mut();
+        let from = m.annotation_from.take()?;
+        let to = square_of_point(row, col, &m.game.bounds, &m.settings.render_settings)?;
+        if from == to {
+            toggle_square_annotation(&mut m.selection, to, color);
+        } else {
+            toggle_arrow_annotation(&mut m.selection, from, to, color);
+        }
+        Some(())
+    });
+    if annotated.is_some() {
+        on_change(Prop::Selection);
+    }
+}
+
+fn press_clock(m: &mut Model) {
+    let turn = evaluate_turn(&m.game.start, &m.game.history);
+    let is_over = game_result(&m.game).is_some();
+    if let Some(clock) = &mut m.clock {
+        if is_over {
+            clock.stop(&DateTimeSource);
+        } else if clock.running.is_none() {
+            clock.start(&turn, &DateTimeSource);
+        } else {
+            clock.press(&DateTimeSource);
+        }
+    }
+}
+
+fn app_is_engine_turn() -> bool {
+    MODEL.with(|i| {
+        let m = i.borrow();
+        m.play_as.is_some_and(|color| color != evaluate_turn(&m.game.start, &m.game.history))
+    })
+}
+
+fn app_schedule_engine_move() {
+    if app_get_result().is_some() || !app_is_engine_turn() {
+        return;
+    }
+    app_set_timeout(app_start_engine_search, ENGINE_MOVE_DELAY_MS);
+}
+
+fn app_set_timeout(cb: impl FnOnce() + 'static, timeout: i32) {
+    let closure = Closure::once_into_js(cb);
+    let _ = window()
+        .unwrap()
+        .set_timeout_with_callback_and_timeout_and_arguments_0(closure.unchecked_ref(), timeout);
+}
+
+fn app_start_engine_search() {
+    if app_get_result().is_some() || !app_is_engine_turn() {
+        return;
+    }
+    let game = MODEL.with(|i| i.borrow().game.clone());
+    let id = ENGINE_SEARCH.with_borrow_mut(|search| {
+        let id = search.as_ref().map_or(0, |search| search.id.wrapping_add(1));
+        *search = Some(EngineSearch { id, game, start: DateTimeSource.now_ms(), result: None });
+        id
+    });
+    app_set_timeout(move || app_engine_search_iteration(id), 0);
+}
+
+fn app_engine_search_iteration(id: u32) {
+    let Some((game, start, depth)) = ENGINE_SEARCH.with_borrow(|search| {
+        let search = search.as_ref().filter(|search| search.id == id)?;
+        let depth = search.result.as_ref().map_or(0, |result| result.depth);
+        Some((search.game.clone(), search.start, depth + 1))
+    }) else {
+        return;
+    };
+    let is_current = MODEL.with(|i| i.borrow().game == game);
+    if app_get_result().is_some() || !app_is_engine_turn() || !is_current {
+        ENGINE_SEARCH.with_borrow_mut(|search| *search = None);
+        return;
+    }
+    let elapsed = DateTimeSource.now_ms().saturating_sub(start);
+    let limits = SearchLimits {
+        depth: Some(depth),
+        time: Some(ENGINE_MOVE_TIME_MS.saturating_sub(elapsed).max(1)),
+        ..Default::default()
+    };
+    let result = ENGINE.with_borrow_mut(|engine| {
+        engine.search(&game, &limits, &DateTimeSource, &AtomicBool::new(false), &mut |_| {})
+    });
+    let elapsed = DateTimeSource.now_ms().saturating_sub(start);
+    let done =
+        result.depth < depth || is_mate_score(result.score) || elapsed >= ENGINE_MOVE_TIME_MS / 2;
+    if !done {
+        ENGINE_SEARCH.with_borrow_mut(|search| {
+            if let Some(search) = search {
+                search.result = Some(result);
+            }
+        });
+        app_set_timeout(move || app_engine_search_iteration(id), 0);
+        return;
+    }
+    ENGINE_SEARCH.with_borrow_mut(|search| *search = None);
+    if let Some(game_move) = result.best_move {
+        app_play_engine_move(game_move);
+    }
+}
+
+fn app_play_engine_move(game_move: GameMove) {
+    // a premove still waiting on the promotion picker is dropped
+    let premove = MODEL.with(|i| {
+        let mut m = i.borrow_mut();
+        let premove = m.premove.take();
+        m.promotion.take().map_or(premove, |_| None)
+    });
+    let promotion = promotion_of_game_move_type(&game_move.typ);
+    app_play_move(&game_move.mov.from, &game_move.mov.to, promotion.as_ref());
+    if let Some(Premove { from, to: Some(to), promotion }) = premove {
+        let is_legal = MODEL.with(|i| {
+            let m = i.borrow();
+            let turn = evaluate_turn(&m.game.start, &m.game.history);
+            m.game.players.get(&turn).is_some_and(|player| {
+                player.moves.get(&from).is_some_and(|moves| moves.contains_key(&to))
+            })
+        });
+        if is_legal && app_can_move() {
+            app_play_move(&from, &to, promotion.as_ref());
+        }
+    }
+}
+
+fn app_play_move(from: &Pos, to: &Pos, promotion: Option<&PieceMoveType>) {
+    MODEL.with(|i| {
+        let mut m = i.borrow_mut();
+        play_move_with_promotion(&mut m.game, from, to, promotion);
+        m.selection = Selection {
+            selected_squares: HashMap::new(),
+            arrows: HashMap::new(),
+            selected_pos: None,
+        };
+        press_clock(&mut m);
+    });
+    on_change(Prop::Move);
+    app_schedule_engine_move();
+}
+
+pub fn app_set_auto_queen(auto_queen: bool) {
+    MODEL.with(|i| i.borrow_mut().settings.auto_queen = auto_queen);
+    on_change(Prop::AutoQueen);
+}
+
+pub fn app_set_coordinates(coordinates: Coordinates) {
+    MODEL.with(|i| i.borrow_mut().settings.render_settings.coordinates = coordinates);
+    on_change(Prop::Coordinates);
+}
+
+pub fn app_set_animation(animation_ms: u32) {
+    MODEL.with(|i| i.borrow_mut().settings.animation_ms = animation_ms);
+    on_change(Prop::Animation);
+}
+
+pub fn app_flip_board() {
+    MODEL.with(|i| {
+        let mut m = i.borrow_mut();
+        m.settings.render_settings.orientation = match m.settings.render_settings.orientation {
+            Color::White => Color::Black,
+            Color::Black => Color::White,
+        };
+    });
+    on_change(Prop::Orientation);
+}
+
+pub fn app_set_play_as(play_as: Option<Color>) {
+    MODEL.with(|i| {
+        let mut m = i.borrow_mut();
+        m.play_as = play_as;
+        m.premove = None;
+        if let Some(color) = play_as {
+            m.settings.render_settings.orientation = color;
+        }
+    });
+    ENGINE.with_borrow_mut(|engine| engine.clear());
+    on_change(Prop::PlayAs);
+    app_schedule_engine_move();
+}
+
+pub fn app_set_time_control(preset: &str) {
+    MODEL.with(|i| i.borrow_mut().clock = try_get_time_control(preset).map(Clock::of));
+    on_change(Prop::Clock);
+}
+
+pub fn app_tick() {
+    let flagged = MODEL.with(|i| {
+        let mut m = i.borrow_mut();
+        let clock = m.clock.as_mut()?;
+        if clock.flagged.is_some() {
+            return None;
+        }
+        clock.check_flag(&DateTimeSource)
+    });
+    if flagged.is_some() {
+        on_change(Prop::Clock);
+    }
+}
+
+pub fn app_get_clock() -> Option<(u64, u64, Option<Color>)> {
+    MODEL.with(|i| {
+        let m = i.borrow();
+        let clock = m.clock.as_ref()?;
+        Some((
+            clock.remaining_ms(&Color::White, &DateTimeSource),
+            clock.remaining_ms(&Color::Black, &DateTimeSource),
+            clock.running.map(|(color, _)| color),
+        ))
+    })
+}
+
+pub fn app_get_result() -> Option<GameResult> {
+    MODEL.with(|i| {
+        let m = i.borrow();
+        match &m.clock {
+            Some(clock) => game_result_with_clock(&m.game, clock),
+            None => game_result(&m.game),
+        }
+    })
+}
+
+fn app_play_action(color: Color, action: GameAction) -> bool {
+    let played = MODEL.with(|i| {
+        let mut m = i.borrow_mut();
+        if m.clock.as_ref().is_some_and(|clock| clock.flagged.is_some()) {
+            return false;
+        }
+        if !play_action(&mut m.game, &color, action) {
+            return false;
+        }
+        let is_over = game_result(&m.game).is_some();
+        if let Some(clock) = &mut m.clock
+            && is_over
+        {
+            clock.stop(&DateTimeSource);
+        }
+        true
+    });
+    if played {
+        on_change(Prop::Event);
+    }
+    played
+}
+
+fn opponent_of(color: Color) -> Color {
+    match color {
+        Color::White => Color::Black,
+        Color::Black => Color::White,
+    }
+}
+
+// against the engine the buttons always act for the human, whatever the side to move
+fn action_color(play_as: Option<Color>, turn: Color) -> Color {
+    play_as.unwrap_or(turn)
+}
+
+fn draw_answer_color(play_as: Option<Color>, offer: Color) -> Option<Color> {
+    let color = opponent_of(offer);
+    play_as.is_none_or(|play_as| play_as == color).then_some(color)
+}
+
+fn app_actor() -> (Color, bool) {
+    MODEL.with(|i| {
+        let m = i.borrow();
+        let turn = evaluate_turn(&m.game.start, &m.game.history);
+        (action_color(m.play_as, turn), m.play_as.is_some())
+    })
+}
+
+pub fn app_resign() -> bool {
+    app_play_action(app_actor().0, GameAction::Resign)
+}
+
+pub fn app_offer_draw() -> bool {
+    let (color, against_engine) = app_actor();
+    let offered = app_play_action(color, GameAction::OfferDraw);
+    if offered && against_engine {
+        app_play_action(opponent_of(color), GameAction::DeclineDraw);
+    }
+    offered
+}
+
+pub fn app_answer_draw(accept: bool) -> bool {
+    let Some(offer) = app_get_draw_offer() else {
+        return false;
+    };
+    let Some(color) = draw_answer_color(MODEL.with(|i| i.borrow().play_as), offer) else {
+        return false;
+    };
+    app_play_action(color, if accept { GameAction::AcceptDraw } else { GameAction::DeclineDraw })
+}
+
+pub fn app_claim_draw() -> bool {
+    let claim = MODEL.with(|i| {
+        let m = i.borrow();
+        [DrawClaim::ThreefoldRepetition, DrawClaim::FiftyMoveRule]
+            .into_iter()
+            .find(|claim| can_claim_draw(&m.game, claim))
+    });
+    match claim {
+        Some(claim) => app_play_action(app_actor().0, GameAction::ClaimDraw(claim)),
+        None => false,
+    }
+}
+
+pub fn app_abort() -> bool {
+    app_play_action(app_actor().0, GameAction::Abort)
+}
+
+pub fn app_get_draw_offer() -> Option<Color> {
+    MODEL.with(|i| pending_draw_offer(&i.borrow().game))
+}
+
+pub fn app_get_pgn() -> Option<String> {
+    let result = app_get_result();
+    MODEL.with(|i| {
+        let m = i.borrow();
+        pgn_of_with_annotations(
+            m.mode.clone(),
+            &m.game,
+            result.as_ref(),
+            &[("Event", "libre_chess")],
+            Some(&m.selection),
+        )
+    })
+}
+
+pub fn app_get_game_json() -> Option<String> {
+    MODEL.with(|i| game_to_json(&i.borrow().game))
+}
+
+fn app_set_game(game: Game) {
+    MODEL.with(|i| {
+        let mut m = i.borrow_mut();
+        m.game = game;
+        m.view = None;
+        m.promotion = None;
+        m.premove = None;
+        m.selection = Selection {
+            selected_squares: HashMap::new(),
+            arrows: HashMap::new(),
+            selected_pos: None,
+        };
+        if let Some(clock) = &m.clock {
+            m.clock = Some(Clock::of(clock.control.clone()));
+        }
+    });
+    on_change(Prop::Event);
+}
+
+pub fn app_load_game_json(json: &str) -> bool {
+    let Some(game) = game_try_of_json(json) else {
+        return false;
+    };
+    app_set_game(game);
+    true
+}
+
+pub fn app_import_pgn(pgn: &str) -> bool {
+    let mode = MODEL.with(|i| i.borrow().mode.clone());
+    let Some(game) = pgn_try_of(mode, pgn) else {
+        return false;
+    };
+    app_set_game(game);
+    true
+}
+
+pub fn app_new_game() {
+    let mode = MODEL.with(|i| i.borrow().mode.clone());
+    app_set_game(game_of_mode(mode));
+    app_schedule_engine_move();
+}
+
+pub fn app_get_history() -> Vec<String> {
+    MODEL.with(|i| {
+        let m = i.borrow();
+        san_history_of(&m.game).unwrap_or_default()
+    })
+}
+
+pub fn app_view_ply(ply: Option<usize>) {
+    MODEL.with(|i| {
+        let mut m = i.borrow_mut();
+        m.view = ply
+            .filter(|ply| *ply < m.game.history.len())
+            .and_then(|ply| Some((ply, game_of_ply(&m.game, ply)?)));
+    });
+    on_change(Prop::View);
+}
+
+// piece image names with how many of that piece were captured
+pub type CaptureGroups = Vec<(&'static str, usize)>;
+
+pub fn app_get_captures() -> (CaptureGroups, CaptureGroups, i32) {
+    MODEL.with(|i| {
+        let m = i.borrow();
+        let game = m.view.as_ref().map_or(&m.game, |(_, game)| game);
+        let captures_of = |color: Color, captured: Color| -> CaptureGroups {
+            game.players
+                .get(&color)
+                .map(|player| captures_by_type(&player.captures))
+                .unwrap_or_default()
+                .into_iter()
+                .map(|(typ, count)| (piece_image_name(&Piece { typ, color: captured }), count))
+                .collect()
+        };
+        (
+            captures_of(Color::White, Color::Black),
+            captures_of(Color::Black, Color::White),
+            material_balance(&game.board),
+        )
+    })
+}
+
+pub fn app_get_piece_image(name: &str) -> Option<String> {
+    MODEL.with(|i| board_set_image(&i.borrow().settings.board_set, name).map(String::from))
+}
+
+pub fn app_get_view_ply() -> Option<usize> {
+    MODEL.with(|i| i.borrow().view.as_ref().map(|(ply, _)| *ply))
+}
+
+#[cfg(test)]
+mod tests {
+    use libre_chess_lib::color::Color;
+
+    use super::{action_color, draw_answer_color};
+
+    #[test]
+    fn test_action_color() {
+        assert_eq!(action_color(None, Color::Black), Color::Black);
+        assert_eq!(action_color(Some(Color::White), Color::Black), Color::White);
+        assert_eq!(action_color(Some(Color::Black), Color::Black), Color::Black);
+    }
+
+    #[test]
+    fn test_draw_answer_color() {
+        assert_eq!(draw_answer_color(None, Color::White), Some(Color::Black));
+        assert_eq!(draw_answer_color(Some(Color::White), Color::White), None);
+        assert_eq!(draw_answer_color(Some(Color::Black), Color::White), Some(Color::Black));
+    }
 }
@@ -1,5 +1,14 @@
+use libre_chess_lib::color::Color;
+
+use crate::render::Coordinates;
+
 #[derive(Debug, PartialEq)]
 pub struct AppInfo {
     pub board_set: String,
     pub board_color: String,
+    pub orientation: Color,
+    pub play_as: Option<Color>,
+    pub auto_queen: bool,
+    pub coordinates: Coordinates,
+    pub animation_ms: u32,
 }
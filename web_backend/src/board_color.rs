@@ -1,39 +1,179 @@
 use std::{collections::HashMap, sync::LazyLock};
 
-#[derive(Debug, PartialEq, Clone)]
+use serde::{Deserialize, Serialize};
+
+use crate::board_set::board_set_image_is_valid;
+
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct BoardColor {
-    pub dark: &'static str,
-    pub light: &'static str,
+    pub dark: String,
+    pub light: String,
+    pub last_move: String,
+    pub check: String,
+    pub selected: String,
+    pub move_dot: String,
+    pub capture_ring: String,
+    pub premove: String,
+    pub border: String,
+    pub coordinate: String,
+    pub dark_texture: Option<String>,
+    pub light_texture: Option<String>,
+    pub background: Option<String>,
 }
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct BoardColorPreset {
-    pub id: &'static str,
-    pub name: &'static str,
+    pub id: String,
+    pub name: String,
 }
 
 pub fn board_color_brown() -> BoardColor {
-    BoardColor { dark: "#b88762", light: "#edd6b0" }
+    BoardColor {
+        dark: "#b88762".into(),
+        light: "#edd6b0".into(),
+        last_move: "#f6eb7288".into(),
+        check: "#d20103".into(),
+        selected: "#f0ec0088".into(),
+        move_dot: "#3d2b1f66".into(),
+        capture_ring: "#3d2b1f66".into(),
+        premove: "#c4463988".into(),
+        border: "#5c4232".into(),
+        coordinate: "#edd6b0".into(),
+        dark_texture: None,
+        light_texture: None,
+        background: None,
+    }
 }
 
 pub fn board_color_green() -> BoardColor {
-    BoardColor { dark: "#739552", light: "#ebecd0" }
+    BoardColor {
+        dark: "#739552".into(),
+        light: "#ebecd0".into(),
+        last_move: "#f5f68288".into(),
+        check: "#d20103".into(),
+        selected: "#f0ec0088".into(),
+        move_dot: "#1e3a0f66".into(),
+        capture_ring: "#1e3a0f66".into(),
+        premove: "#d4533f88".into(),
+        border: "#4a5e36".into(),
+        coordinate: "#ebecd0".into(),
+        dark_texture: None,
+        light_texture: None,
+        background: None,
+    }
 }
 
 pub fn board_color_red() -> BoardColor {
-    BoardColor { dark: "#bb5746", light: "#f5dbc3" }
+    BoardColor {
+        dark: "#bb5746".into(),
+        light: "#f5dbc3".into(),
+        last_move: "#f7c63188".into(),
+        check: "#8b0000".into(),
+        selected: "#f0ec0088".into(),
+        move_dot: "#4a1a1266".into(),
+        capture_ring: "#4a1a1266".into(),
+        premove: "#5b7fc488".into(),
+        border: "#6e2f25".into(),
+        coordinate: "#f5dbc3".into(),
+        dark_texture: None,
+        light_texture: None,
+        background: None,
+    }
 }
 
 pub fn board_color_orange() -> BoardColor {
-    BoardColor { dark: "#d18815", light: "#fae4ae" }
+    BoardColor {
+        dark: "#d18815".into(),
+        light: "#fae4ae".into(),
+        last_move: "#7fb2e588".into(),
+        check: "#d20103".into(),
+        selected: "#f0ec0088".into(),
+        move_dot: "#4d300566".into(),
+        capture_ring: "#4d300566".into(),
+        premove: "#4f8a8b88".into(),
+        border: "#7a4f0c".into(),
+        coordinate: "#fae4ae".into(),
+        dark_texture: None,
+        light_texture: None,
+        background: None,
+    }
 }
 
 pub fn board_color_blue() -> BoardColor {
-    BoardColor { dark: "#4b7399", light: "#eae9d2" }
+    BoardColor {
+        dark: "#4b7399".into(),
+        light: "#eae9d2".into(),
+        last_move: "#9fd8ef88".into(),
+        check: "#d20103".into(),
+        selected: "#f0ec0088".into(),
+        move_dot: "#14304d66".into(),
+        capture_ring: "#14304d66".into(),
+        premove: "#e5894a88".into(),
+        border: "#2c4459".into(),
+        coordinate: "#eae9d2".into(),
+        dark_texture: None,
+        light_texture: None,
+        background: None,
+    }
 }
 
 pub fn board_color_purple() -> BoardColor {
-    BoardColor { dark: "#8476ba", light: "#f0f1f0" }
+    BoardColor {
+        dark: "#8476ba".into(),
+        light: "#f0f1f0".into(),
+        last_move: "#b9a9f088".into(),
+        check: "#D20103".into(),
+        selected: "#f0ec0088".into(),
+        move_dot: "#09056b88".into(),
+        capture_ring: "#09056b88".into(),
+        premove: "#e0607e88".into(),
+        border: "#4f4670".into(),
+        coordinate: "#f0f1f0".into(),
+        dark_texture: None,
+        light_texture: None,
+        background: None,
+    }
+}
+
+const WOOD_DARK: &str = include_str!("../themes/wood/dark.svg");
+const WOOD_LIGHT: &str = include_str!("../themes/wood/light.svg");
+const MARBLE_DARK: &str = include_str!("../themes/marble/dark.svg");
+const MARBLE_LIGHT: &str = include_str!("../themes/marble/light.svg");
+
+pub fn board_color_wood() -> BoardColor {
+    BoardColor {
+        dark: "#a8703f".into(),
+        light: "#e8c99b".into(),
+        last_move: "#f6eb7288".into(),
+        check: "#d20103".into(),
+        selected: "#f0ec0088".into(),
+        move_dot: "#3d2b1f66".into(),
+        capture_ring: "#3d2b1f66".into(),
+        premove: "#c4463988".into(),
+        border: "#4a2f1b".into(),
+        coordinate: "#e8c99b".into(),
+        dark_texture: Some(WOOD_DARK.into()),
+        light_texture: Some(WOOD_LIGHT.into()),
+        background: None,
+    }
+}
+
+pub fn board_color_marble() -> BoardColor {
+    BoardColor {
+        dark: "#5d6670".into(),
+        light: "#f1f0ec".into(),
+        last_move: "#9bc7e888".into(),
+        check: "#d20103".into(),
+        selected: "#f0ec0088".into(),
+        move_dot: "#1e262e66".into(),
+        capture_ring: "#1e262e66".into(),
+        premove: "#c4463988".into(),
+        border: "#33393f".into(),
+        coordinate: "#f1f0ec".into(),
+        dark_texture: Some(MARBLE_DARK.into()),
+        light_texture: Some(MARBLE_LIGHT.into()),
+        background: None,
+    }
 }
 
 static PRESETS: LazyLock<HashMap<&str, BoardColor>> = LazyLock::new(|| {
@@ -44,9 +184,46 @@ static PRESETS: LazyLock<HashMap<&str, BoardColor>> = LazyLock::new(|| {
         ("orange", board_color_orange()),
         ("blue", board_color_blue()),
         ("purple", board_color_purple()),
+        ("wood", board_color_wood()),
+        ("marble", board_color_marble()),
     ])
 });
 
+fn color_is_valid(color: &str) -> bool {
+    color.strip_prefix('#').is_some_and(|hex| {
+        matches!(hex.len(), 3 | 4 | 6 | 8) && hex.chars().all(|c| c.is_ascii_hexdigit())
+    })
+}
+
+pub fn board_color_is_valid(board_color: &BoardColor) -> bool {
+    [
+        &board_color.dark,
+        &board_color.light,
+        &board_color.last_move,
+        &board_color.check,
+        &board_color.selected,
+        &board_color.move_dot,
+        &board_color.capture_ring,
+        &board_color.premove,
+        &board_color.border,
+        &board_color.coordinate,
+    ]
+    .iter()
+    .all(|color| color_is_valid(color))
+        && board_color_images(board_color).iter().all(|(_, image)| board_set_image_is_valid(image))
+}
+
+pub fn board_color_images(board_color: &BoardColor) -> Vec<(&'static str, &str)> {
+    [
+        ("dark", &board_color.dark_texture),
+        ("light", &board_color.light_texture),
+        ("background", &board_color.background),
+    ]
+    .into_iter()
+    .filter_map(|(name, image)| image.as_deref().map(|image| (name, image)))
+    .collect()
+}
+
 pub fn try_get_board_color(preset: &str) -> Option<BoardColor> {
     PRESETS.get(preset).cloned()
 }
@@ -57,11 +234,52 @@ pub fn get_board_color(preset: &str) -> BoardColor {
 
 pub fn get_board_color_presets() -> Vec<BoardColorPreset> {
     vec![
-        BoardColorPreset { id: "brown", name: "Brown" },
-        BoardColorPreset { id: "green", name: "Green" },
-        BoardColorPreset { id: "red", name: "Red" },
-        BoardColorPreset { id: "orange", name: "Orange" },
-        BoardColorPreset { id: "blue", name: "Blue" },
-        BoardColorPreset { id: "purple", name: "Purple" },
+        BoardColorPreset { id: "brown".into(), name: "Brown".into() },
+        BoardColorPreset { id: "green".into(), name: "Green".into() },
+        BoardColorPreset { id: "red".into(), name: "Red".into() },
+        BoardColorPreset { id: "orange".into(), name: "Orange".into() },
+        BoardColorPreset { id: "blue".into(), name: "Blue".into() },
+        BoardColorPreset { id: "purple".into(), name: "Purple".into() },
+        BoardColorPreset { id: "wood".into(), name: "Wood".into() },
+        BoardColorPreset { id: "marble".into(), name: "Marble".into() },
     ]
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        board_color_images, board_color_is_valid, board_color_purple, board_color_wood,
+        get_board_color_presets, try_get_board_color,
+    };
+
+    #[test]
+    fn board_color_validation() {
+        for preset in get_board_color_presets() {
+            assert!(board_color_is_valid(&try_get_board_color(&preset.id).unwrap()));
+        }
+        let mut board_color = board_color_purple();
+        board_color.dark = "#abc".into();
+        assert!(board_color_is_valid(&board_color));
+        board_color.premove = "#12345".into();
+        assert!(!board_color_is_valid(&board_color));
+        board_color.premove = "red".into();
+        assert!(!board_color_is_valid(&board_color));
+        board_color.premove = "#gg0000".into();
+        assert!(!board_color_is_valid(&board_color));
+    }
+
+    #[test]
+    fn board_color_textures() {
+        assert_eq!(board_color_images(&board_color_purple()), vec![]);
+        let mut board_color = board_color_wood();
+        assert_eq!(
+            board_color_images(&board_color).iter().map(|(name, _)| *name).collect::<Vec<_>>(),
+            vec!["dark", "light"]
+        );
+        board_color.background = Some("https://example.com/board.png".into());
+        assert!(board_color_is_valid(&board_color));
+        assert_eq!(board_color_images(&board_color).len(), 3);
+        board_color.background = Some("board.png".into());
+        assert!(!board_color_is_valid(&board_color));
+    }
+}
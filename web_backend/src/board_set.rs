@@ -1,25 +1,27 @@
 use std::{collections::HashMap, sync::LazyLock};
 
-#[derive(Debug, PartialEq, Clone)]
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct BoardSet {
-    pub bb: &'static str,
-    pub bk: &'static str,
-    pub bn: &'static str,
-    pub bp: &'static str,
-    pub bq: &'static str,
-    pub br: &'static str,
-    pub wb: &'static str,
-    pub wk: &'static str,
-    pub wn: &'static str,
-    pub wp: &'static str,
-    pub wq: &'static str,
-    pub wr: &'static str,
-}
-
-#[derive(Debug, PartialEq, Clone)]
+    pub bb: String,
+    pub bk: String,
+    pub bn: String,
+    pub bp: String,
+    pub bq: String,
+    pub br: String,
+    pub wb: String,
+    pub wk: String,
+    pub wn: String,
+    pub wp: String,
+    pub wq: String,
+    pub wr: String,
+}
+
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct BoardSetPreset {
-    pub id: &'static str,
-    pub name: &'static str,
+    pub id: String,
+    pub name: String,
 }
 
 const BB_NORMAL_1: &str = include_str!("../sets/normal_1/bb.svg");
@@ -76,69 +78,69 @@ const WR_SET_SPATIAL: &str = include_str!("../sets/spatial/wr.svg");
 
 pub fn board_set_normal_1() -> BoardSet {
     BoardSet {
-        bb: BB_NORMAL_1,
-        bk: BK_NORMAL_1,
-        bn: BN_NORMAL_1,
-        bp: BP_NORMAL_1,
-        bq: BQ_NORMAL_1,
-        br: BR_NORMAL_1,
-        wb: WB_NORMAL_1,
-        wk: WK_NORMAL_1,
-        wn: WN_NORMAL_1,
-        wp: WP_NORMAL_1,
-        wq: WQ_NORMAL_1,
-        wr: WR_NORMAL_1,
+        bb: BB_NORMAL_1.into(),
+        bk: BK_NORMAL_1.into(),
+        bn: BN_NORMAL_1.into(),
+        bp: BP_NORMAL_1.into(),
+        bq: BQ_NORMAL_1.into(),
+        br: BR_NORMAL_1.into(),
+        wb: WB_NORMAL_1.into(),
+        wk: WK_NORMAL_1.into(),
+        wn: WN_NORMAL_1.into(),
+        wp: WP_NORMAL_1.into(),
+        wq: WQ_NORMAL_1.into(),
+        wr: WR_NORMAL_1.into(),
     }
 }
 
 pub fn board_set_normal_2() -> BoardSet {
     BoardSet {
-        bb: BB_NORMAL_2,
-        bk: BK_NORMAL_2,
-        bn: BN_NORMAL_2,
-        bp: BP_NORMAL_2,
-        bq: BQ_NORMAL_2,
-        br: BR_NORMAL_2,
-        wb: WB_NORMAL_2,
-        wk: WK_NORMAL_2,
-        wn: WN_NORMAL_2,
-        wp: WP_NORMAL_2,
-        wq: WQ_NORMAL_2,
-        wr: WR_NORMAL_2,
+        bb: BB_NORMAL_2.into(),
+        bk: BK_NORMAL_2.into(),
+        bn: BN_NORMAL_2.into(),
+        bp: BP_NORMAL_2.into(),
+        bq: BQ_NORMAL_2.into(),
+        br: BR_NORMAL_2.into(),
+        wb: WB_NORMAL_2.into(),
+        wk: WK_NORMAL_2.into(),
+        wn: WN_NORMAL_2.into(),
+        wp: WP_NORMAL_2.into(),
+        wq: WQ_NORMAL_2.into(),
+        wr: WR_NORMAL_2.into(),
     }
 }
 
 pub fn board_set_fantasy() -> BoardSet {
     BoardSet {
-        bb: BB_SET_FANTASY,
-        bk: BK_SET_FANTASY,
-        bn: BN_SET_FANTASY,
-        bp: BP_SET_FANTASY,
-        bq: BQ_SET_FANTASY,
-        br: BR_SET_FANTASY,
-        wb: WB_SET_FANTASY,
-        wk: WK_SET_FANTASY,
-        wn: WN_SET_FANTASY,
-        wp: WP_SET_FANTASY,
-        wq: WQ_SET_FANTASY,
-        wr: WR_SET_FANTASY,
+        bb: BB_SET_FANTASY.into(),
+        bk: BK_SET_FANTASY.into(),
+        bn: BN_SET_FANTASY.into(),
+        bp: BP_SET_FANTASY.into(),
+        bq: BQ_SET_FANTASY.into(),
+        br: BR_SET_FANTASY.into(),
+        wb: WB_SET_FANTASY.into(),
+        wk: WK_SET_FANTASY.into(),
+        wn: WN_SET_FANTASY.into(),
+        wp: WP_SET_FANTASY.into(),
+        wq: WQ_SET_FANTASY.into(),
+        wr: WR_SET_FANTASY.into(),
     }
 }
 
 pub fn board_set_spatial() -> BoardSet {
     BoardSet {
-        bb: BB_SET_SPATIAL,
-        bk: BK_SET_SPATIAL,
-        bn: BN_SET_SPATIAL,
-        bp: BP_SET_SPATIAL,
-        bq: BQ_SET_SPATIAL,
-        br: BR_SET_SPATIAL,
-        wb: WB_SET_SPATIAL,
-        wk: WK_SET_SPATIAL,
-        wn: WN_SET_SPATIAL,
-        wp: WP_SET_SPATIAL,
-        wq: WQ_SET_SPATIAL,
-        wr: WR_SET_SPATIAL,
+        bb: BB_SET_SPATIAL.into(),
+        bk: BK_SET_SPATIAL.into(),
+        bn: BN_SET_SPATIAL.into(),
+        bp: BP_SET_SPATIAL.into(),
+        bq: BQ_SET_SPATIAL.into(),
+        br: BR_SET_SPATIAL.into(),
+        wb: WB_SET_SPATIAL.into(),
+        wk: WK_SET_SPATIAL.into(),
+        wn: WN_SET_SPATIAL.into(),
+        wp: WP_SET_SPATIAL.into(),
+        wq: WQ_SET_SPATIAL.into(),
+        wr: WR_SET_SPATIAL.into(),
     }
 }
 
@@ -151,6 +153,46 @@ static PRESETS: LazyLock<HashMap<String, BoardSet>> = LazyLock::new(|| {
     ])
 });
 
+pub fn board_set_images(board_set: &BoardSet) -> [(&'static str, &str); 12] {
+    [
+        ("bb", &board_set.bb),
+        ("bk", &board_set.bk),
+        ("bn", &board_set.bn),
+        ("bp", &board_set.bp),
+        ("bq", &board_set.bq),
+        ("br", &board_set.br),
+        ("wb", &board_set.wb),
+        ("wk", &board_set.wk),
+        ("wn", &board_set.wn),
+        ("wp", &board_set.wp),
+        ("wq", &board_set.wq),
+        ("wr", &board_set.wr),
+    ]
+}
+
+pub fn board_set_image<'a>(board_set: &'a BoardSet, name: &str) -> Option<&'a str> {
+    board_set_images(board_set).into_iter().find(|(n, _)| *n == name).map(|(_, image)| image)
+}
+
+pub fn board_set_image_is_url(image: &str) -> bool {
+    ["http://", "https://", "data:image/", "blob:"].iter().any(|prefix| image.starts_with(prefix))
+}
+
+fn board_set_image_is_svg(image: &str) -> bool {
+    let image = image.trim();
+    (image.starts_with("<svg") || image.starts_with("<?xml"))
+        && image.contains("<svg")
+        && image.ends_with("</svg>")
+}
+
+pub fn board_set_image_is_valid(image: &str) -> bool {
+    board_set_image_is_url(image) || board_set_image_is_svg(image)
+}
+
+pub fn board_set_is_valid(board_set: &BoardSet) -> bool {
+    board_set_images(board_set).iter().all(|(_, image)| board_set_image_is_valid(image))
+}
+
 pub fn try_get_board_set(preset: &str) -> Option<BoardSet> {
     PRESETS.get(preset).cloned()
 }
@@ -161,9 +203,50 @@ pub fn get_board_set(preset: &str) -> BoardSet {
 
 pub fn get_board_set_presets() -> Vec<BoardSetPreset> {
     vec![
-        BoardSetPreset { id: "normal_1", name: "Normal 1" },
-        BoardSetPreset { id: "normal_2", name: "Normal 2" },
-        BoardSetPreset { id: "fantasy", name: "Fantasy" },
-        BoardSetPreset { id: "spatial", name: "Spatial" },
+        BoardSetPreset { id: "normal_1".into(), name: "Normal 1".into() },
+        BoardSetPreset { id: "normal_2".into(), name: "Normal 2".into() },
+        BoardSetPreset { id: "fantasy".into(), name: "Fantasy".into() },
+        BoardSetPreset { id: "spatial".into(), name: "Spatial".into() },
     ]
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        BoardSet, board_set_image, board_set_image_is_url, board_set_is_valid, board_set_normal_1,
+        get_board_set_presets, try_get_board_set,
+    };
+
+    #[test]
+    fn board_set_validation() {
+        for preset in get_board_set_presets() {
+            assert!(board_set_is_valid(&try_get_board_set(&preset.id).unwrap()));
+        }
+        let svg = String::from("<svg xmlns=\"http://www.w3.org/2000/svg\"></svg>");
+        let url = String::from("https://example.com/wk.svg");
+        let mut board_set = BoardSet {
+            bb: svg.clone(),
+            bk: svg.clone(),
+            bn: svg.clone(),
+            bp: svg.clone(),
+            bq: svg.clone(),
+            br: svg.clone(),
+            wb: svg.clone(),
+            wk: url.clone(),
+            wn: svg.clone(),
+            wp: svg.clone(),
+            wq: svg.clone(),
+            wr: svg.clone(),
+        };
+        assert!(board_set_is_valid(&board_set));
+        assert_eq!(board_set_image(&board_set, "wk"), Some(url.as_str()));
+        assert_eq!(board_set_image(&board_set, "xx"), None);
+        assert!(board_set_image_is_url(&url));
+        assert!(!board_set_image_is_url(&svg));
+        board_set.bq = "<script></script>".into();
+        assert!(!board_set_is_valid(&board_set));
+        board_set.bq = "javascript:alert(1)".into();
+        assert!(!board_set_is_valid(&board_set));
+        assert!(board_set_image(&board_set_normal_1(), "bq").is_some());
+    }
+}
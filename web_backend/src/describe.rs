@@ -0,0 +1,193 @@
+use libre_chess_lib::{
+    color::Color,
+    game::{
+        board::GameBoard,
+        game::Game,
+        mov::{GameMove, GameMoveType},
+        rule::{check::is_in_check, turn::evaluate_turn},
+    },
+    piece::{Piece, PieceType},
+    pos::{Pos, pos_to_string},
+};
+
+const DESCRIBE_ORDER: [PieceType; 6] = [
+    PieceType::King,
+    PieceType::Queen,
+    PieceType::Rook,
+    PieceType::Bishop,
+    PieceType::Knight,
+    PieceType::Pawn,
+];
+
+fn color_name(color: &Color) -> &'static str {
+    match color {
+        Color::White => "white",
+        Color::Black => "black",
+    }
+}
+
+fn piece_type_name(typ: &PieceType) -> &'static str {
+    match typ {
+        PieceType::Rook => "rook",
+        PieceType::Knight => "knight",
+        PieceType::Bishop => "bishop",
+        PieceType::Queen => "queen",
+        PieceType::King => "king",
+        PieceType::Pawn => "pawn",
+    }
+}
+
+fn square_name(pos: &Pos) -> String {
+    pos_to_string(pos).to_ascii_lowercase()
+}
+
+fn capitalize(s: &str) -> String {
+    let mut chars = s.chars();
+    chars.next().map_or(String::new(), |c| c.to_ascii_uppercase().to_string() + chars.as_str())
+}
+
+pub fn describe_square(board: &GameBoard, pos: &Pos) -> String {
+    match board.get(pos) {
+        Some(piece) => {
+            format!(
+                "{}, {} {}",
+                square_name(pos),
+                color_name(&piece.color),
+                piece_type_name(&piece.typ)
+            )
+        }
+        None => format!("{}, empty", square_name(pos)),
+    }
+}
+
+pub fn describe_position(game: &Game) -> String {
+    let turn = evaluate_turn(&game.start, &game.history);
+    let mut description = format!("{} to move.", capitalize(color_name(&turn)));
+    if is_in_check(&game.board, &game.players, &game.start, &game.history) {
+        description.push_str(" Check.");
+    }
+    for color in [Color::White, Color::Black] {
+        let groups: Vec<String> = DESCRIBE_ORDER
+            .iter()
+            .filter_map(|typ| {
+                let mut squares: Vec<&Pos> = game
+                    .board
+                    .iter()
+                    .filter(|(_, piece)| **piece == Piece { typ: *typ, color })
+                    .map(|(pos, _)| pos)
+                    .collect();
+                if squares.is_empty() {
+                    return None;
+                }
+                squares.sort_by_key(|pos| (pos.col, pos.row));
+                let squares: Vec<String> = squares.into_iter().map(square_name).collect();
+                Some(format!("{} {}", piece_type_name(typ), squares.join(" ")))
+            })
+            .collect();
+        description.push_str(&format!(
+            " {}: {}.",
+            capitalize(color_name(&color)),
+            groups.join(", ")
+        ));
+    }
+    description
+}
+
+pub fn describe_move(game_move: &GameMove) -> String {
+    let piece = &game_move.mov.piece;
+    let color = capitalize(color_name(&piece.color));
+    let from = square_name(&game_move.mov.from);
+    let to = square_name(&game_move.mov.to);
+    let typ = piece_type_name(&piece.typ);
+    match game_move.typ {
+        GameMoveType::ShortCastling => format!("{color} castles kingside"),
+        GameMoveType::LongCastling => format!("{color} castles queenside"),
+        GameMoveType::Capture => format!("{color} {typ} {from} takes {to}"),
+        GameMoveType::EnPassant => format!("{color} {typ} {from} takes {to} en passant"),
+        GameMoveType::PromotionToQueen => {
+            format!("{color} {typ} {from} to {to}, promotes to queen")
+        }
+        GameMoveType::PromotionToRook => format!("{color} {typ} {from} to {to}, promotes to rook"),
+        GameMoveType::PromotionToBishop => {
+            format!("{color} {typ} {from} to {to}, promotes to bishop")
+        }
+        GameMoveType::PromotionToKnight => {
+            format!("{color} {typ} {from} to {to}, promotes to knight")
+        }
+        GameMoveType::Default | GameMoveType::Menace => format!("{color} {typ} {from} to {to}"),
+    }
+}
+
+pub fn describe_last_move(game: &Game) -> Option<String> {
+    let game_move = game.history.last()?;
+    let mut description = describe_move(game_move);
+    if is_in_check(&game.board, &game.players, &game.start, &game.history) {
+        description.push_str(", check");
+    }
+    Some(description)
+}
+
+#[cfg(test)]
+mod tests {
+    use libre_chess_lib::{
+        game::{
+            mode::standard_chess, mov::GameMove, mov::GameMoveType, rule::init::game_of_mode,
+            rule::move_piece::play_move,
+        },
+        pos::pos_of,
+    };
+
+    use super::{describe_last_move, describe_move, describe_position, describe_square};
+
+    #[test]
+    fn test_describe_square() {
+        let game = game_of_mode(standard_chess());
+        assert_eq!(describe_square(&game.board, &pos_of("E2")), "e2, white pawn");
+        assert_eq!(describe_square(&game.board, &pos_of("D8")), "d8, black queen");
+        assert_eq!(describe_square(&game.board, &pos_of("E4")), "e4, empty");
+    }
+
+    #[test]
+    fn test_describe_position() {
+        let game = game_of_mode(standard_chess());
+        assert_eq!(
+            describe_position(&game),
+            "White to move. \
+            White: king e1, queen d1, rook a1 h1, bishop c1 f1, knight b1 g1, pawn a2 b2 c2 d2 e2 f2 g2 h2. \
+            Black: king e8, queen d8, rook a8 h8, bishop c8 f8, knight b8 g8, pawn a7 b7 c7 d7 e7 f7 g7 h7."
+        );
+    }
+
+    #[test]
+    fn test_describe_move() {
+        assert_eq!(
+            describe_move(&GameMove::try_of('♘', "G1", "F3", GameMoveType::Default).unwrap()),
+            "White knight g1 to f3"
+        );
+        assert_eq!(
+            describe_move(&GameMove::try_of('♟', "D4", "E3", GameMoveType::EnPassant).unwrap()),
+            "Black pawn d4 takes e3 en passant"
+        );
+        assert_eq!(
+            describe_move(&GameMove::try_of('♔', "E1", "H1", GameMoveType::ShortCastling).unwrap()),
+            "White castles kingside"
+        );
+        assert_eq!(
+            describe_move(
+                &GameMove::try_of('♙', "B7", "B8", GameMoveType::PromotionToKnight).unwrap()
+            ),
+            "White pawn b7 to b8, promotes to knight"
+        );
+    }
+
+    #[test]
+    fn test_describe_last_move() {
+        let mut game = game_of_mode(standard_chess());
+        assert_eq!(describe_last_move(&game), None);
+        play_move(&mut game, &pos_of("E2"), &pos_of("E4"));
+        assert_eq!(describe_last_move(&game), Some("White pawn e2 to e4".into()));
+        play_move(&mut game, &pos_of("F7"), &pos_of("F6"));
+        play_move(&mut game, &pos_of("D1"), &pos_of("H5"));
+        assert_eq!(describe_last_move(&game), Some("White queen d1 to h5, check".into()));
+    }
+}
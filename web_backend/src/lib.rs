@@ -1,23 +1,47 @@
 use app::{
-    app_add_on_change_listener, app_click, app_get_settings, app_init, app_set_board_color,
-    app_set_board_set, app_set_dim,
+    app_abort, app_add_on_change_listener, app_annotation_end, app_annotation_start,
+    app_answer_draw, app_cancel_selection, app_claim_draw, app_cursor_select,
+    app_describe_position, app_enter_move, app_flip_board, app_get_announcement,
+    app_get_board_color_presets, app_get_board_set_presets, app_get_book_moves, app_get_captures,
+    app_get_clock, app_get_draw_offer, app_get_game_json, app_get_history, app_get_opening,
+    app_get_pgn, app_get_piece_image, app_get_result, app_get_settings, app_get_view_ply,
+    app_import_pgn, app_init, app_load_book, app_load_game_json, app_move_cursor, app_new_game,
+    app_offer_draw, app_pointer_cancel, app_pointer_down, app_pointer_move, app_pointer_up,
+    app_register_board_color, app_register_board_set, app_resign, app_set_animation,
+    app_set_auto_queen, app_set_board_color, app_set_board_set, app_set_coordinates, app_set_dim,
+    app_set_play_as, app_set_time_control, app_tick, app_view_ply,
 };
-use board_color::get_board_color_presets;
-use board_set::get_board_set_presets;
+use libre_chess_lib::{
+    clock::get_time_control_presets,
+    color::Color,
+    game::{
+        lan::lan_to_string,
+        result::{DrawReason, GameResult, WinReason},
+        selection::AnnotationColor,
+    },
+    pos::pos_to_string,
+};
+use render::{coordinates_to_string, coordinates_try_of_str};
 use serde::Serialize;
 use wasm_bindgen::{
     JsCast, JsValue,
     prelude::{Closure, wasm_bindgen},
 };
-use web_sys::{CanvasRenderingContext2d, HtmlCanvasElement, MouseEvent, js_sys::Function};
+use web_sys::{
+    CanvasRenderingContext2d, HtmlCanvasElement, KeyboardEvent, MouseEvent, PointerEvent,
+    js_sys::Function,
+};
 
 mod app;
 mod app_info;
 mod board_color;
 mod board_set;
+mod describe;
 mod model;
 mod prop;
 mod render;
+mod storage;
+mod time_source;
 
 #[derive(Serialize)]
 pub struct Preset {
@@ -25,10 +49,49 @@ pub struct Preset {
     pub name: String,
 }
 
+#[derive(Serialize)]
+pub struct BookMoveInfo {
+    pub lan: String,
+    pub from: String,
+    pub to: String,
+    pub weight: u16,
+}
+
+#[derive(Serialize)]
+pub struct OpeningInfo {
+    pub code: String,
+    pub name: String,
+}
+
+#[derive(Serialize)]
+pub struct CaptureGroupInfo {
+    pub piece: String,
+    pub count: usize,
+}
+
+#[derive(Serialize)]
+pub struct CapturesInfo {
+    pub white: Vec<CaptureGroupInfo>,
+    pub black: Vec<CaptureGroupInfo>,
+    pub balance: i32,
+}
+
+#[derive(Serialize)]
+pub struct ClockInfo {
+    pub white_ms: u64,
+    pub black_ms: u64,
+    pub running: Option<String>,
+}
+
 #[wasm_bindgen]
 pub struct EngineInfo {
     board_set: String,
     board_color: String,
+    orientation: String,
+    play_as: Option<String>,
+    auto_queen: bool,
+    coordinates: String,
+    animation_ms: u32,
 }
 
 #[wasm_bindgen]
@@ -42,23 +105,122 @@ impl EngineInfo {
     pub fn board_color(&self) -> String {
         self.board_color.clone()
     }
+
+    #[wasm_bindgen(getter)]
+    pub fn orientation(&self) -> String {
+        self.orientation.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn play_as(&self) -> Option<String> {
+        self.play_as.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn auto_queen(&self) -> bool {
+        self.auto_queen
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn coordinates(&self) -> String {
+        self.coordinates.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn animation_ms(&self) -> u32 {
+        self.animation_ms
+    }
 }
 
 #[wasm_bindgen(js_name = "engineInit")]
 pub fn main_init(canvas: HtmlCanvasElement) {
     if let Ok(Some(context)) = canvas.get_context("2d") {
         app_init(context.dyn_into::<CanvasRenderingContext2d>().map_err(|_| ()).unwrap());
-        let closure = Closure::wrap(Box::new(move |e: MouseEvent| {
-            if let Some(Ok(element)) =
-                e.current_target().map(|target| target.dyn_into::<HtmlCanvasElement>())
-            {
-                let x = e.page_x() - element.offset_left();
-                let y = e.page_y() - element.offset_top();
-                app_click(y as u16, x as u16);
-            }
+        let listeners = [
+            ("pointerdown", main_pointer_down as fn(&PointerEvent, u16, u16)),
+            ("pointermove", main_pointer_move),
+            ("pointerup", main_pointer_up),
+            ("pointercancel", main_pointer_cancel),
+            ("lostpointercapture", main_pointer_cancel),
+        ];
+        for (event, handler) in listeners {
+            let closure = Closure::wrap(Box::new(move |e: PointerEvent| {
+                if let Some(Ok(element)) =
+                    e.current_target().map(|target| target.dyn_into::<HtmlCanvasElement>())
+                {
+                    if event == "pointerdown" {
+                        let _ = element.set_pointer_capture(e.pointer_id());
+                    }
+                    let x = (e.page_x() - element.offset_left()).max(0);
+                    let y = (e.page_y() - element.offset_top()).max(0);
+                    handler(&e, y as u16, x as u16);
+                }
+            }) as Box<dyn FnMut(_)>);
+            canvas
+                .add_event_listener_with_callback(event, closure.as_ref().unchecked_ref())
+                .unwrap();
+            closure.forget();
+        }
+        let closure = Closure::wrap(Box::new(|e: MouseEvent| {
+            e.prevent_default();
         }) as Box<dyn FnMut(_)>);
-        canvas.add_event_listener_with_callback("click", closure.as_ref().unchecked_ref()).unwrap();
+        canvas
+            .add_event_listener_with_callback("contextmenu", closure.as_ref().unchecked_ref())
+            .unwrap();
         closure.forget();
+        let closure = Closure::wrap(Box::new(main_key_down) as Box<dyn FnMut(_)>);
+        canvas
+            .add_event_listener_with_callback("keydown", closure.as_ref().unchecked_ref())
+            .unwrap();
+        closure.forget();
+    }
+}
+
+fn main_key_down(e: KeyboardEvent) {
+    match e.key().as_str() {
+        "ArrowUp" => app_move_cursor(0, -1),
+        "ArrowDown" => app_move_cursor(0, 1),
+        "ArrowLeft" => app_move_cursor(-1, 0),
+        "ArrowRight" => app_move_cursor(1, 0),
+        "Enter" | " " => app_cursor_select(),
+        "Escape" => app_cancel_selection(),
+        _ => return,
+    }
+    e.prevent_default();
+}
+
+const SECONDARY_BUTTON: i16 = 2;
+
+fn annotation_color_of(e: &PointerEvent) -> AnnotationColor {
+    match (e.shift_key(), e.alt_key()) {
+        (false, false) => AnnotationColor::Green,
+        (true, false) => AnnotationColor::Red,
+        (false, true) => AnnotationColor::Blue,
+        (true, true) => AnnotationColor::Yellow,
+    }
+}
+
+fn main_pointer_down(e: &PointerEvent, row: u16, col: u16) {
+    if e.button() == SECONDARY_BUTTON {
+        app_annotation_start(row, col);
+    } else {
+        app_pointer_down(row, col);
+    }
+}
+
+fn main_pointer_move(_: &PointerEvent, row: u16, col: u16) {
+    app_pointer_move(row, col);
+}
+
+fn main_pointer_cancel(_: &PointerEvent, _: u16, _: u16) {
+    app_pointer_cancel();
+}
+
+fn main_pointer_up(e: &PointerEvent, row: u16, col: u16) {
+    if e.button() == SECONDARY_BUTTON {
+        app_annotation_end(row, col, annotation_color_of(e));
+    } else {
+        app_pointer_up(row, col);
     }
 }
 
@@ -80,30 +242,285 @@ pub fn main_set_board_set(board_set: &str) {
 #[wasm_bindgen(js_name = "engineGetSettings")]
 pub fn main_get_settings() -> EngineInfo {
     let settings = app_get_settings();
-    EngineInfo { board_color: settings.board_color, board_set: settings.board_set }
+    EngineInfo {
+        board_color: settings.board_color,
+        board_set: settings.board_set,
+        orientation: color_to_string(&settings.orientation),
+        play_as: settings.play_as.as_ref().map(color_to_string),
+        auto_queen: settings.auto_queen,
+        coordinates: coordinates_to_string(&settings.coordinates).into(),
+        animation_ms: settings.animation_ms,
+    }
 }
 
 #[wasm_bindgen(js_name = "engineGetBoardSetPresets")]
 pub fn main_get_board_set_presets() -> JsValue {
-    let groups: Vec<Preset> = get_board_set_presets()
-        .iter()
-        .map(|g| Preset { id: g.id.into(), name: g.name.into() })
+    let groups: Vec<Preset> = app_get_board_set_presets()
+        .into_iter()
+        .map(|g| Preset { id: g.id, name: g.name })
         .collect();
     serde_wasm_bindgen::to_value(&groups).unwrap()
 }
 
 #[wasm_bindgen(js_name = "engineGetBoardColorPresets")]
 pub fn main_get_board_color_presets() -> JsValue {
-    let groups: Vec<Preset> = get_board_color_presets()
-        .iter()
-        .map(|g| Preset { id: g.id.into(), name: g.name.into() })
+    let groups: Vec<Preset> = app_get_board_color_presets()
+        .into_iter()
+        .map(|g| Preset { id: g.id, name: g.name })
         .collect();
     serde_wasm_bindgen::to_value(&groups).unwrap()
 }
 
+#[wasm_bindgen(js_name = "engineRegisterBoardSet")]
+pub fn main_register_board_set(id: &str, name: &str, board_set: JsValue) -> bool {
+    serde_wasm_bindgen::from_value(board_set)
+        .is_ok_and(|board_set| app_register_board_set(id, name, board_set))
+}
+
+#[wasm_bindgen(js_name = "engineRegisterBoardColor")]
+pub fn main_register_board_color(id: &str, name: &str, board_color: JsValue) -> bool {
+    serde_wasm_bindgen::from_value(board_color)
+        .is_ok_and(|board_color| app_register_board_color(id, name, board_color))
+}
+
+#[wasm_bindgen(js_name = "engineEnterMove")]
+pub fn main_enter_move(text: &str) -> bool {
+    app_enter_move(text)
+}
+
+#[wasm_bindgen(js_name = "engineGetAnnouncement")]
+pub fn main_get_announcement() -> String {
+    app_get_announcement()
+}
+
+#[wasm_bindgen(js_name = "engineDescribePosition")]
+pub fn main_describe_position() -> String {
+    app_describe_position()
+}
+
 #[wasm_bindgen(js_name = "engineAddOnChangeListener")]
 pub fn main_add_on_change_listener(cb: Function) {
     app_add_on_change_listener(move |_| {
         cb.call0(&JsValue::null()).unwrap();
     });
 }
+
+#[wasm_bindgen(js_name = "engineLoadBook")]
+pub fn main_load_book(bytes: &[u8]) -> bool {
+    app_load_book(bytes)
+}
+
+#[wasm_bindgen(js_name = "engineGetBookMoves")]
+pub fn main_get_book_moves() -> JsValue {
+    let moves: Vec<BookMoveInfo> = app_get_book_moves()
+        .iter()
+        .map(|mv| BookMoveInfo {
+            lan: lan_to_string(&mv.game_move),
+            from: pos_to_string(&mv.from),
+            to: pos_to_string(&mv.to),
+            weight: mv.weight,
+        })
+        .collect();
+    serde_wasm_bindgen::to_value(&moves).unwrap()
+}
+
+#[wasm_bindgen(js_name = "engineGetOpening")]
+pub fn main_get_opening() -> JsValue {
+    let opening = app_get_opening()
+        .map(|opening| OpeningInfo { code: opening.code.into(), name: opening.name.into() });
+    serde_wasm_bindgen::to_value(&opening).unwrap()
+}
+
+fn color_to_string(color: &Color) -> String {
+    match color {
+        Color::White => "white".into(),
+        Color::Black => "black".into(),
+    }
+}
+
+fn color_try_of_str(s: &str) -> Option<Color> {
+    match s {
+        "white" => Some(Color::White),
+        "black" => Some(Color::Black),
+        _ => None,
+    }
+}
+
+#[wasm_bindgen(js_name = "engineGetTimeControlPresets")]
+pub fn main_get_time_control_presets() -> JsValue {
+    let groups: Vec<Preset> = get_time_control_presets()
+        .iter()
+        .map(|g| Preset { id: g.id.into(), name: g.name.into() })
+        .collect();
+    serde_wasm_bindgen::to_value(&groups).unwrap()
+}
+
+#[wasm_bindgen(js_name = "engineSetTimeControl")]
+pub fn main_set_time_control(preset: &str) {
+    app_set_time_control(preset);
+}
+
+#[wasm_bindgen(js_name = "engineTick")]
+pub fn main_tick() {
+    app_tick();
+}
+
+#[wasm_bindgen(js_name = "engineGetClock")]
+pub fn main_get_clock() -> JsValue {
+    let clock = app_get_clock().map(|(white_ms, black_ms, running)| ClockInfo {
+        white_ms,
+        black_ms,
+        running: running.as_ref().map(color_to_string),
+    });
+    serde_wasm_bindgen::to_value(&clock).unwrap()
+}
+
+#[wasm_bindgen(js_name = "engineGetResult")]
+pub fn main_get_result() -> Option<String> {
+    app_get_result().map(|result| match result {
+        GameResult::Win(color, reason) => {
+            let winner = match color {
+                Color::White => "White",
+                Color::Black => "Black",
+            };
+            let reason = match reason {
+                WinReason::Checkmate => "checkmate",
+                WinReason::Resignation => "resignation",
+                WinReason::Timeout => "timeout",
+            };
+            format!("{winner} wins by {reason}")
+        }
+        GameResult::Draw(reason) => {
+            let reason = match reason {
+                DrawReason::Stalemate => "stalemate",
+                DrawReason::InsufficientMaterial => "insufficient material",
+                DrawReason::TimeoutVsInsufficientMaterial => "timeout vs insufficient material",
+                DrawReason::Agreement => "agreement",
+                DrawReason::ThreefoldRepetition => "threefold repetition",
+                DrawReason::FiftyMoveRule => "fifty-move rule",
+            };
+            format!("Draw by {reason}")
+        }
+        GameResult::Aborted => "Game aborted".into(),
+    })
+}
+
+#[wasm_bindgen(js_name = "engineResign")]
+pub fn main_resign() -> bool {
+    app_resign()
+}
+
+#[wasm_bindgen(js_name = "engineOfferDraw")]
+pub fn main_offer_draw() -> bool {
+    app_offer_draw()
+}
+
+#[wasm_bindgen(js_name = "engineAcceptDraw")]
+pub fn main_accept_draw() -> bool {
+    app_answer_draw(true)
+}
+
+#[wasm_bindgen(js_name = "engineDeclineDraw")]
+pub fn main_decline_draw() -> bool {
+    app_answer_draw(false)
+}
+
+#[wasm_bindgen(js_name = "engineClaimDraw")]
+pub fn main_claim_draw() -> bool {
+    app_claim_draw()
+}
+
+#[wasm_bindgen(js_name = "engineAbort")]
+pub fn main_abort() -> bool {
+    app_abort()
+}
+
+#[wasm_bindgen(js_name = "engineGetDrawOffer")]
+pub fn main_get_draw_offer() -> Option<String> {
+    app_get_draw_offer().as_ref().map(color_to_string)
+}
+
+#[wasm_bindgen(js_name = "engineGetPgn")]
+pub fn main_get_pgn() -> Option<String> {
+    app_get_pgn()
+}
+
+#[wasm_bindgen(js_name = "engineGetGame")]
+pub fn main_get_game() -> Option<String> {
+    app_get_game_json()
+}
+
+#[wasm_bindgen(js_name = "engineLoadGame")]
+pub fn main_load_game(json: &str) -> bool {
+    app_load_game_json(json)
+}
+
+#[wasm_bindgen(js_name = "engineImportPgn")]
+pub fn main_import_pgn(pgn: &str) -> bool {
+    app_import_pgn(pgn)
+}
+
+#[wasm_bindgen(js_name = "engineNewGame")]
+pub fn main_new_game() {
+    app_new_game();
+}
+
+#[wasm_bindgen(js_name = "engineGetHistory")]
+pub fn main_get_history() -> Vec<String> {
+    app_get_history()
+}
+
+#[wasm_bindgen(js_name = "engineViewPly")]
+pub fn main_view_ply(ply: Option<usize>) {
+    app_view_ply(ply);
+}
+
+#[wasm_bindgen(js_name = "engineGetViewPly")]
+pub fn main_get_view_ply() -> Option<usize> {
+    app_get_view_ply()
+}
+
+#[wasm_bindgen(js_name = "engineFlipBoard")]
+pub fn main_flip_board() {
+    app_flip_board();
+}
+
+#[wasm_bindgen(js_name = "enginePlayAs")]
+pub fn main_play_as(color: &str) {
+    app_set_play_as(color_try_of_str(color));
+}
+
+#[wasm_bindgen(js_name = "engineSetAutoQueen")]
+pub fn main_set_auto_queen(auto_queen: bool) {
+    app_set_auto_queen(auto_queen);
+}
+
+#[wasm_bindgen(js_name = "engineSetCoordinates")]
+pub fn main_set_coordinates(coordinates: &str) {
+    if let Some(coordinates) = coordinates_try_of_str(coordinates) {
+        app_set_coordinates(coordinates);
+    }
+}
+
+#[wasm_bindgen(js_name = "engineSetAnimation")]
+pub fn main_set_animation(animation_ms: u32) {
+    app_set_animation(animation_ms);
+}
+
+#[wasm_bindgen(js_name = "engineGetCaptures")]
+pub fn main_get_captures() -> JsValue {
+    let (white, black, balance) = app_get_captures();
+    let groups_of = |groups: Vec<(&str, usize)>| -> Vec<CaptureGroupInfo> {
+        groups
+            .into_iter()
+            .map(|(piece, count)| CaptureGroupInfo { piece: piece.into(), count })
+            .collect()
+    };
+    let captures = CapturesInfo { white: groups_of(white), black: groups_of(black), balance };
+    serde_wasm_bindgen::to_value(&captures).unwrap()
+}
+
+#[wasm_bindgen(js_name = "engineGetPieceImage")]
+pub fn main_get_piece_image(name: &str) -> Option<String> {
+    app_get_piece_image(name)
+}
@@ -1,15 +1,25 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::HashMap;
 
 use web_sys::{CanvasRenderingContext2d, HtmlImageElement};
 
-use libre_chess_lib::game::{
-    game::Game, mode::standard_chess, rule::init::game_of_mode, selection::Selection,
+use libre_chess_lib::{
+    book::polyglot::PolyglotBook,
+    clock::Clock,
+    color::Color,
+    game::{
+        game::Game,
+        mode::{GameMode, standard_chess},
+        mov::PieceMoveType,
+        rule::init::game_of_mode,
+        selection::Selection,
+    },
+    pos::Pos,
 };
 
 use crate::{
-    board_color::{BoardColor, board_color_purple},
-    board_set::{BoardSet, board_set_normal_1},
-    render::RenderSettings,
+    board_color::{BoardColor, BoardColorPreset, board_color_purple},
+    board_set::{BoardSet, BoardSetPreset, board_set_normal_1},
+    render::{Coordinates, MoveAnimation, RenderSettings},
 };
 
 #[derive(Debug, PartialEq)]
@@ -19,6 +29,8 @@ pub struct Settings {
     pub board_set_id: String,
     pub board_color: BoardColor,
     pub board_color_id: String,
+    pub auto_queen: bool,
+    pub animation_ms: u32,
 }
 
 #[derive(Debug, PartialEq, Clone)]
@@ -37,29 +49,83 @@ pub struct BoardSetFinal {
     pub wr: HtmlImageElement,
 }
 
+#[derive(Debug, PartialEq, Clone)]
+pub struct Drag {
+    pub from: Pos,
+    pub x: f64,
+    pub y: f64,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Premove {
+    pub from: Pos,
+    pub to: Option<Pos>,
+    pub promotion: Option<PieceMoveType>,
+}
+
 #[derive(Debug, PartialEq)]
 pub struct Model {
+    pub mode: GameMode,
     pub game: Game,
     pub settings: Settings,
     pub context: Option<CanvasRenderingContext2d>,
     pub selection: Selection,
     pub board_set: HashMap<String, HtmlImageElement>,
+    pub board_textures: HashMap<String, HtmlImageElement>,
+    pub book: PolyglotBook,
+    pub clock: Option<Clock>,
+    pub view: Option<(usize, Game)>,
+    pub play_as: Option<Color>,
+    pub drag: Option<Drag>,
+    pub promotion: Option<(Pos, Pos)>,
+    pub premove: Option<Premove>,
+    pub annotation_from: Option<Pos>,
+    pub animation: Option<(MoveAnimation, f64)>,
+    pub cursor: Option<Pos>,
+    pub announcement: String,
+    pub custom_board_sets: Vec<(BoardSetPreset, BoardSet)>,
+    pub custom_board_colors: Vec<(BoardColorPreset, BoardColor)>,
 }
 
 impl Default for Model {
     fn default() -> Self {
         Model {
+            mode: standard_chess(),
             game: game_of_mode(standard_chess()),
             settings: Settings {
-                render_settings: RenderSettings { dim: 0 },
+                render_settings: RenderSettings {
+                    dim: 0,
+                    orientation: Color::White,
+                    coordinates: Coordinates::Inside,
+                },
                 board_set: board_set_normal_1(),
                 board_set_id: "normal_1".into(),
                 board_color: board_color_purple(),
                 board_color_id: "purple".into(),
+                auto_queen: false,
+                animation_ms: 200,
             },
             context: None,
-            selection: Selection { selected_squares: HashSet::new(), selected_pos: None },
+            selection: Selection {
+                selected_squares: HashMap::new(),
+                arrows: HashMap::new(),
+                selected_pos: None,
+            },
             board_set: HashMap::new(),
+            board_textures: HashMap::new(),
+            book: PolyglotBook::default(),
+            clock: None,
+            view: None,
+            play_as: None,
+            drag: None,
+            promotion: None,
+            premove: None,
+            annotation_from: None,
+            animation: None,
+            cursor: None,
+            announcement: String::new(),
+            custom_board_sets: Vec::new(),
+            custom_board_colors: Vec::new(),
         }
     }
 }
@@ -1,6 +1,18 @@
 #[derive(Debug, PartialEq, Clone)]
 pub enum Prop {
+    Animation,
+    AutoQueen,
     BoardColor,
     BoardSet,
+    Book,
+    Clock,
+    Coordinates,
+    Cursor,
     Dim,
+    Event,
+    Move,
+    Orientation,
+    PlayAs,
+    Selection,
+    View,
 }
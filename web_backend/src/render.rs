@@ -1,14 +1,47 @@
-use manfredo::cartesian::rect::rect_f64::RectF64;
+use manfredo::{
+    cartesian::rect::rect_f64::RectF64,
+    matrix::rect::rect_u8::{len_col, len_row},
+};
 
 use libre_chess_lib::{
-    game::{board::GameBoard, game::GameBounds},
-    piece::Piece,
-    pos::Pos,
+    color::Color,
+    game::{
+        board::GameBoard,
+        game::{GameBounds, GameHistory},
+        mov::{GameMove, GameMoveType},
+    },
+    piece::{Piece, PieceType},
+    pos::{Pos, col_to_string, row_to_string},
 };
 
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Coordinates {
+    Hidden,
+    Inside,
+    Outside,
+}
+
 #[derive(Debug, PartialEq)]
 pub struct RenderSettings {
     pub dim: u16,
+    pub orientation: Color,
+    pub coordinates: Coordinates,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct CoordinateLabel {
+    pub text: String,
+    pub pos: Pos,
+    pub x: f64,
+    pub y: f64,
+    pub align: &'static str,
+    pub baseline: &'static str,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct MoveAnimation {
+    pub paths: Vec<(Pos, Pos)>,
+    pub captured: Option<(Piece, Pos)>,
 }
 
 #[derive(Debug, PartialEq)]
@@ -17,25 +50,265 @@ pub struct ValueToRender {
     pub rect: RectF64,
 }
 
+pub fn coordinates_try_of_str(s: &str) -> Option<Coordinates> {
+    match s {
+        "hidden" => Some(Coordinates::Hidden),
+        "inside" => Some(Coordinates::Inside),
+        "outside" => Some(Coordinates::Outside),
+        _ => None,
+    }
+}
+
+pub fn coordinates_to_string(coordinates: &Coordinates) -> &'static str {
+    match coordinates {
+        Coordinates::Hidden => "hidden",
+        Coordinates::Inside => "inside",
+        Coordinates::Outside => "outside",
+    }
+}
+
+pub fn cell_size(bounds: &GameBounds, settings: &RenderSettings) -> f64 {
+    let len = f64::from(len_row(bounds).max(len_col(bounds)));
+    match settings.coordinates {
+        Coordinates::Outside => settings.dim as f64 / (len + 1.0),
+        _ => settings.dim as f64 / len,
+    }
+}
+
+pub fn border_size(bounds: &GameBounds, settings: &RenderSettings) -> f64 {
+    match settings.coordinates {
+        Coordinates::Outside => cell_size(bounds, settings) / 2.0,
+        _ => 0.0,
+    }
+}
+
+pub fn square_rect(pos: &Pos, bounds: &GameBounds, settings: &RenderSettings) -> RectF64 {
+    let cell_size = cell_size(bounds, settings);
+    let border_size = border_size(bounds, settings);
+    let (col, row) = match settings.orientation {
+        Color::White => (pos.col - bounds.min.col, bounds.max.row - pos.row),
+        Color::Black => (bounds.max.col - pos.col, pos.row - bounds.min.row),
+    };
+    let x = border_size + (col as f64) * cell_size;
+    let y = border_size + (row as f64) * cell_size;
+    RectF64::of(x, y, x + cell_size, y + cell_size)
+}
+
+pub fn board_rect(bounds: &GameBounds, settings: &RenderSettings) -> RectF64 {
+    let cell_size = cell_size(bounds, settings);
+    let border_size = border_size(bounds, settings);
+    RectF64::of(
+        border_size,
+        border_size,
+        border_size + f64::from(len_col(bounds)) * cell_size,
+        border_size + f64::from(len_row(bounds)) * cell_size,
+    )
+}
+
+pub fn cursor_origin(bounds: &GameBounds, orientation: &Color) -> Pos {
+    match orientation {
+        Color::White => Pos { row: bounds.min.row, col: bounds.min.col },
+        Color::Black => Pos { row: bounds.max.row, col: bounds.max.col },
+    }
+}
+
+pub fn cursor_step(cursor: &Pos, dx: i8, dy: i8, bounds: &GameBounds, orientation: &Color) -> Pos {
+    let (d_col, d_row) = match orientation {
+        Color::White => (i16::from(dx), -i16::from(dy)),
+        Color::Black => (-i16::from(dx), i16::from(dy)),
+    };
+    let step = |value: u8, delta: i16, min: u8, max: u8| {
+        (i16::from(value) + delta).clamp(i16::from(min), i16::from(max)) as u8
+    };
+    Pos {
+        row: step(cursor.row, d_row, bounds.min.row, bounds.max.row),
+        col: step(cursor.col, d_col, bounds.min.col, bounds.max.col),
+    }
+}
+
+pub fn square_of_point(
+    row: u16,
+    col: u16,
+    bounds: &GameBounds,
+    settings: &RenderSettings,
+) -> Option<Pos> {
+    let cell_size = cell_size(bounds, settings);
+    let border_size = border_size(bounds, settings);
+    let cell_row = ((row as f64 - border_size) / cell_size).floor();
+    let cell_col = ((col as f64 - border_size) / cell_size).floor();
+    if cell_row < 0.0
+        || cell_col < 0.0
+        || cell_row >= f64::from(len_row(bounds))
+        || cell_col >= f64::from(len_col(bounds))
+    {
+        return None;
+    }
+    let (cell_row, cell_col) = (cell_row as u8, cell_col as u8);
+    match settings.orientation {
+        Color::White => {
+            Some(Pos { row: bounds.max.row - cell_row, col: bounds.min.col + cell_col })
+        }
+        Color::Black => {
+            Some(Pos { row: bounds.min.row + cell_row, col: bounds.max.col - cell_col })
+        }
+    }
+}
+
+pub fn coordinate_labels(bounds: &GameBounds, settings: &RenderSettings) -> Vec<CoordinateLabel> {
+    let cell_size = cell_size(bounds, settings);
+    let border_size = border_size(bounds, settings);
+    let (bottom_row, left_col) = match settings.orientation {
+        Color::White => (bounds.min.row, bounds.min.col),
+        Color::Black => (bounds.max.row, bounds.max.col),
+    };
+    let files = bounds.iter_col().map(|col| {
+        let pos = Pos { row: bottom_row, col };
+        let rect = square_rect(&pos, bounds, settings);
+        let text = col_to_string(col).to_ascii_lowercase();
+        match settings.coordinates {
+            Coordinates::Outside => CoordinateLabel {
+                text,
+                pos,
+                x: (rect.min.x + rect.max.x) / 2.0,
+                y: rect.max.y + border_size / 2.0,
+                align: "center",
+                baseline: "middle",
+            },
+            _ => CoordinateLabel {
+                text,
+                pos,
+                x: rect.max.x - cell_size / 16.0,
+                y: rect.max.y - cell_size / 16.0,
+                align: "right",
+                baseline: "bottom",
+            },
+        }
+    });
+    let ranks = bounds.iter_row().map(|row| {
+        let pos = Pos { row, col: left_col };
+        let rect = square_rect(&pos, bounds, settings);
+        let text = row_to_string(row);
+        match settings.coordinates {
+            Coordinates::Outside => CoordinateLabel {
+                text,
+                pos,
+                x: rect.min.x - border_size / 2.0,
+                y: (rect.min.y + rect.max.y) / 2.0,
+                align: "center",
+                baseline: "middle",
+            },
+            _ => CoordinateLabel {
+                text,
+                pos,
+                x: rect.min.x + cell_size / 16.0,
+                y: rect.min.y + cell_size / 16.0,
+                align: "left",
+                baseline: "top",
+            },
+        }
+    });
+    match settings.coordinates {
+        Coordinates::Hidden => Vec::new(),
+        _ => files.chain(ranks).collect(),
+    }
+}
+
+pub fn promotion_squares(to: &Pos, bounds: &GameBounds, count: u8) -> Vec<Pos> {
+    (0..count)
+        .filter_map(|i| {
+            let row = if to.row == bounds.max.row { to.row.checked_sub(i)? } else { to.row + i };
+            if row < bounds.min.row || row > bounds.max.row {
+                return None;
+            }
+            Some(Pos { row, col: to.col })
+        })
+        .collect()
+}
+
+pub fn last_move_squares(history: &GameHistory) -> Option<(Pos, Pos)> {
+    let game_move = history.last()?;
+    let from = game_move.mov.from.clone();
+    let to = match game_move.typ {
+        GameMoveType::ShortCastling => Pos { row: from.row, col: 6 },
+        GameMoveType::LongCastling => Pos { row: from.row, col: 2 },
+        _ => game_move.mov.to.clone(),
+    };
+    Some((from, to))
+}
+
+pub fn move_animation_of(game_move: &GameMove) -> Option<MoveAnimation> {
+    let from = &game_move.mov.from;
+    let to = &game_move.mov.to;
+    if from == to {
+        return None;
+    }
+    let row = from.row;
+    let animation = match game_move.typ {
+        GameMoveType::ShortCastling => MoveAnimation {
+            paths: vec![(from.clone(), Pos { row, col: 6 }), (to.clone(), Pos { row, col: 5 })],
+            captured: None,
+        },
+        GameMoveType::LongCastling => MoveAnimation {
+            paths: vec![(from.clone(), Pos { row, col: 2 }), (to.clone(), Pos { row, col: 3 })],
+            captured: None,
+        },
+        GameMoveType::EnPassant => {
+            let color = match game_move.mov.piece.color {
+                Color::White => Color::Black,
+                Color::Black => Color::White,
+            };
+            MoveAnimation {
+                paths: vec![(from.clone(), to.clone())],
+                captured: Some((Piece { typ: PieceType::Pawn, color }, Pos { row, col: to.col })),
+            }
+        }
+        _ => MoveAnimation { paths: vec![(from.clone(), to.clone())], captured: None },
+    };
+    Some(animation)
+}
+
+pub fn animation_progress(elapsed: f64, duration: f64) -> f64 {
+    if duration <= 0.0 {
+        return 1.0;
+    }
+    let t = (elapsed / duration).clamp(0.0, 1.0);
+    if t < 0.5 { 4.0 * t * t * t } else { 1.0 - (-2.0 * t + 2.0).powi(3) / 2.0 }
+}
+
+pub fn interpolate_rect(from: &RectF64, to: &RectF64, t: f64) -> RectF64 {
+    let x = from.min.x + (to.min.x - from.min.x) * t;
+    let y = from.min.y + (to.min.y - from.min.y) * t;
+    RectF64::of(x, y, x + (from.max.x - from.min.x), y + (from.max.y - from.min.y))
+}
+
+pub fn square_center(pos: &Pos, bounds: &GameBounds, settings: &RenderSettings) -> (f64, f64) {
+    let rect = square_rect(pos, bounds, settings);
+    ((rect.min.x + rect.max.x) / 2.0, (rect.min.y + rect.max.y) / 2.0)
+}
+
+pub fn arrow_head(from: (f64, f64), to: (f64, f64), size: f64) -> [(f64, f64); 3] {
+    let len = (to.0 - from.0).hypot(to.1 - from.1);
+    if len == 0.0 {
+        return [to, to, to];
+    }
+    let (dx, dy) = ((to.0 - from.0) / len, (to.1 - from.1) / len);
+    let base = (to.0 - dx * size, to.1 - dy * size);
+    let (nx, ny) = (-dy * size / 2.0, dx * size / 2.0);
+    [to, (base.0 + nx, base.1 + ny), (base.0 - nx, base.1 - ny)]
+}
+
 pub fn get_values_to_render(
     board: &GameBoard,
     bounds: &GameBounds,
     settings: &RenderSettings,
 ) -> Vec<ValueToRender> {
     let mut values_to_render: Vec<ValueToRender> = Vec::new();
-    let cell_size = settings.dim as f64 / 8.0;
     for row in bounds.iter_row() {
         for col in bounds.iter_col() {
-            if let Some(piece) = board.get(&Pos { row, col }) {
-                values_to_render.push(ValueToRender {
-                    piece: *piece,
-                    rect: RectF64::of(
-                        (col as f64) * cell_size,
-                        (settings.dim as f64) - (row as f64) * cell_size - cell_size,
-                        (col as f64) * cell_size + cell_size,
-                        (settings.dim as f64) - (row as f64) * cell_size,
-                    ),
-                });
+            let pos = Pos { row, col };
+            if let Some(piece) = board.get(&pos) {
+                let rect = square_rect(&pos, bounds, settings);
+                values_to_render.push(ValueToRender { piece: *piece, rect });
             }
         }
     }
@@ -51,50 +324,501 @@ pub fn get_values_to_render(
 #[cfg(test)]
 mod tests {
 
-    use libre_chess_lib::game::mode::standard_chess;
+    use libre_chess_lib::game::{
+        game::GameBounds,
+        mode::standard_chess,
+        mov::{GameMove, GameMoveType},
+    };
 
     use super::*;
 
     #[test]
     fn test_get_values_to_render() {
         let mode = standard_chess();
-        let settings = RenderSettings { dim: 987 };
+        let settings = RenderSettings {
+            dim: 987,
+            orientation: Color::White,
+            coordinates: Coordinates::Hidden,
+        };
         assert_eq!(
             get_values_to_render(&mode.initial_board, &mode.bounds, &settings),
             [
-                ValueToRender { piece: Piece::of('♜'), rect: RectF64::of(0.0, 0.0, 123.375, 123.375) },
-                ValueToRender { piece: Piece::of('♞'), rect: RectF64::of(123.375, 0.0, 246.75, 123.375) },
-                ValueToRender { piece: Piece::of('♝'), rect: RectF64::of(246.75, 0.0, 370.125, 123.375) },
-                ValueToRender { piece: Piece::of('♛'), rect: RectF64::of(370.125, 0.0, 493.5, 123.375) },
-                ValueToRender { piece: Piece::of('♚'), rect: RectF64::of(493.5, 0.0, 616.875, 123.375) },
-                ValueToRender { piece: Piece::of('♝'), rect: RectF64::of(616.875, 0.0, 740.25, 123.375) },
-                ValueToRender { piece: Piece::of('♞'), rect: RectF64::of(740.25, 0.0, 863.625, 123.375) },
-                ValueToRender { piece: Piece::of('♜'), rect: RectF64::of(863.625, 0.0, 987.0, 123.375) },
-                ValueToRender { piece: Piece::of('♟'), rect: RectF64::of(0.0, 123.375, 123.375, 246.75) },
-                ValueToRender { piece: Piece::of('♟'), rect: RectF64::of(123.375, 123.375, 246.75, 246.75) },
-                ValueToRender { piece: Piece::of('♟'), rect: RectF64::of(246.75, 123.375, 370.125, 246.75) },
-                ValueToRender { piece: Piece::of('♟'), rect: RectF64::of(370.125, 123.375, 493.5, 246.75) },
-                ValueToRender { piece: Piece::of('♟'), rect: RectF64::of(493.5, 123.375, 616.875, 246.75) },
-                ValueToRender { piece: Piece::of('♟'), rect: RectF64::of(616.875, 123.375, 740.25, 246.75) },
-                ValueToRender { piece: Piece::of('♟'), rect: RectF64::of(740.25, 123.375, 863.625, 246.75) },
-                ValueToRender { piece: Piece::of('♟'), rect: RectF64::of(863.625, 123.375, 987.0, 246.75) },
-                ValueToRender { piece: Piece::of('♙'), rect: RectF64::of(0.0, 740.25, 123.375, 863.625) },
-                ValueToRender { piece: Piece::of('♙'), rect: RectF64::of(123.375, 740.25, 246.75, 863.625) },
-                ValueToRender { piece: Piece::of('♙'), rect: RectF64::of(246.75, 740.25, 370.125, 863.625) },
-                ValueToRender { piece: Piece::of('♙'), rect: RectF64::of(370.125, 740.25, 493.5, 863.625) },
-                ValueToRender { piece: Piece::of('♙'), rect: RectF64::of(493.5, 740.25, 616.875, 863.625) },
-                ValueToRender { piece: Piece::of('♙'), rect: RectF64::of(616.875, 740.25, 740.25, 863.625) },
-                ValueToRender { piece: Piece::of('♙'), rect: RectF64::of(740.25, 740.25, 863.625, 863.625) },
-                ValueToRender { piece: Piece::of('♙'), rect: RectF64::of(863.625, 740.25, 987.0, 863.625) },
-                ValueToRender { piece: Piece::of('♖'), rect: RectF64::of(0.0, 863.625, 123.375, 987.0) },
-                ValueToRender { piece: Piece::of('♘'), rect: RectF64::of(123.375, 863.625, 246.75, 987.0) },
-                ValueToRender { piece: Piece::of('♗'), rect: RectF64::of(246.75, 863.625, 370.125, 987.0) },
-                ValueToRender { piece: Piece::of('♕'), rect: RectF64::of(370.125, 863.625, 493.5, 987.0) },
-                ValueToRender { piece: Piece::of('♔'), rect: RectF64::of(493.5, 863.625, 616.875, 987.0) },
-                ValueToRender { piece: Piece::of('♗'), rect: RectF64::of(616.875, 863.625, 740.25, 987.0) },
-                ValueToRender { piece: Piece::of('♘'), rect: RectF64::of(740.25, 863.625, 863.625, 987.0) },
-                ValueToRender { piece: Piece::of('♖'), rect: RectF64::of(863.625, 863.625, 987.0, 987.0) },
+                ValueToRender {
+                    piece: Piece::of('♜'),
+                    rect: RectF64::of(0.0, 0.0, 123.375, 123.375)
+                },
+                ValueToRender {
+                    piece: Piece::of('♞'),
+                    rect: RectF64::of(123.375, 0.0, 246.75, 123.375)
+                },
+                ValueToRender {
+                    piece: Piece::of('♝'),
+                    rect: RectF64::of(246.75, 0.0, 370.125, 123.375)
+                },
+                ValueToRender {
+                    piece: Piece::of('♛'),
+                    rect: RectF64::of(370.125, 0.0, 493.5, 123.375)
+                },
+                ValueToRender {
+                    piece: Piece::of('♚'),
+                    rect: RectF64::of(493.5, 0.0, 616.875, 123.375)
+                },
+                ValueToRender {
+                    piece: Piece::of('♝'),
+                    rect: RectF64::of(616.875, 0.0, 740.25, 123.375)
+                },
+                ValueToRender {
+                    piece: Piece::of('♞'),
+                    rect: RectF64::of(740.25, 0.0, 863.625, 123.375)
+                },
+                ValueToRender {
+                    piece: Piece::of('♜'),
+                    rect: RectF64::of(863.625, 0.0, 987.0, 123.375)
+                },
+                ValueToRender {
+                    piece: Piece::of('♟'),
+                    rect: RectF64::of(0.0, 123.375, 123.375, 246.75)
+                },
+                ValueToRender {
+                    piece: Piece::of('♟'),
+                    rect: RectF64::of(123.375, 123.375, 246.75, 246.75)
+                },
+                ValueToRender {
+                    piece: Piece::of('♟'),
+                    rect: RectF64::of(246.75, 123.375, 370.125, 246.75)
+                },
+                ValueToRender {
+                    piece: Piece::of('♟'),
+                    rect: RectF64::of(370.125, 123.375, 493.5, 246.75)
+                },
+                ValueToRender {
+                    piece: Piece::of('♟'),
+                    rect: RectF64::of(493.5, 123.375, 616.875, 246.75)
+                },
+                ValueToRender {
+                    piece: Piece::of('♟'),
+                    rect: RectF64::of(616.875, 123.375, 740.25, 246.75)
+                },
+                ValueToRender {
+                    piece: Piece::of('♟'),
+                    rect: RectF64::of(740.25, 123.375, 863.625, 246.75)
+                },
+                ValueToRender {
+                    piece: Piece::of('♟'),
+                    rect: RectF64::of(863.625, 123.375, 987.0, 246.75)
+                },
+                ValueToRender {
+                    piece: Piece::of('♙'),
+                    rect: RectF64::of(0.0, 740.25, 123.375, 863.625)
+                },
+                ValueToRender {
+                    piece: Piece::of('♙'),
+                    rect: RectF64::of(123.375, 740.25, 246.75, 863.625)
+                },
+                ValueToRender {
+                    piece: Piece::of('♙'),
+                    rect: RectF64::of(246.75, 740.25, 370.125, 863.625)
+                },
+                ValueToRender {
+                    piece: Piece::of('♙'),
+                    rect: RectF64::of(370.125, 740.25, 493.5, 863.625)
+                },
+                ValueToRender {
+                    piece: Piece::of('♙'),
+                    rect: RectF64::of(493.5, 740.25, 616.875, 863.625)
+                },
+                ValueToRender {
+                    piece: Piece::of('♙'),
+                    rect: RectF64::of(616.875, 740.25, 740.25, 863.625)
+                },
+                ValueToRender {
+                    piece: Piece::of('♙'),
+                    rect: RectF64::of(740.25, 740.25, 863.625, 863.625)
+                },
+                ValueToRender {
+                    piece: Piece::of('♙'),
+                    rect: RectF64::of(863.625, 740.25, 987.0, 863.625)
+                },
+                ValueToRender {
+                    piece: Piece::of('♖'),
+                    rect: RectF64::of(0.0, 863.625, 123.375, 987.0)
+                },
+                ValueToRender {
+                    piece: Piece::of('♘'),
+                    rect: RectF64::of(123.375, 863.625, 246.75, 987.0)
+                },
+                ValueToRender {
+                    piece: Piece::of('♗'),
+                    rect: RectF64::of(246.75, 863.625, 370.125, 987.0)
+                },
+                ValueToRender {
+                    piece: Piece::of('♕'),
+                    rect: RectF64::of(370.125, 863.625, 493.5, 987.0)
+                },
+                ValueToRender {
+                    piece: Piece::of('♔'),
+                    rect: RectF64::of(493.5, 863.625, 616.875, 987.0)
+                },
+                ValueToRender {
+                    piece: Piece::of('♗'),
+                    rect: RectF64::of(616.875, 863.625, 740.25, 987.0)
+                },
+                ValueToRender {
+                    piece: Piece::of('♘'),
+                    rect: RectF64::of(740.25, 863.625, 863.625, 987.0)
+                },
+                ValueToRender {
+                    piece: Piece::of('♖'),
+                    rect: RectF64::of(863.625, 863.625, 987.0, 987.0)
+                },
             ]
         );
     }
+
+    #[test]
+    fn test_get_values_to_render_black_orientation() {
+        let mode = standard_chess();
+        let settings = RenderSettings {
+            dim: 800,
+            orientation: Color::Black,
+            coordinates: Coordinates::Hidden,
+        };
+        let values = get_values_to_render(&mode.initial_board, &mode.bounds, &settings);
+        assert_eq!(values.len(), 32);
+        assert_eq!(
+            values[0],
+            ValueToRender { piece: Piece::of('♖'), rect: RectF64::of(0.0, 0.0, 100.0, 100.0) }
+        );
+        assert_eq!(
+            values[3],
+            ValueToRender { piece: Piece::of('♔'), rect: RectF64::of(300.0, 0.0, 400.0, 100.0) }
+        );
+        assert_eq!(
+            values[31],
+            ValueToRender {
+                piece: Piece::of('♜'), rect: RectF64::of(700.0, 700.0, 800.0, 800.0)
+            }
+        );
+    }
+
+    #[test]
+    fn test_square_rect() {
+        let bounds = GameBounds::of(0, 0, 7, 7);
+        let white = RenderSettings {
+            dim: 800,
+            orientation: Color::White,
+            coordinates: Coordinates::Hidden,
+        };
+        let black = RenderSettings {
+            dim: 800,
+            orientation: Color::Black,
+            coordinates: Coordinates::Hidden,
+        };
+        assert_eq!(
+            square_rect(&Pos { row: 0, col: 0 }, &bounds, &white),
+            RectF64::of(0.0, 700.0, 100.0, 800.0)
+        );
+        assert_eq!(
+            square_rect(&Pos { row: 0, col: 0 }, &bounds, &black),
+            RectF64::of(700.0, 0.0, 800.0, 100.0)
+        );
+        assert_eq!(
+            square_rect(&Pos { row: 3, col: 4 }, &bounds, &white),
+            RectF64::of(400.0, 400.0, 500.0, 500.0)
+        );
+        assert_eq!(
+            square_rect(&Pos { row: 3, col: 4 }, &bounds, &black),
+            RectF64::of(300.0, 300.0, 400.0, 400.0)
+        );
+    }
+
+    #[test]
+    fn test_board_rect() {
+        let bounds = GameBounds::of(0, 0, 7, 7);
+        let hidden = RenderSettings {
+            dim: 800,
+            orientation: Color::White,
+            coordinates: Coordinates::Hidden,
+        };
+        let outside = RenderSettings {
+            dim: 900,
+            orientation: Color::Black,
+            coordinates: Coordinates::Outside,
+        };
+        assert_eq!(board_rect(&bounds, &hidden), RectF64::of(0.0, 0.0, 800.0, 800.0));
+        assert_eq!(board_rect(&bounds, &outside), RectF64::of(50.0, 50.0, 850.0, 850.0));
+    }
+
+    #[test]
+    fn test_cursor() {
+        let bounds = GameBounds::of(0, 0, 7, 7);
+        let a1 = cursor_origin(&bounds, &Color::White);
+        let h8 = cursor_origin(&bounds, &Color::Black);
+        assert_eq!(a1, Pos { row: 0, col: 0 });
+        assert_eq!(h8, Pos { row: 7, col: 7 });
+        assert_eq!(cursor_step(&a1, 0, -1, &bounds, &Color::White), Pos { row: 1, col: 0 });
+        assert_eq!(cursor_step(&a1, 1, 0, &bounds, &Color::White), Pos { row: 0, col: 1 });
+        assert_eq!(cursor_step(&a1, -1, 1, &bounds, &Color::White), a1);
+        assert_eq!(cursor_step(&h8, 0, -1, &bounds, &Color::Black), Pos { row: 6, col: 7 });
+        assert_eq!(cursor_step(&h8, 1, 0, &bounds, &Color::Black), Pos { row: 7, col: 6 });
+        assert_eq!(cursor_step(&h8, -1, 1, &bounds, &Color::Black), h8);
+    }
+
+    #[test]
+    fn test_square_of_point() {
+        let bounds = GameBounds::of(0, 0, 7, 7);
+        let white = RenderSettings {
+            dim: 800,
+            orientation: Color::White,
+            coordinates: Coordinates::Hidden,
+        };
+        let black = RenderSettings {
+            dim: 800,
+            orientation: Color::Black,
+            coordinates: Coordinates::Hidden,
+        };
+        assert_eq!(square_of_point(750, 50, &bounds, &white), Some(Pos { row: 0, col: 0 }));
+        assert_eq!(square_of_point(50, 750, &bounds, &black), Some(Pos { row: 0, col: 0 }));
+        assert_eq!(square_of_point(0, 0, &bounds, &white), Some(Pos { row: 7, col: 0 }));
+        assert_eq!(square_of_point(0, 0, &bounds, &black), Some(Pos { row: 0, col: 7 }));
+        assert_eq!(square_of_point(800, 0, &bounds, &white), None);
+        assert_eq!(square_of_point(0, 800, &bounds, &black), None);
+    }
+
+    #[test]
+    fn test_non_standard_bounds() {
+        let bounds = GameBounds::of(2, 1, 11, 5);
+        let white = RenderSettings {
+            dim: 500,
+            orientation: Color::White,
+            coordinates: Coordinates::Hidden,
+        };
+        let black = RenderSettings {
+            dim: 500,
+            orientation: Color::Black,
+            coordinates: Coordinates::Hidden,
+        };
+        assert_eq!(cell_size(&bounds, &white), 50.0);
+        assert_eq!(
+            square_rect(&Pos { row: 2, col: 1 }, &bounds, &white),
+            RectF64::of(0.0, 450.0, 50.0, 500.0)
+        );
+        assert_eq!(
+            square_rect(&Pos { row: 2, col: 1 }, &bounds, &black),
+            RectF64::of(200.0, 0.0, 250.0, 50.0)
+        );
+        assert_eq!(
+            square_rect(&Pos { row: 11, col: 5 }, &bounds, &white),
+            RectF64::of(200.0, 0.0, 250.0, 50.0)
+        );
+        assert_eq!(square_of_point(499, 0, &bounds, &white), Some(Pos { row: 2, col: 1 }));
+        assert_eq!(square_of_point(0, 249, &bounds, &white), Some(Pos { row: 11, col: 5 }));
+        assert_eq!(square_of_point(0, 249, &bounds, &black), Some(Pos { row: 2, col: 1 }));
+        assert_eq!(square_of_point(0, 250, &bounds, &white), None);
+        assert_eq!(square_of_point(0, 250, &bounds, &black), None);
+    }
+
+    #[test]
+    fn test_promotion_squares() {
+        let bounds = GameBounds::of(0, 0, 7, 7);
+        assert_eq!(
+            promotion_squares(&Pos { row: 7, col: 2 }, &bounds, 4),
+            [
+                Pos { row: 7, col: 2 },
+                Pos { row: 6, col: 2 },
+                Pos { row: 5, col: 2 },
+                Pos { row: 4, col: 2 }
+            ]
+        );
+        assert_eq!(
+            promotion_squares(&Pos { row: 0, col: 5 }, &bounds, 4),
+            [
+                Pos { row: 0, col: 5 },
+                Pos { row: 1, col: 5 },
+                Pos { row: 2, col: 5 },
+                Pos { row: 3, col: 5 }
+            ]
+        );
+        assert_eq!(
+            promotion_squares(&Pos { row: 2, col: 0 }, &GameBounds::of(0, 0, 2, 2), 4),
+            [Pos { row: 2, col: 0 }, Pos { row: 1, col: 0 }, Pos { row: 0, col: 0 }]
+        );
+    }
+
+    #[test]
+    fn test_last_move_squares() {
+        assert_eq!(last_move_squares(&Vec::new()), None);
+        assert_eq!(
+            last_move_squares(&vec![GameMove::default_of('♙', "E2", "E4")]),
+            Some((Pos { row: 1, col: 4 }, Pos { row: 3, col: 4 }))
+        );
+        assert_eq!(
+            last_move_squares(&vec![GameMove::of('♔', "E1", "H1", GameMoveType::ShortCastling)]),
+            Some((Pos { row: 0, col: 4 }, Pos { row: 0, col: 6 }))
+        );
+        assert_eq!(
+            last_move_squares(&vec![GameMove::of('♚', "E8", "A8", GameMoveType::LongCastling)]),
+            Some((Pos { row: 7, col: 4 }, Pos { row: 7, col: 2 }))
+        );
+    }
+
+    #[test]
+    fn test_arrow() {
+        let bounds = standard_chess().bounds;
+        let settings = RenderSettings {
+            dim: 800,
+            orientation: Color::White,
+            coordinates: Coordinates::Hidden,
+        };
+        assert_eq!(square_center(&Pos { row: 0, col: 0 }, &bounds, &settings), (50.0, 750.0));
+        assert_eq!(square_center(&Pos { row: 7, col: 2 }, &bounds, &settings), (250.0, 50.0));
+        assert_eq!(
+            arrow_head((50.0, 750.0), (250.0, 750.0), 20.0),
+            [(250.0, 750.0), (230.0, 760.0), (230.0, 740.0)]
+        );
+        assert_eq!(
+            arrow_head((50.0, 750.0), (50.0, 750.0), 20.0),
+            [(50.0, 750.0), (50.0, 750.0), (50.0, 750.0)]
+        );
+    }
+
+    #[test]
+    fn test_coordinates() {
+        let bounds = standard_chess().bounds;
+        let hidden = RenderSettings {
+            dim: 900,
+            orientation: Color::White,
+            coordinates: Coordinates::Hidden,
+        };
+        let inside = RenderSettings {
+            dim: 800,
+            orientation: Color::White,
+            coordinates: Coordinates::Inside,
+        };
+        let outside = RenderSettings {
+            dim: 900,
+            orientation: Color::Black,
+            coordinates: Coordinates::Outside,
+        };
+        assert_eq!(coordinates_try_of_str("outside"), Some(Coordinates::Outside));
+        assert_eq!(coordinates_try_of_str("border"), None);
+        assert_eq!(coordinates_to_string(&Coordinates::Inside), "inside");
+        assert_eq!(coordinate_labels(&bounds, &hidden), Vec::new());
+        assert_eq!(cell_size(&bounds, &outside), 100.0);
+        assert_eq!(border_size(&bounds, &outside), 50.0);
+        assert_eq!(border_size(&bounds, &inside), 0.0);
+        assert_eq!(
+            square_rect(&Pos { row: 0, col: 7 }, &bounds, &outside),
+            RectF64::of(50.0, 50.0, 150.0, 150.0)
+        );
+        assert_eq!(square_of_point(40, 60, &bounds, &outside), None);
+        assert_eq!(square_of_point(60, 40, &bounds, &outside), None);
+        assert_eq!(square_of_point(60, 60, &bounds, &outside), Some(Pos { row: 0, col: 7 }));
+        assert_eq!(square_of_point(849, 849, &bounds, &outside), Some(Pos { row: 7, col: 0 }));
+        assert_eq!(square_of_point(860, 449, &bounds, &outside), None);
+        let labels = coordinate_labels(&bounds, &inside);
+        assert_eq!(labels.len(), 16);
+        assert_eq!(
+            labels[0],
+            CoordinateLabel {
+                text: "a".into(),
+                pos: Pos { row: 0, col: 0 },
+                x: 93.75,
+                y: 793.75,
+                align: "right",
+                baseline: "bottom",
+            }
+        );
+        assert_eq!(
+            labels[8],
+            CoordinateLabel {
+                text: "1".into(),
+                pos: Pos { row: 0, col: 0 },
+                x: 6.25,
+                y: 706.25,
+                align: "left",
+                baseline: "top",
+            }
+        );
+        let labels = coordinate_labels(&bounds, &outside);
+        assert_eq!(
+            labels[0],
+            CoordinateLabel {
+                text: "a".into(),
+                pos: Pos { row: 7, col: 0 },
+                x: 800.0,
+                y: 875.0,
+                align: "center",
+                baseline: "middle",
+            }
+        );
+        assert_eq!(
+            labels[15],
+            CoordinateLabel {
+                text: "8".into(),
+                pos: Pos { row: 7, col: 7 },
+                x: 25.0,
+                y: 800.0,
+                align: "center",
+                baseline: "middle",
+            }
+        );
+    }
+
+    #[test]
+    fn test_move_animation_of() {
+        assert_eq!(move_animation_of(&GameMove::default_of('♙', "A1", "A1")), None);
+        assert_eq!(
+            move_animation_of(&GameMove::capture_of('♘', "G1", "F3")),
+            Some(MoveAnimation {
+                paths: vec![(Pos { row: 0, col: 6 }, Pos { row: 2, col: 5 })],
+                captured: None,
+            })
+        );
+        assert_eq!(
+            move_animation_of(&GameMove::of('♔', "E1", "H1", GameMoveType::ShortCastling)),
+            Some(MoveAnimation {
+                paths: vec![
+                    (Pos { row: 0, col: 4 }, Pos { row: 0, col: 6 }),
+                    (Pos { row: 0, col: 7 }, Pos { row: 0, col: 5 }),
+                ],
+                captured: None,
+            })
+        );
+        assert_eq!(
+            move_animation_of(&GameMove::of('♚', "E8", "A8", GameMoveType::LongCastling)),
+            Some(MoveAnimation {
+                paths: vec![
+                    (Pos { row: 7, col: 4 }, Pos { row: 7, col: 2 }),
+                    (Pos { row: 7, col: 0 }, Pos { row: 7, col: 3 }),
+                ],
+                captured: None,
+            })
+        );
+        assert_eq!(
+            move_animation_of(&GameMove::en_passant_of('♙', "E5", "D6")),
+            Some(MoveAnimation {
+                paths: vec![(Pos { row: 4, col: 4 }, Pos { row: 5, col: 3 })],
+                captured: Some((Piece::of('♟'), Pos { row: 4, col: 3 })),
+            })
+        );
+    }
+
+    #[test]
+    fn test_animation_progress() {
+        assert_eq!(animation_progress(0.0, 200.0), 0.0);
+        assert_eq!(animation_progress(50.0, 200.0), 0.0625);
+        assert_eq!(animation_progress(100.0, 200.0), 0.5);
+        assert_eq!(animation_progress(150.0, 200.0), 0.9375);
+        assert_eq!(animation_progress(300.0, 200.0), 1.0);
+        assert_eq!(animation_progress(10.0, 0.0), 1.0);
+        assert_eq!(
+            interpolate_rect(
+                &RectF64::of(0.0, 0.0, 100.0, 100.0),
+                &RectF64::of(200.0, 400.0, 300.0, 500.0),
+                0.5
+            ),
+            RectF64::of(100.0, 200.0, 200.0, 300.0)
+        );
+    }
 }
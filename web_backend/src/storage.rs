@@ -0,0 +1,39 @@
+use serde::{Serialize, de::DeserializeOwned};
+use web_sys::{Storage, js_sys, window};
+
+pub const GAME_KEY: &str = "libre_chess.game";
+pub const BOARD_SET_KEY: &str = "libre_chess.board_set";
+pub const BOARD_COLOR_KEY: &str = "libre_chess.board_color";
+pub const AUTO_QUEEN_KEY: &str = "libre_chess.auto_queen";
+pub const ANIMATION_KEY: &str = "libre_chess.animation";
+pub const COORDINATES_KEY: &str = "libre_chess.coordinates";
+pub const CUSTOM_BOARD_SETS_KEY: &str = "libre_chess.custom_board_sets";
+pub const CUSTOM_BOARD_COLORS_KEY: &str = "libre_chess.custom_board_colors";
+
+fn local_storage() -> Option<Storage> {
+    window()?.local_storage().ok()?
+}
+
+pub fn storage_get(key: &str) -> Option<String> {
+    local_storage()?.get_item(key).ok()?
+}
+
+pub fn storage_set(key: &str, value: &str) {
+    if let Some(storage) = local_storage() {
+        let _ = storage.set_item(key, value);
+    }
+}
+
+pub fn storage_get_json<T: DeserializeOwned>(key: &str) -> Option<T> {
+    let value = js_sys::JSON::parse(&storage_get(key)?).ok()?;
+    serde_wasm_bindgen::from_value(value).ok()
+}
+
+pub fn storage_set_json<T: Serialize>(key: &str, value: &T) {
+    if let Ok(value) = serde_wasm_bindgen::to_value(value)
+        && let Ok(json) = js_sys::JSON::stringify(&value)
+        && let Some(json) = json.as_string()
+    {
+        storage_set(key, &json);
+    }
+}
@@ -0,0 +1,11 @@
+use libre_chess_lib::time::TimeSource;
+use web_sys::js_sys::Date;
+
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct DateTimeSource;
+
+impl TimeSource for DateTimeSource {
+    fn now_ms(&self) -> u64 {
+        Date::now() as u64
+    }
+}